@@ -0,0 +1,130 @@
+// Criterion benchmarks for lexing, parsing and formatting over a few
+// representative inputs. Run with `cargo bench`.
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use jp::{parse, parse_value, serialize_pretty, tokenize, FormatOptions, ParseOptions};
+
+// Count allocations, so changes that only avoid allocating show up too
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn flat_object(keys: usize) -> String {
+    let entries = (0..keys)
+        .map(|i| format!(r#""key{}": "value {}""#, i, i))
+        .collect::<Vec<_>>();
+    format!("{{{}}}", entries.join(", "))
+}
+
+// Many small records, where the cost per object matters more than per key
+fn records(count: usize) -> String {
+    let records = (0..count)
+        .map(|i| format!(r#"{{"id": {}, "name": "user {}", "active": true}}"#, i, i))
+        .collect::<Vec<_>>();
+    format!("[{}]", records.join(","))
+}
+
+fn deeply_nested(depth: usize) -> String {
+    let mut input = String::new();
+    for i in 0..depth {
+        input.push_str(&format!(r#"{{"level": {}, "child": "#, i));
+    }
+    input.push_str("null");
+    input.push_str(&"}".repeat(depth));
+    input
+}
+
+fn number_array(len: usize) -> String {
+    let numbers = (0..len)
+        .map(|i| format!("{}.{}e{}", i, i % 97, i % 7))
+        .collect::<Vec<_>>();
+    format!("[{}]", numbers.join(","))
+}
+
+// Long strings with escapes and non-ASCII text spread through them, so both
+// the scan for the closing quote and escape decoding are exercised
+fn long_strings(count: usize, len: usize) -> String {
+    let text = "caf\u{e9} \\n \\\"quoted\\\" ".repeat(len / 20);
+    let strings = (0..count)
+        .map(|_| format!("\"{}\"", text))
+        .collect::<Vec<_>>();
+    format!("[{}]", strings.join(","))
+}
+
+fn inputs() -> Vec<(&'static str, String)> {
+    vec![
+        ("flat object", flat_object(5_000)),
+        // Large enough that a scan per key for duplicates would dominate
+        ("large object", flat_object(100_000)),
+        ("records", records(20_000)),
+        // Stays below the parser's default nesting limit
+        ("deeply nested", deeply_nested(120)),
+        ("number array", number_array(100_000)),
+        ("long strings", long_strings(200, 20_000)),
+    ]
+}
+
+// Time `f` over each input as one group, after printing the allocations made
+// by one run, so changes that only avoid allocating show up too
+fn bench(c: &mut Criterion, group: &str, inputs: &[(&str, String)], f: impl Fn(&str)) {
+    for (name, input) in inputs {
+        let before = ALLOCATIONS.load(Ordering::Relaxed);
+        f(input);
+        let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+        println!("{}/{}: {} allocations", group, name, allocations);
+    }
+    let mut group = c.benchmark_group(group);
+    group.sample_size(20);
+    for (name, input) in inputs {
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(name),
+            input.as_str(),
+            |b, input| b.iter(|| f(black_box(input))),
+        );
+    }
+    group.finish();
+}
+
+fn benches(c: &mut Criterion) {
+    let inputs = inputs();
+    let parse_options = ParseOptions::default();
+    let format_options = FormatOptions::default();
+
+    bench(c, "lex", &inputs, |input| {
+        black_box(tokenize(input).unwrap());
+    });
+    bench(c, "parse", &inputs, |input| {
+        black_box(parse_value(input).unwrap());
+    });
+    bench(c, "format", &inputs, |input| {
+        black_box(parse(input, &parse_options, &format_options).unwrap());
+    });
+    bench(c, "serialize", &inputs, |input| {
+        let value = parse_value(input).unwrap();
+        black_box(serialize_pretty(&value, &format_options));
+    });
+}
+
+criterion_group!(parse_benches, benches);
+criterion_main!(parse_benches);
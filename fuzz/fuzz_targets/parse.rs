@@ -0,0 +1,39 @@
+// Feed arbitrary input through the lexer, parser and formatters. Any input may
+// be rejected with an `Err`, but none may panic.
+#![no_main]
+
+use jp::{
+    format_value, parse, parse_value_with, validate, validate_all, validate_reader, FormatOptions,
+    Lexer, ParseOptions,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    // The reader lexer takes raw bytes, invalid UTF-8 included
+    let lenient = ParseOptions {
+        allow_comments: true,
+        allow_trailing_commas: true,
+        ..ParseOptions::default()
+    };
+    let _ = validate_reader(data, &lenient);
+
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    for options in [ParseOptions::default(), lenient] {
+        let _ = Lexer::from(input)
+            .allow_comments(options.allow_comments)
+            .lex_all();
+        let _ = validate(input, &options);
+        let _ = validate_all(input, &options);
+        let _ = parse(input, &options, &FormatOptions::default());
+        if let Ok(value) = parse_value_with(input, &options) {
+            let narrow = FormatOptions {
+                line_width: Some(20),
+                depth: Some(3),
+                ..FormatOptions::default()
+            };
+            let _ = format_value(&value, &narrow);
+        }
+    }
+});
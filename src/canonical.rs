@@ -0,0 +1,138 @@
+use crate::escape;
+use crate::types::{Error, JsonValue};
+
+// Serialize a value in the canonical form of RFC 8785 (JCS), so equal documents
+// produce identical bytes:
+// - no whitespace between tokens
+// - object keys sorted by their UTF-16 code units
+// - strings escape only `"`, `\` and control characters, using the short forms
+//   \b \t \n \f \r where they exist and \u00xx otherwise
+// - numbers written as ECMAScript would print the nearest f64: `1.0` becomes
+//   `1`, `-0` becomes `0`, and exponents are used below 1e-6 and from 1e21
+// Numbers too large for an f64 have no canonical form and are an error.
+pub fn to_canonical(value: &JsonValue) -> Result<String, Error> {
+    let mut out = String::new();
+    write_value(&mut out, value)?;
+    Ok(out)
+}
+
+fn write_value(out: &mut String, value: &JsonValue) -> Result<(), Error> {
+    match value {
+        JsonValue::Null => out.push_str("null"),
+        JsonValue::Bool(bool) => out.push_str(if *bool { "true" } else { "false" }),
+        JsonValue::Number(number) => {
+            out.push_str(&number_to_string(number.as_f64()).ok_or_else(|| {
+                Error::Unsupported(format!(
+                    "Number {} has no canonical form: it is out of range for a double",
+                    number.raw()
+                ))
+            })?)
+        }
+        JsonValue::String(string) => out.push_str(&escape::quote(string, false)),
+        JsonValue::Array(array) => {
+            out.push('[');
+            for (i, item) in array.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, item)?;
+            }
+            out.push(']');
+        }
+        JsonValue::Object(object) => {
+            let mut entries = object.iter().collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&escape::quote(key, false));
+                out.push(':');
+                write_value(out, item)?;
+            }
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+// ECMAScript's Number::toString, built from the shortest digits that round-trip
+fn number_to_string(value: f64) -> Option<String> {
+    if !value.is_finite() {
+        return None;
+    }
+    if value == 0.0 {
+        return Some("0".to_string());
+    }
+
+    // `{:e}` gives the shortest round-trip digits, e.g. 1.2345e-7
+    let scientific = format!("{:e}", value.abs());
+    let (mantissa, exponent) = scientific.split_once('e')?;
+    let digits = mantissa.replace('.', "");
+    let k = digits.len() as i32;
+    // The value is 0.DIGITS × 10^n
+    let n = exponent.parse::<i32>().ok()? + 1;
+
+    let mut out = String::new();
+    if value < 0.0 {
+        out.push('-');
+    }
+    if k <= n && n <= 21 {
+        out.push_str(&digits);
+        out.push_str(&"0".repeat((n - k) as usize));
+    } else if 0 < n && n <= 21 {
+        out.push_str(&digits[..n as usize]);
+        out.push('.');
+        out.push_str(&digits[n as usize..]);
+    } else if -6 < n && n <= 0 {
+        out.push_str("0.");
+        out.push_str(&"0".repeat(-n as usize));
+        out.push_str(&digits);
+    } else {
+        out.push_str(&digits[..1]);
+        if k > 1 {
+            out.push('.');
+            out.push_str(&digits[1..]);
+        }
+        out.push('e');
+        out.push(if n > 0 { '+' } else { '-' });
+        out.push_str(&(n - 1).abs().to_string());
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_value;
+
+    fn canonical(input: &str) -> String {
+        to_canonical(&parse_value(input).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn equal_documents_canonicalize_identically() {
+        let a = canonical(r#"{"b": [1.0, "x"], "a": {"d": true, "c": null}}"#);
+        let b =
+            canonical("{\n  \"a\": {\"c\": null, \"d\": true},\n  \"b\": [1e0, \"\\u0078\"]\n}");
+        assert_eq!(a, b);
+        assert_eq!(a, r#"{"a":{"c":null,"d":true},"b":[1,"x"]}"#);
+    }
+    #[test]
+    fn canonical_numbers_strings_and_key_order() {
+        let numbers = "[0, -0, 1.50, 100, 1e21, 1e20, 123e-9, 0.000001, -1.5e-7, 4.5, 2e-3, 1E400]";
+        let value = parse_value(numbers).unwrap();
+        assert!(to_canonical(&value).is_err());
+        assert_eq!(
+            canonical(&numbers.replace(", 1E400", "")),
+            "[0,0,1.5,100,1e+21,100000000000000000000,1.23e-7,0.000001,-1.5e-7,4.5,0.002]"
+        );
+        assert_eq!(canonical(r#""\u00e9\/\u001f\n""#), "\"\u{e9}/\\u001f\\n\"");
+        // U+1F600 sorts before U+FF61 in UTF-16, though not by code point
+        assert_eq!(
+            canonical(r#"{"\uff61": 1, "\ud83d\ude00": 2, "b": 3, "a": 4}"#),
+            "{\"a\":4,\"b\":3,\"\u{1F600}\":2,\"\u{ff61}\":1}"
+        );
+    }
+}
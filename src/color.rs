@@ -0,0 +1,64 @@
+use crate::options::Theme;
+
+pub const RESET: &str = "\x1b[0m";
+pub const KEY: &str = "\x1b[34;1m";
+pub const STRING: &str = "\x1b[32m";
+pub const NUMBER: &str = "\x1b[33m";
+pub const BOOL: &str = "\x1b[35m";
+pub const NULL: &str = "\x1b[90m";
+
+// ANSI codes for keys, strings, numbers, booleans and null, in that order
+type Palette = [&'static str; 5];
+
+const DEFAULT: Palette = [KEY, STRING, NUMBER, BOOL, NULL];
+// Bright variants, which stay readable on a dark background
+const DARK: Palette = ["\x1b[94;1m", "\x1b[92m", "\x1b[93m", "\x1b[95m", "\x1b[37m"];
+// No yellow or light gray, which fade into a white background
+const LIGHT: Palette = ["\x1b[34;1m", "\x1b[32m", "\x1b[31m", "\x1b[35m", "\x1b[2m"];
+
+// What a piece of output is, for choosing its ANSI color or HTML class
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Highlight {
+    Key,
+    String,
+    Number,
+    Bool,
+    Null,
+    Punct,
+}
+
+impl Highlight {
+    // Punctuation is never colored in the terminal, and nothing is with
+    // `Theme::None`
+    pub fn code(self, theme: Theme) -> Option<&'static str> {
+        let palette = match theme {
+            Theme::Default => DEFAULT,
+            Theme::Dark => DARK,
+            Theme::Light => LIGHT,
+            Theme::None => return None,
+        };
+        match self {
+            Self::Key => Some(palette[0]),
+            Self::String => Some(palette[1]),
+            Self::Number => Some(palette[2]),
+            Self::Bool => Some(palette[3]),
+            Self::Null => Some(palette[4]),
+            Self::Punct => None,
+        }
+    }
+    pub fn class(self) -> &'static str {
+        match self {
+            Self::Key => "json-key",
+            Self::String => "json-string",
+            Self::Number => "json-number",
+            Self::Bool => "json-bool",
+            Self::Null => "json-null",
+            Self::Punct => "json-punct",
+        }
+    }
+}
+
+// Wrap text in the given ANSI escape code
+pub fn paint(code: &str, text: &str) -> String {
+    format!("{}{}{}", code, text, RESET)
+}
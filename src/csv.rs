@@ -0,0 +1,134 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::options::{FormatOptions, Style};
+use crate::serializer::serialize_pretty;
+use crate::types::{Error, JsonValue};
+
+// Render an array of objects as CSV: a header row from the union of keys in
+// first-seen order, then one row per object. Missing keys and nulls become
+// empty cells, and nested values are written as compact JSON.
+pub fn to_csv(value: &JsonValue) -> Result<String, Error> {
+    let Some(rows) = value.as_array() else {
+        return Err(Error::Unsupported(format!(
+            "CSV output requires a top-level array of objects, found {}",
+            type_name(value)
+        )));
+    };
+
+    let mut objects = Vec::with_capacity(rows.len());
+    for (i, row) in rows.iter().enumerate() {
+        match row.as_object() {
+            Some(object) => objects.push(object),
+            None => {
+                return Err(Error::Unsupported(format!(
+                    "CSV output requires every array element to be an object, found {} at index {}",
+                    type_name(row),
+                    i
+                )))
+            }
+        }
+    }
+
+    // The column of each key, so rows are laid out without a scan per cell
+    let mut header: Vec<&str> = Vec::new();
+    let mut columns: HashMap<&str, usize> = HashMap::new();
+    for object in &objects {
+        for (key, _) in object.iter() {
+            columns.entry(key).or_insert_with(|| {
+                header.push(key);
+                header.len() - 1
+            });
+        }
+    }
+
+    let mut lines = vec![header
+        .iter()
+        .map(|key| quote(key))
+        .collect::<Vec<_>>()
+        .join(",")];
+    for object in &objects {
+        let mut cells: Vec<Option<&JsonValue>> = vec![None; header.len()];
+        for (key, value) in object.iter() {
+            // A repeated key keeps its first value
+            cells[columns[key.as_ref()]].get_or_insert(value);
+        }
+        let cells = cells
+            .into_iter()
+            .map(|value| value.map(cell).unwrap_or_default());
+        lines.push(cells.collect::<Vec<_>>().join(","));
+    }
+    Ok(lines.join("\n"))
+}
+
+fn cell(value: &JsonValue) -> String {
+    let text = match value {
+        JsonValue::Null => Cow::Borrowed(""),
+        JsonValue::String(string) => Cow::Borrowed(string.as_ref()),
+        JsonValue::Array(_) | JsonValue::Object(_) => {
+            let options = FormatOptions {
+                style: Style::Compact,
+                ..FormatOptions::default()
+            };
+            Cow::Owned(serialize_pretty(value, &options))
+        }
+        scalar => Cow::Owned(scalar.to_string()),
+    };
+    quote(&text)
+}
+
+// Quote a field containing a delimiter, quote or line break, doubling inner quotes
+fn quote(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn type_name(value: &JsonValue) -> &'static str {
+    match value {
+        JsonValue::Null => "null",
+        JsonValue::Bool(_) => "a boolean",
+        JsonValue::Number(_) => "a number",
+        JsonValue::String(_) => "a string",
+        JsonValue::Array(_) => "an array",
+        JsonValue::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_value;
+
+    fn csv(input: &str) -> Result<String, Error> {
+        to_csv(&parse_value(input).unwrap())
+    }
+
+    #[test]
+    fn csv_union_of_keys_with_empty_cells() {
+        let input = r#"[{"a": 1, "b": 2}, {"b": 4, "c": true}, {"a": null, "d": {"x": [1, 2]}}]"#;
+        let expected = "a,b,c,d\n1,2,,\n,4,true,\n,,,\"{\"\"x\"\":[1,2]}\"";
+        assert_eq!(csv(input).unwrap(), expected);
+    }
+    #[test]
+    fn csv_quotes_special_characters() {
+        let input = r#"[{"text": "plain", "n": 1.50}, {"text": "a,b"}, {"text": "say \"hi\""}, {"text": "two\nlines"}, {"text, key": ""}]"#;
+        let expected = "text,n,\"text, key\"\nplain,1.50,\n\"a,b\",,\n\"say \"\"hi\"\"\",,\n\"two\nlines\",,\n,,";
+        assert_eq!(csv(input).unwrap(), expected);
+    }
+    #[test]
+    fn csv_rejects_other_shapes() {
+        let error = csv(r#"{"a": 1}"#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "CSV output requires a top-level array of objects, found an object"
+        );
+        let error = csv(r#"[{"a": 1}, 2]"#).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "CSV output requires every array element to be an object, found a number at index 1"
+        );
+    }
+}
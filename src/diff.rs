@@ -0,0 +1,163 @@
+use std::fmt;
+
+use crate::options::{FormatOptions, Style};
+use crate::pointer::push_segment;
+use crate::serializer::serialize_pretty;
+use crate::types::{last_wins, JsonValue};
+
+// A single structural difference, located by the JSON Pointer of the value.
+// `old` is absent for additions and `new` for removals.
+#[derive(Debug, PartialEq)]
+pub struct Change<'a> {
+    pub path: String,
+    pub old: Option<JsonValue<'a>>,
+    pub new: Option<JsonValue<'a>>,
+}
+
+impl fmt::Display for Change<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let path = if self.path.is_empty() {
+            "(root)"
+        } else {
+            &self.path
+        };
+        match (&self.old, &self.new) {
+            (Some(old), Some(new)) => write!(f, "~ {}: {} -> {}", path, compact(old), compact(new)),
+            (None, Some(new)) => write!(f, "+ {}: {}", path, compact(new)),
+            (Some(old), None) => write!(f, "- {}: {}", path, compact(old)),
+            (None, None) => write!(f, "  {}", path),
+        }
+    }
+}
+
+fn compact(value: &JsonValue) -> String {
+    let options = FormatOptions {
+        style: Style::Spaced,
+        ..FormatOptions::default()
+    };
+    serialize_pretty(value, &options)
+}
+
+// Compare two documents key by key and index by index. Object key order is
+// ignored; array elements are compared by position, with any extra elements
+// reported as additions or removals.
+pub fn diff<'a>(a: &JsonValue<'a>, b: &JsonValue<'a>) -> Vec<Change<'a>> {
+    let mut changes = Vec::new();
+    walk(a, b, &mut String::new(), &mut changes);
+    changes
+}
+
+fn walk<'a>(
+    a: &JsonValue<'a>,
+    b: &JsonValue<'a>,
+    path: &mut String,
+    changes: &mut Vec<Change<'a>>,
+) {
+    match (a, b) {
+        (JsonValue::Object(old), JsonValue::Object(new)) => {
+            // A repeated key is compared once, by its last value
+            let (old_entries, new_entries) = (last_wins(old), last_wins(new));
+            for (key, old_value) in old {
+                if !std::ptr::eq(old_entries[key.as_ref()], old_value) {
+                    continue;
+                }
+                let len = push_segment(path, key);
+                match new_entries.get(key.as_ref()) {
+                    Some(new_value) => walk(old_value, new_value, path, changes),
+                    None => changes.push(change(path, Some(old_value), None)),
+                }
+                path.truncate(len);
+            }
+            for (key, new_value) in new {
+                if std::ptr::eq(new_entries[key.as_ref()], new_value)
+                    && !old_entries.contains_key(key.as_ref())
+                {
+                    let len = push_segment(path, key);
+                    changes.push(change(path, None, Some(new_value)));
+                    path.truncate(len);
+                }
+            }
+        }
+        (JsonValue::Array(old), JsonValue::Array(new)) => {
+            for i in 0..old.len().max(new.len()) {
+                let len = push_segment(path, &i.to_string());
+                match (old.get(i), new.get(i)) {
+                    (Some(old_value), Some(new_value)) => walk(old_value, new_value, path, changes),
+                    (old_value, new_value) => changes.push(change(path, old_value, new_value)),
+                }
+                path.truncate(len);
+            }
+        }
+        (old, new) if old != new => changes.push(change(path, Some(old), Some(new))),
+        _ => {}
+    }
+}
+
+fn change<'a>(path: &str, old: Option<&JsonValue<'a>>, new: Option<&JsonValue<'a>>) -> Change<'a> {
+    Change {
+        path: path.to_string(),
+        old: old.cloned(),
+        new: new.cloned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_value;
+
+    fn diff_str(a: &str, b: &str) -> Vec<String> {
+        let a = parse_value(a).unwrap();
+        let b = parse_value(b).unwrap();
+        diff(&a, &b)
+            .iter()
+            .map(|change| change.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn diff_objects_and_arrays() {
+        let a = r#"{"name": "jp", "tags": ["a", "b", "c"], "old": true, "nested": {"x": 1}}"#;
+        let b = r#"{"nested": {"x": 2}, "tags": ["a", "z"], "name": "jp", "new": {"k": [1]}}"#;
+        let expected = [
+            "~ /tags/1: \"b\" -> \"z\"",
+            "- /tags/2: \"c\"",
+            "- /old: true",
+            "~ /nested/x: 1 -> 2",
+            "+ /new: {\"k\": [1]}",
+        ];
+        assert_eq!(diff_str(a, b), expected);
+    }
+    #[test]
+    fn diff_identical_and_type_changes() {
+        assert!(diff_str(r#"{"a": [1, {"b": null}]}"#, r#"{"a": [1, {"b": null}]}"#).is_empty());
+        assert_eq!(
+            diff_str("[1]", r#"{"0": 1}"#),
+            ["~ (root): [1] -> {\"0\": 1}"]
+        );
+        assert_eq!(diff_str(r#"{"a/b~": 1}"#, "{}"), ["- /a~1b~0: 1"]);
+    }
+    #[test]
+    fn diff_repeated_keys_by_their_last_value() {
+        // The parser keeps one entry per key, so build the repeats by hand
+        let repeated = |key: &str, first: f64, last: f64| {
+            JsonValue::Object(vec![
+                (key.to_string().into(), JsonValue::from(first)),
+                (key.to_string().into(), JsonValue::from(last)),
+            ])
+        };
+        let single = parse_value(r#"{"a": 2}"#).unwrap();
+        let changes = |a: &JsonValue, b: &JsonValue| {
+            diff(a, b)
+                .iter()
+                .map(|change| change.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert!(changes(&repeated("a", 1.0, 2.0), &single).is_empty());
+        assert_eq!(changes(&single, &repeated("a", 2.0, 3.0)), ["~ /a: 2 -> 3"]);
+        assert_eq!(
+            changes(&repeated("a", 1.0, 2.0), &repeated("b", 1.0, 2.0)),
+            ["- /a: 2", "+ /b: 2"]
+        );
+    }
+}
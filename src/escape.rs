@@ -0,0 +1,89 @@
+use std::fmt::Write;
+
+use crate::options::FormatOptions;
+
+// Quote a string for JSON output, escaping quotes, backslashes and control characters.
+// With `ascii`, everything outside ASCII is escaped too, using surrogate pairs above the BMP.
+pub fn quote(string: &str, ascii: bool) -> String {
+    quote_escaping(string, ascii, false)
+}
+
+// Quote a string as the formatters write it, honoring `ascii` and `escape_slashes`
+pub fn quote_for(string: &str, options: &FormatOptions) -> String {
+    quote_escaping(string, options.ascii, options.escape_slashes)
+}
+
+fn quote_escaping(string: &str, ascii: bool, slashes: bool) -> String {
+    let mut out = String::with_capacity(string.len() + 2);
+    out.push('"');
+    for char in string.chars() {
+        match char {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '/' if slashes => out.push_str("\\/"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0C}' => out.push_str("\\f"),
+            c if c < ' ' => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c if ascii && !c.is_ascii() => {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    let _ = write!(out, "\\u{:04x}", unit);
+                }
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+// Escape text for an HTML element body
+pub fn html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for char in text.chars() {
+        match char {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quote_escapes_special_characters() {
+        assert_eq!(quote("plain", false), r#""plain""#);
+        assert_eq!(quote("he said \"hi\"", false), r#""he said \"hi\"""#);
+        assert_eq!(quote("a\\b/c", false), r#""a\\b/c""#);
+        assert_eq!(quote("\n\r\t\u{08}\u{0C}", false), r#""\n\r\t\b\f""#);
+        assert_eq!(quote("\u{00}\u{1F}", false), r#""\u0000\u001f""#);
+        assert_eq!(quote("café", false), r#""café""#);
+    }
+    #[test]
+    fn quote_ascii_escapes_non_ascii() {
+        assert_eq!(quote("café", true), r#""caf\u00e9""#);
+        assert_eq!(quote("😀", true), r#""\ud83d\ude00""#);
+        assert_eq!(quote("plain\n", true), r#""plain\n""#);
+    }
+    #[test]
+    fn quote_for_escapes_slashes_when_asked() {
+        let options = FormatOptions {
+            escape_slashes: true,
+            ..FormatOptions::default()
+        };
+        assert_eq!(quote_for("</script>", &options), r#""<\/script>""#);
+        assert_eq!(
+            quote_for("</script>", &FormatOptions::default()),
+            r#""</script>""#
+        );
+    }
+}
@@ -0,0 +1,300 @@
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::sync::Arc;
+use std::time::Duration;
+
+use rustls::pki_types::ServerName;
+use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+// Where a URL points: the address to connect to, the Host header and the path
+#[derive(Debug, PartialEq)]
+struct Target<'a> {
+    tls: bool,
+    host: &'a str,
+    address: String,
+    authority: &'a str,
+    path: &'a str,
+}
+
+fn parse_url(url: &str) -> Result<Target<'_>, String> {
+    let (tls, rest) = if let Some(rest) = url.strip_prefix("https://") {
+        (true, rest)
+    } else if let Some(rest) = url.strip_prefix("http://") {
+        (false, rest)
+    } else {
+        return Err("not an http or https URL".to_string());
+    };
+    let (authority, path) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, "/"),
+    };
+    if authority.is_empty() {
+        return Err("missing host".to_string());
+    }
+    // An IPv6 host is bracketed and full of colons, so only a colon after
+    // the closing bracket starts a port
+    let host = match authority.rfind(':') {
+        Some(i) if !authority[i..].contains(']') => &authority[..i],
+        _ => authority,
+    };
+    let address = if host.len() < authority.len() {
+        authority.to_string()
+    } else {
+        format!("{}:{}", authority, if tls { 443 } else { 80 })
+    };
+    Ok(Target {
+        tls,
+        host: host.trim_start_matches('[').trim_end_matches(']'),
+        address,
+        authority,
+        path,
+    })
+}
+
+// Fetch a document with an HTTP/1.1 GET, over TLS for https URLs. With a
+// `limit`, a body longer than that many bytes is `None`, and reading stops
+// just past the limit.
+pub fn fetch(url: &str, limit: Option<usize>) -> Result<Option<String>, String> {
+    let target = parse_url(url)?;
+    let stream = TcpStream::connect(&target.address).map_err(|e| e.to_string())?;
+    stream
+        .set_read_timeout(Some(TIMEOUT))
+        .map_err(|e| e.to_string())?;
+    if !target.tls {
+        return get(stream, &target, limit);
+    }
+
+    let mut roots = RootCertStore::empty();
+    roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    let config =
+        ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .map_err(|e| e.to_string())?
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+    let name = ServerName::try_from(target.host.to_string()).map_err(|e| e.to_string())?;
+    let connection = ClientConnection::new(Arc::new(config), name).map_err(|e| e.to_string())?;
+    get(StreamOwned::new(connection, stream), &target, limit)
+}
+
+fn get(
+    mut stream: impl Read + Write,
+    target: &Target,
+    limit: Option<usize>,
+) -> Result<Option<String>, String> {
+    write!(
+        stream,
+        "GET {} HTTP/1.1\r\nHost: {}\r\nAccept: application/json\r\nUser-Agent: jp/{}\r\nConnection: close\r\n\r\n",
+        target.path,
+        target.authority,
+        env!("CARGO_PKG_VERSION")
+    )
+    .map_err(|e| e.to_string())?;
+    stream.flush().map_err(|e| e.to_string())?;
+
+    read_response(BufReader::new(stream), limit)
+}
+
+fn read_response(mut reader: impl BufRead, limit: Option<usize>) -> Result<Option<String>, String> {
+    let status_line = read_line(&mut reader)?;
+    let mut parts = status_line.splitn(3, ' ');
+    let status = match (parts.next(), parts.next()) {
+        (Some(version), Some(code)) if version.starts_with("HTTP/") => code
+            .parse::<u16>()
+            .map_err(|_| format!("malformed status line: {}", status_line))?,
+        _ => return Err(format!("malformed status line: {}", status_line)),
+    };
+    let reason = parts.next().unwrap_or("").trim();
+
+    let mut content_length = None;
+    let mut chunked = false;
+    loop {
+        let line = read_line(&mut reader)?;
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.parse::<u64>().ok();
+            } else if name.eq_ignore_ascii_case("transfer-encoding") {
+                chunked = value.eq_ignore_ascii_case("chunked");
+            }
+        }
+    }
+
+    if !(200..300).contains(&status) {
+        return Err(format!("HTTP {} {}", status, reason).trim_end().to_string());
+    }
+
+    // Sizes from the headers aren't trusted to allocate up front; the body
+    // grows only as bytes actually arrive, and no further than one byte past
+    // the limit
+    let cap = limit.map_or(u64::MAX, |limit| limit as u64 + 1);
+    let mut body = Vec::new();
+    if chunked {
+        loop {
+            let size = read_line(&mut reader)?;
+            let size = size.split(';').next().unwrap_or("").trim();
+            let size = u64::from_str_radix(size, 16)
+                .map_err(|_| format!("malformed chunk size: {}", size))?;
+            if size == 0 {
+                break;
+            }
+            let end = (body.len() as u64)
+                .checked_add(size)
+                .ok_or_else(|| format!("chunk size too large: {:x}", size))?;
+            read_exactly(&mut reader, &mut body, end.min(cap))?;
+            if body.len() as u64 >= cap {
+                return Ok(None);
+            }
+            read_line(&mut reader)?;
+        }
+    } else if let Some(len) = content_length {
+        read_exactly(&mut reader, &mut body, len.min(cap))?;
+    } else {
+        reader
+            .take(cap)
+            .read_to_end(&mut body)
+            .map_err(|e| e.to_string())?;
+    }
+    if body.len() as u64 >= cap {
+        return Ok(None);
+    }
+    String::from_utf8(body)
+        .map(Some)
+        .map_err(|_| "response is not valid UTF-8".to_string())
+}
+
+// Read from `reader` until `body` is `len` bytes long
+fn read_exactly(reader: &mut impl BufRead, body: &mut Vec<u8>, len: u64) -> Result<(), String> {
+    let wanted = len.saturating_sub(body.len() as u64);
+    let read = reader
+        .take(wanted)
+        .read_to_end(body)
+        .map_err(|e| e.to_string())?;
+    if (read as u64) < wanted {
+        return Err("connection closed before the response ended".to_string());
+    }
+    Ok(())
+}
+
+// One header line without its CRLF
+fn read_line(reader: &mut impl BufRead) -> Result<String, String> {
+    let mut line = String::new();
+    let read = reader.read_line(&mut line).map_err(|e| e.to_string())?;
+    if read == 0 {
+        return Err("connection closed before the response ended".to_string());
+    }
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    // Serve one canned response on a local port and return its URL
+    fn serve(response: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+            while request.read_line(&mut line).unwrap() > 2 {
+                line.clear();
+            }
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        format!("http://{}/data.json", address)
+    }
+
+    #[test]
+    fn fetch_from_mock_server() {
+        let url = serve("HTTP/1.1 200 OK\r\nContent-Length: 8\r\n\r\n{\"a\":1}\n");
+        assert_eq!(fetch(&url, None).unwrap().unwrap(), "{\"a\":1}\n");
+
+        let url =
+            serve("HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\n[1, \r\n2\r\n2]\r\n0\r\n\r\n");
+        assert_eq!(fetch(&url, None).unwrap().unwrap(), "[1, 2]");
+    }
+    #[test]
+    fn fetch_reports_errors() {
+        let url = serve("HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n");
+        assert_eq!(fetch(&url, None).unwrap_err(), "HTTP 404 Not Found");
+        assert_eq!(
+            fetch("ftp://example.com/", None).unwrap_err(),
+            "not an http or https URL"
+        );
+        assert_eq!(fetch("http:///x", None).unwrap_err(), "missing host");
+
+        // Sizes far beyond the body fail once the connection closes, without
+        // allocating for them first
+        let url = serve("HTTP/1.1 200 OK\r\nContent-Length: 99999999999999\r\n\r\n[1]");
+        assert_eq!(
+            fetch(&url, None).unwrap_err(),
+            "connection closed before the response ended"
+        );
+        let url =
+            serve("HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nffffffffffffffff\r\n[1]");
+        assert_eq!(
+            fetch(&url, None).unwrap_err(),
+            "connection closed before the response ended"
+        );
+    }
+    #[test]
+    fn fetch_stops_past_the_limit() {
+        let body = "[1, 2, 3]";
+        let response = "HTTP/1.1 200 OK\r\nContent-Length: 9\r\n\r\n[1, 2, 3]";
+        assert_eq!(
+            fetch(&serve(response), Some(9)).unwrap().as_deref(),
+            Some(body)
+        );
+        assert_eq!(fetch(&serve(response), Some(8)).unwrap(), None);
+        // The size in the header isn't believed, only the bytes that arrive
+        let response = "HTTP/1.1 200 OK\r\nContent-Length: 99999999999999\r\n\r\n[1, 2, 3]";
+        assert_eq!(fetch(&serve(response), Some(4)).unwrap(), None);
+        let response = "HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4\r\n[1, \r\n5\r\n2, 3]\r\n0\r\n\r\n";
+        assert_eq!(fetch(&serve(response), Some(6)).unwrap(), None);
+        let response = "HTTP/1.1 200 OK\r\nConnection: close\r\n\r\n[1, 2, 3]";
+        assert_eq!(fetch(&serve(response), Some(8)).unwrap(), None);
+        assert_eq!(
+            fetch(&serve(response), Some(9)).unwrap().as_deref(),
+            Some(body)
+        );
+    }
+    #[test]
+    fn https_goes_through_tls() {
+        // A plain HTTP server can't complete a TLS handshake
+        let url = serve("HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n[]");
+        let url = url.replacen("http://", "https://", 1);
+        assert!(fetch(&url, None).is_err());
+    }
+    #[test]
+    fn parse_url_defaults_ports() {
+        let target = parse_url("https://example.com/data.json").unwrap();
+        assert!(target.tls);
+        assert_eq!(target.host, "example.com");
+        assert_eq!(target.address, "example.com:443");
+        assert_eq!(target.path, "/data.json");
+
+        let target = parse_url("http://example.com:8080").unwrap();
+        assert!(!target.tls);
+        assert_eq!(target.host, "example.com");
+        assert_eq!(target.address, "example.com:8080");
+        assert_eq!(target.path, "/");
+
+        let target = parse_url("http://[::1]/x").unwrap();
+        assert_eq!(target.host, "::1");
+        assert_eq!(target.address, "[::1]:80");
+        assert_eq!(target.authority, "[::1]");
+
+        let target = parse_url("https://[::1]:8443/x").unwrap();
+        assert_eq!(target.host, "::1");
+        assert_eq!(target.address, "[::1]:8443");
+    }
+}
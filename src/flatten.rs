@@ -0,0 +1,249 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::pointer::parse_index;
+use crate::types::{Error, JsonValue};
+
+// Flatten nested containers into a single object keyed by dotted paths, with
+// array indices as path segments: `{"a":{"b":1},"c":[10]}` becomes
+// `{"a.b":1,"c.0":10}`. Keys that already contain `.` are left as they are, so
+// `{"a.b":1}` and `{"a":{"b":1}}` flatten to the same key. Empty containers are
+// kept as leaf values; a top-level scalar is returned unchanged.
+pub fn flatten<'a>(value: &JsonValue<'a>) -> JsonValue<'a> {
+    match value {
+        JsonValue::Object(_) | JsonValue::Array(_) => {
+            let mut flat = Vec::new();
+            walk(value, &mut String::new(), &mut flat);
+            JsonValue::Object(flat)
+        }
+        scalar => scalar.clone(),
+    }
+}
+
+fn walk<'a>(
+    value: &JsonValue<'a>,
+    path: &mut String,
+    flat: &mut Vec<(Cow<'a, str>, JsonValue<'a>)>,
+) {
+    match value {
+        JsonValue::Object(object) if !object.is_empty() => {
+            for (key, item) in object {
+                descend(item, path, key, flat);
+            }
+        }
+        JsonValue::Array(array) if !array.is_empty() => {
+            for (i, item) in array.iter().enumerate() {
+                descend(item, path, &i.to_string(), flat);
+            }
+        }
+        leaf => flat.push((Cow::Owned(path.clone()), leaf.clone())),
+    }
+}
+
+fn descend<'a>(
+    value: &JsonValue<'a>,
+    path: &mut String,
+    segment: &str,
+    flat: &mut Vec<(Cow<'a, str>, JsonValue<'a>)>,
+) {
+    let len = path.len();
+    if len > 0 {
+        path.push('.');
+    }
+    path.push_str(segment);
+    walk(value, path, flat);
+    path.truncate(len);
+}
+
+// Rebuild nested values from dotted keys, the inverse of `flatten`. A level
+// whose keys are exactly the indices 0..n becomes an array; anything else
+// becomes an object in first-seen key order. A path used both as a value and as
+// a parent of other keys is an error. Non-object values are returned unchanged.
+pub fn unflatten<'a>(value: &JsonValue<'a>) -> Result<JsonValue<'a>, Error> {
+    let Some(object) = value.as_object() else {
+        return Ok(value.clone());
+    };
+    let mut root = Level::default();
+    for (key, item) in object {
+        let segments = key.split('.').collect::<Vec<_>>();
+        insert(&mut root, key, &segments, item)?;
+    }
+    Ok(build(root))
+}
+
+enum Node<'a> {
+    Leaf(JsonValue<'a>),
+    Branch(Level<'a>),
+}
+
+// One level's entries in first-seen order, and where each key is among them,
+// so unflattening is linear in the number of keys
+#[derive(Default)]
+struct Level<'a> {
+    entries: Vec<(String, Node<'a>)>,
+    index: HashMap<String, usize>,
+}
+
+impl<'a> Level<'a> {
+    fn push(&mut self, key: &str, node: Node<'a>) {
+        self.index.insert(key.to_string(), self.entries.len());
+        self.entries.push((key.to_string(), node));
+    }
+}
+
+fn insert<'a>(
+    level: &mut Level<'a>,
+    key: &str,
+    segments: &[&str],
+    value: &JsonValue<'a>,
+) -> Result<(), Error> {
+    let (segment, rest) = segments.split_first().expect("split yields a segment");
+    let existing = level.index.get(*segment).copied();
+    let conflict = || {
+        Error::Unsupported(format!(
+            "Cannot unflatten key {:?}: a path is used both as a value and as an object",
+            key
+        ))
+    };
+    match (existing, !rest.is_empty()) {
+        (None, false) => level.push(segment, Node::Leaf(value.clone())),
+        (None, true) => {
+            let mut branch = Level::default();
+            insert(&mut branch, key, rest, value)?;
+            level.push(segment, Node::Branch(branch));
+        }
+        (Some(i), true) => match &mut level.entries[i].1 {
+            Node::Branch(branch) => insert(branch, key, rest, value)?,
+            Node::Leaf(_) => return Err(conflict()),
+        },
+        (Some(_), false) => return Err(conflict()),
+    }
+    Ok(())
+}
+
+fn build(level: Level) -> JsonValue {
+    // Keys are unique, so when each is a distinct index below the count they
+    // are exactly 0..n
+    let len = level.entries.len();
+    let indices = level
+        .entries
+        .iter()
+        .map(|(key, _)| parse_index(key).filter(|&i| i < len))
+        .collect::<Option<Vec<_>>>();
+    let entries = level.entries.into_iter().map(|(key, node)| {
+        let value = match node {
+            Node::Leaf(value) => value,
+            Node::Branch(branch) => build(branch),
+        };
+        (key, value)
+    });
+    match indices {
+        Some(indices) if len > 0 => {
+            let mut array = entries
+                .zip(indices)
+                .map(|((_, value), i)| (i, value))
+                .collect::<Vec<_>>();
+            array.sort_unstable_by_key(|(i, _)| *i);
+            JsonValue::Array(array.into_iter().map(|(_, value)| value).collect())
+        }
+        _ => JsonValue::Object(
+            entries
+                .map(|(key, value)| (Cow::Owned(key), value))
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::{FormatOptions, Style};
+    use crate::parse_value;
+    use crate::serializer::serialize_pretty;
+
+    fn unflat(input: &str) -> Result<String, Error> {
+        let value = unflatten(&parse_value(input).unwrap())?;
+        let options = FormatOptions {
+            style: Style::Compact,
+            ..FormatOptions::default()
+        };
+        Ok(serialize_pretty(&value, &options))
+    }
+
+    fn flat_keys(input: &str) -> Vec<(String, String)> {
+        let value = flatten(&parse_value(input).unwrap());
+        value
+            .as_object()
+            .unwrap()
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn flatten_deeply_nested_objects() {
+        let keys = flat_keys(r#"{"a": {"b": {"c": {"d": 1}}, "e": true}, "f": null}"#);
+        let expected = [("a.b.c.d", "1"), ("a.e", "true"), ("f", "null")];
+        let expected = expected.map(|(k, v)| (k.to_string(), v.to_string()));
+        assert_eq!(keys, expected);
+    }
+    #[test]
+    fn flatten_arrays_of_objects() {
+        let keys =
+            flat_keys(r#"{"c": [10, 20], "rows": [{"id": 1}, {"id": 2, "tags": []}], "x": {}}"#);
+        let expected = [
+            ("c.0", "10"),
+            ("c.1", "20"),
+            ("rows.0.id", "1"),
+            ("rows.1.id", "2"),
+            ("rows.1.tags", "[Array]"),
+            ("x", "[Object]"),
+        ];
+        let expected = expected.map(|(k, v)| (k.to_string(), v.to_string()));
+        assert_eq!(keys, expected);
+    }
+    #[test]
+    fn unflatten_rebuilds_arrays() {
+        assert_eq!(
+            unflat(r#"{"a.b": 1, "c.0": 10, "c.1": 20}"#).unwrap(),
+            r#"{"a":{"b":1},"c":[10,20]}"#
+        );
+        // Indices may arrive in any order, but must cover 0..n exactly
+        assert_eq!(
+            unflat(r#"{"r.1.id": 2, "r.0.id": 1, "r.0.tags": []}"#).unwrap(),
+            r#"{"r":[{"id":1,"tags":[]},{"id":2}]}"#
+        );
+        assert_eq!(
+            unflat(r#"{"s.1": 1, "s.2": 2}"#).unwrap(),
+            r#"{"s":{"1":1,"2":2}}"#
+        );
+        assert_eq!(
+            unflat(r#"{"s.0": 0, "s.01": 1}"#).unwrap(),
+            r#"{"s":{"0":0,"01":1}}"#
+        );
+
+        let input = r#"{"a": {"b": [1, {"c": null}], "d": {}}, "e": "f"}"#;
+        let round_trip = unflatten(&flatten(&parse_value(input).unwrap())).unwrap();
+        assert_eq!(round_trip, parse_value(input).unwrap());
+
+        // Linear in the number of keys, so a large array comes back quickly
+        let input = format!("[{}]", vec!["{\"id\": 1}"; 100_000].join(","));
+        let value = parse_value(&input).unwrap();
+        assert_eq!(unflatten(&flatten(&value)).unwrap(), value);
+    }
+    #[test]
+    fn unflatten_rejects_conflicts() {
+        for input in [
+            r#"{"a": 1, "a.b": 2}"#,
+            r#"{"a.b": 2, "a": 1}"#,
+            r#"{"a.b.c": 1, "a.b": 2}"#,
+        ] {
+            let error = unflat(input).unwrap_err();
+            assert!(
+                error.to_string().starts_with("Cannot unflatten key"),
+                "{}",
+                input
+            );
+        }
+    }
+}
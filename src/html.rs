@@ -0,0 +1,58 @@
+use crate::options::FormatOptions;
+use crate::serializer::serialize_pretty;
+use crate::types::JsonValue;
+
+// A small default theme for the classes `to_html` emits
+pub const STYLESHEET: &str = "pre.json { background: #f6f8fa; padding: 1em; }
+.json-key { color: #0550ae; font-weight: bold; }
+.json-string { color: #0a3069; }
+.json-number { color: #953800; }
+.json-bool { color: #8250df; }
+.json-null { color: #6e7781; }
+.json-punct { color: #24292f; }";
+
+// Render a value as a `<pre>` block with each token in a classed `<span>`,
+// using the layout options but never ANSI colors
+pub fn to_html(value: &JsonValue, options: &FormatOptions) -> String {
+    let options = FormatOptions {
+        html: true,
+        color: false,
+        ..options.clone()
+    };
+    format!(
+        "<pre class=\"json\">{}</pre>",
+        serialize_pretty(value, &options)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::Style;
+    use crate::parse_value;
+
+    #[test]
+    fn html_wraps_tokens_in_spans() {
+        let value = parse_value(r#"{"a<b": [1, true, null, "x & y"]}"#).unwrap();
+        let options = FormatOptions {
+            style: Style::Compact,
+            ..FormatOptions::default()
+        };
+        let expected = concat!(
+            r#"<pre class="json"><span class="json-punct">{</span>"#,
+            r#"<span class="json-key">"a&lt;b"</span><span class="json-punct">:</span>"#,
+            r#"<span class="json-punct">[</span><span class="json-number">1</span>"#,
+            r#"<span class="json-punct">,</span><span class="json-bool">true</span>"#,
+            r#"<span class="json-punct">,</span><span class="json-null">null</span>"#,
+            r#"<span class="json-punct">,</span><span class="json-string">"x &amp; y"</span>"#,
+            r#"<span class="json-punct">]</span><span class="json-punct">}</span></pre>"#,
+        );
+        assert_eq!(to_html(&value, &options), expected);
+    }
+    #[test]
+    fn html_keeps_pretty_layout() {
+        let value = parse_value(r#"{"a": []}"#).unwrap();
+        let expected = "<pre class=\"json\"><span class=\"json-punct\">{</span>\n    <span class=\"json-key\">\"a\"</span><span class=\"json-punct\">:</span> <span class=\"json-punct\">[]</span>\n<span class=\"json-punct\">}</span></pre>";
+        assert_eq!(to_html(&value, &FormatOptions::default()), expected);
+    }
+}
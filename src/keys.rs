@@ -0,0 +1,110 @@
+use crate::types::JsonValue;
+
+// Keep only the named entries of an object, or of each object in an array,
+// in their original order. Keys that aren't present are skipped, and values
+// of any other type are left alone.
+pub fn select_keys(value: &mut JsonValue, keys: &[&str]) {
+    match value {
+        JsonValue::Object(object) => object.retain(|(key, _)| keys.contains(&key.as_ref())),
+        JsonValue::Array(array) => {
+            for item in array {
+                if let JsonValue::Object(object) = item {
+                    object.retain(|(key, _)| keys.contains(&key.as_ref()));
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+// Remove every object entry with one of the named keys, at any depth
+pub fn omit_keys(value: &mut JsonValue, keys: &[&str]) {
+    match value {
+        JsonValue::Object(object) => {
+            object.retain(|(key, _)| !keys.contains(&key.as_ref()));
+            for (_, item) in object {
+                omit_keys(item, keys);
+            }
+        }
+        JsonValue::Array(array) => {
+            for item in array {
+                omit_keys(item, keys);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Remove every object entry whose value is `null`, at any depth. Nulls in
+// arrays are kept, since removing them would shift the later elements.
+pub fn drop_nulls(value: &mut JsonValue) {
+    match value {
+        JsonValue::Object(object) => {
+            object.retain(|(_, item)| *item != JsonValue::Null);
+            for (_, item) in object {
+                drop_nulls(item);
+            }
+        }
+        JsonValue::Array(array) => {
+            for item in array {
+                drop_nulls(item);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_value;
+
+    fn select(input: &str, keys: &[&str]) -> String {
+        let mut value = parse_value(input).unwrap();
+        select_keys(&mut value, keys);
+        crate::to_canonical(&value).unwrap()
+    }
+
+    #[test]
+    fn omit_keys_at_every_depth() {
+        let mut value = parse_value(
+            r#"{"token": "t", "user": {"name": "a", "password": "p", "keys": [{"token": 1, "id": 2}]}, "password": null}"#,
+        )
+        .unwrap();
+        omit_keys(&mut value, &["password", "token"]);
+        assert_eq!(
+            crate::to_canonical(&value).unwrap(),
+            r#"{"user":{"keys":[{"id":2}],"name":"a"}}"#
+        );
+    }
+    #[test]
+    fn drop_nulls_from_objects_only() {
+        let mut value = parse_value(
+            r#"{"a": null, "b": {"c": null, "d": [null, {"e": null, "f": 0}]}, "g": {"h": null}}"#,
+        )
+        .unwrap();
+        drop_nulls(&mut value);
+        assert_eq!(
+            crate::to_canonical(&value).unwrap(),
+            r#"{"b":{"d":[null,{"f":0}]},"g":{}}"#
+        );
+    }
+    #[test]
+    fn select_keys_of_an_object() {
+        let input = r#"{"c": 3, "a": {"b": 1, "x": 2}, "d": 4}"#;
+        assert_eq!(
+            select(input, &["a", "c", "missing"]),
+            r#"{"a":{"b":1,"x":2},"c":3}"#
+        );
+        assert_eq!(select(input, &[]), "{}");
+        assert_eq!(select("42", &["a"]), "42");
+    }
+    #[test]
+    fn select_keys_of_each_object_in_an_array() {
+        let input = r#"[{"id": 1, "name": "a", "tags": []}, {"name": "b"}, 3, [{"id": 2}]]"#;
+        assert_eq!(
+            select(input, &["id", "name"]),
+            r#"[{"id":1,"name":"a"},{"name":"b"},3,[{"id":2}]]"#
+        );
+    }
+}
@@ -1,115 +1,425 @@
+use std::borrow::Cow;
+use std::io::{self, BufRead};
+
 use crate::prelude::*;
-use crate::types::{Error, JsonValue, Token, TokenType};
+use crate::types::{Error, JsonValue, Number, Token, TokenType};
 
 #[derive(Debug)]
 pub struct Lexer<'a> {
     source: &'a str,
-    index: usize,
+    index: usize, // Byte offset into source, always on a char boundary
     line: usize,
-    column: usize,
+    column: usize, // In characters, as an editor counts them, not bytes
+    allow_comments: bool,
+    preserve_comments: bool,
+    strict_integers: bool,
+    strict_precision: bool,
+    eof_token: bool,
+    // Whether the stream has ended, with an error or an `Eof` token
+    ended: bool,
 }
 
 impl<'a> From<&'a str> for Lexer<'a> {
     fn from(source: &'a str) -> Self {
         Lexer {
             source,
-            index: 0,
-            line: 0,
-            column: 0,
+            index: start_index(source),
+            line: 1,
+            column: 1,
+            allow_comments: false,
+            preserve_comments: false,
+            strict_integers: false,
+            strict_precision: false,
+            eof_token: false,
+            ended: false,
         }
     }
 }
 
+// Skip a leading UTF-8 byte order mark
+fn start_index(source: &str) -> usize {
+    if source.starts_with(JSON_BOM) {
+        JSON_BOM.len_utf8()
+    } else {
+        0
+    }
+}
+
 impl<'a> Lexer<'a> {
-    pub fn lex(&mut self) -> Result<Vec<Token>, Error> {
-        let mut tokens: Vec<Token> = Vec::new();
-
-        while self.index < self.source.len() {
-            if let Some(next) = self.source.chars().nth(self.index) {
-                // Skip whitespace
-                if next.is_ascii_whitespace() {
-                    self.whitespace(next);
-                    continue;
+    // Treat `//` and `/* */` comments as whitespace (JSONC)
+    pub fn allow_comments(mut self, allow: bool) -> Self {
+        self.allow_comments = allow;
+        self
+    }
+
+    // With `allow_comments`, emit each comment as a `TokenType::Comment` token
+    // holding its text, delimiters included, instead of skipping it
+    pub fn preserve_comments(mut self, preserve: bool) -> Self {
+        self.preserve_comments = preserve;
+        self
+    }
+
+    // Fail on integers outside the `i64` range. By default they are accepted
+    // and keep their exact text, with only the `f64` value losing precision
+    pub fn strict_integers(mut self, strict: bool) -> Self {
+        self.strict_integers = strict;
+        self
+    }
+
+    // Fail on integers that an `f64` can't hold exactly, such as 2^53 + 1
+    pub fn strict_precision(mut self, strict: bool) -> Self {
+        self.strict_precision = strict;
+        self
+    }
+
+    // End a successful stream with one `TokenType::Eof` token at the end of the
+    // source, so consumers can always look a token ahead
+    pub fn eof_token(mut self, eof: bool) -> Self {
+        self.eof_token = eof;
+        self
+    }
+
+    // Point the lexer at a new source, keeping its options, so one lexer can
+    // be reused across many documents
+    pub fn reset(&mut self, source: &'a str) {
+        self.source = source;
+        self.index = start_index(source);
+        self.line = 1;
+        self.column = 1;
+        self.ended = false;
+    }
+
+    pub fn lex(&mut self) -> Result<Vec<Token<'a>>, Error> {
+        self.collect()
+    }
+
+    // Lex the whole source without stopping at errors. After each error the
+    // lexer skips to the next plausible token boundary and leaves a placeholder
+    // token in place of the bad text, so the parser doesn't trip over its absence.
+    pub fn lex_all(&mut self) -> (Vec<Token<'a>>, Vec<Error>) {
+        let mut tokens = Vec::new();
+        let mut errors = Vec::new();
+        loop {
+            let (start, line, column) = (self.index, self.line, self.column);
+            match self.lex_token() {
+                None => break,
+                Some(Ok(token)) => tokens.push(token),
+                Some(Err(e)) => {
+                    // Nothing sensible follows an unterminated string or comment
+                    let fatal = matches!(
+                        e,
+                        Error::UnexpectedEndOfString
+                            | Error::DanglingEscape(..)
+                            | Error::UnexpectedEndOfComment(..)
+                    );
+                    let in_string = matches!(e, Error::InvalidEscape(..));
+                    errors.push(e);
+                    if fatal {
+                        break;
+                    }
+
+                    // Restart from where the failed token began, past any whitespace
+                    (self.index, self.line, self.column) = (start, line, column);
+                    while let Some(next) = self.peek().filter(|c| is_json_whitespace(*c)) {
+                        self.whitespace(next);
+                    }
+                    let (start, line, column) = (self.index, self.line, self.column);
+                    self.skip_bad_token(in_string);
+                    let (token_type, value) = if in_string {
+                        (TokenType::String, JsonValue::String(Cow::Borrowed("")))
+                    } else {
+                        (TokenType::Null, JsonValue::Null)
+                    };
+                    tokens.push(Token {
+                        token_type,
+                        value: Some(value),
+                        line,
+                        column,
+                        start,
+                        end: self.index,
+                    });
                 }
+            }
+        }
+        if self.eof_token {
+            tokens.push(self.eof());
+        }
+        (tokens, errors)
+    }
 
-                let token = match next {
-                    JSON_QUOTE => self.lex_string()?,
-                    n if n.is_ascii_digit() => self.lex_number()?,
-                    '.' | '-' | 'e' | 'E' => self.lex_number()?,
-                    't' | 'f' => self.lex_boolean()?,
-                    'n' => self.lex_null()?,
-                    c => self.lex_syntax(c)?,
-                };
-                tokens.push(token);
+    fn eof(&self) -> Token<'a> {
+        Token {
+            token_type: TokenType::Eof,
+            value: None,
+            line: self.line,
+            column: self.column,
+            start: self.source.len(),
+            end: self.source.len(),
+        }
+    }
+
+    // Move past a token that failed to lex: a whole string, or at least one
+    // character and then up to the next whitespace or structural character
+    fn skip_bad_token(&mut self, in_string: bool) {
+        if in_string {
+            self.advance(); // Opening JSON_QUOTE
+            while let Some(next) = self.peek() {
+                if next == '\n' || next == '\r' {
+                    return;
+                }
+                self.advance();
+                if next == '\\' {
+                    self.advance();
+                } else if next == JSON_QUOTE {
+                    return;
+                }
             }
+            return;
         }
+        self.advance();
+        while let Some(next) = self.peek() {
+            let boundary = matches!(
+                next,
+                JSON_COMMA
+                    | JSON_COLON
+                    | JSON_QUOTE
+                    | JSON_LEFTBRACKET
+                    | JSON_RIGHTBRACKET
+                    | JSON_LEFTBRACE
+                    | JSON_RIGHTBRACE
+            );
+            if boundary || is_json_whitespace(next) {
+                return;
+            }
+            self.advance();
+        }
+    }
 
-        Ok(tokens)
+    // Step over one character, keeping the line and column in step
+    fn advance(&mut self) {
+        match self.peek() {
+            Some(next @ ('\n' | '\r')) => self.step(next as u8),
+            Some(next) => {
+                self.column += 1;
+                self.index += next.len_utf8();
+            }
+            None => {}
+        }
     }
 
-    fn lex_string(&mut self) -> Result<Token, Error> {
+    fn lex_token(&mut self) -> Option<Result<Token<'a>, Error>> {
+        while let Some(next) = self.peek() {
+            // Skip whitespace
+            if is_json_whitespace(next) {
+                self.whitespace(next);
+                continue;
+            }
+
+            // Skip comments, or keep them as tokens
+            if next == JSON_SLASH && self.allow_comments {
+                let (start, line, column) = (self.index, self.line, self.column);
+                if let Err(e) = self.comment() {
+                    return Some(Err(e));
+                }
+                if self.preserve_comments {
+                    return Some(Ok(Token {
+                        token_type: TokenType::Comment,
+                        value: Some(JsonValue::String(Cow::Borrowed(
+                            &self.source[start..self.index],
+                        ))),
+                        line,
+                        column,
+                        start,
+                        end: self.index,
+                    }));
+                }
+                continue;
+            }
+
+            let token = match next {
+                JSON_QUOTE => self.lex_string(),
+                n if n.is_ascii_digit() => self.lex_number(),
+                '.' | '-' | 'e' | 'E' => self.lex_number(),
+                't' | 'f' => self.lex_boolean(),
+                'n' => self.lex_null(),
+                c => self.lex_syntax(c),
+            };
+            return Some(token);
+        }
+
+        None
+    }
+
+    // Character at the cursor
+    fn peek(&self) -> Option<char> {
+        self.source[self.index..].chars().next()
+    }
+
+    fn lex_string(&mut self) -> Result<Token<'a>, Error> {
         let start_column = self.column;
+        let start_index = self.index;
 
         self.index += 1; // Move past JSON_QUOTE
         self.column += 1;
 
-        let mut chars = self.source.chars().skip(self.index);
-        // Find index of next JSON_QUOTE
-        let char_index = match chars.position(|c| c == JSON_QUOTE) {
-            Some(idx) => idx,
-            None => return Err(Error::UnexpectedEndOfString),
-        };
+        // Scan to the closing JSON_QUOTE, only allocating if an escape needs decoding
+        let source = self.source;
+        let bytes = source.as_bytes();
+        let start = self.index;
+        let mut chunk_start = start;
+        let mut decoded: Option<String> = None;
+        let mut i = start;
+        // Characters before `chunk_start`, counted as each chunk ends so the
+        // string is only walked once however many escapes it has
+        let mut width = 0;
+        loop {
+            match bytes.get(i) {
+                None => return Err(Error::UnexpectedEndOfString),
+                Some(b'"') => break,
+                Some(b'\\') => {
+                    let buf = decoded.get_or_insert_with(String::new);
+                    let chunk = &source[chunk_start..i];
+                    buf.push_str(chunk);
+                    width += chunk.chars().count();
+                    let end = self.lex_escape(i, self.column + width, buf)?;
+                    // Escapes are ASCII, one column per byte
+                    width += end - i;
+                    i = end;
+                    chunk_start = i;
+                }
+                Some(_) => i += 1,
+            }
+        }
 
-        // Get characters between JSON_QUOTE's
-        let json_string = self
-            .source
-            .chars()
-            .skip(self.index)
-            .take(char_index)
-            .collect::<String>();
+        let json_string = match decoded {
+            Some(mut buf) => {
+                buf.push_str(&source[chunk_start..i]);
+                Cow::Owned(buf)
+            }
+            None => Cow::Borrowed(&source[start..i]),
+        };
 
         // Increment position
-        let inc = json_string.len() + 1;
-        self.index += inc;
-        self.column += inc;
+        self.index += i - start + 1;
+        self.column += width + source[chunk_start..i].chars().count() + 1;
 
         Ok(Token {
             token_type: TokenType::String,
             value: Some(JsonValue::String(json_string)),
             line: self.line,
             column: start_column,
+            start: start_index,
+            end: self.index,
         })
     }
 
-    fn lex_number(&mut self) -> Result<Token, Error> {
+    // Decode the escape sequence starting at the backslash at byte `i` into `buf`,
+    // returning the byte offset just past it
+    fn lex_escape(&self, i: usize, column: usize, buf: &mut String) -> Result<usize, Error> {
+        let invalid = |len: usize| {
+            let sequence = self.source[i..].chars().take(len).collect::<String>();
+            Error::InvalidEscape(sequence, (self.line, column), i)
+        };
+
+        let decoded = match self.source.as_bytes().get(i + 1) {
+            None => return Err(Error::DanglingEscape((self.line, column), i)),
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'/') => '/',
+            Some(b'b') => '\u{8}',
+            Some(b'f') => '\u{c}',
+            Some(b'n') => '\n',
+            Some(b'r') => '\r',
+            Some(b't') => '\t',
+            Some(b'u') => {
+                let high = self.hex_escape(i).ok_or_else(|| invalid(6))?;
+                if !(0xD800..0xDC00).contains(&high) {
+                    let char = char::from_u32(high).ok_or_else(|| invalid(6))?;
+                    buf.push(char);
+                    return Ok(i + 6);
+                }
+
+                // High surrogate must be followed by an escaped low surrogate
+                if !self.source[i + 6..].starts_with("\\u") {
+                    return Err(invalid(6));
+                }
+                let low = self.hex_escape(i + 6).ok_or_else(|| invalid(12))?;
+                if !(0xDC00..0xE000).contains(&low) {
+                    return Err(invalid(12));
+                }
+                let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                buf.push(char::from_u32(code).ok_or_else(|| invalid(12))?);
+                return Ok(i + 12);
+            }
+            Some(_) => return Err(invalid(2)),
+        };
+        buf.push(decoded);
+        Ok(i + 2)
+    }
+
+    // Parse the four hex digits of a `\uXXXX` escape starting at byte `i`
+    fn hex_escape(&self, i: usize) -> Option<u32> {
+        let digits = self.source.get(i + 2..i + 6)?;
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        u32::from_str_radix(digits, 16).ok()
+    }
+
+    fn lex_number(&mut self) -> Result<Token<'a>, Error> {
         let start_column = self.column;
+        let start_index = self.index;
         let numeric_chars = [
-            '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', '.', '-', 'e', 'E',
+            b'0', b'1', b'2', b'3', b'4', b'5', b'6', b'7', b'8', b'9', b'.', b'-', b'+', b'e',
+            b'E',
         ];
 
-        let chars = self
-            .source
-            .chars()
-            .skip(self.index)
+        let rest = &self.source[self.index..];
+        let len = rest
+            .bytes()
             .take_while(|c| numeric_chars.contains(c))
-            .collect::<String>();
+            .count();
+        let chars = &rest[..len];
 
-        let number = chars.parse::<f64>()?;
+        // The lexeme is written back out as is, so it must be a JSON number
+        // and not just anything `f64` accepts, such as `.5` or `01`
+        let value = Some(chars)
+            .filter(|chars| is_json_number(chars))
+            .and_then(|chars| chars.parse::<f64>().ok())
+            .ok_or_else(|| {
+                Error::ParseNumber(chars.to_string(), (self.line, start_column), start_index)
+            })?;
+        let number = Number::new(value, Cow::Borrowed(chars));
+        if self.strict_integers && number.is_integer() && number.as_i64().is_none() {
+            return Err(Error::NumberOutOfRange(
+                chars.to_string(),
+                (self.line, start_column),
+                start_index,
+            ));
+        }
+        if self.strict_precision && !number.is_exact() {
+            return Err(Error::ImpreciseNumber(
+                chars.to_string(),
+                (self.line, start_column),
+                start_index,
+            ));
+        }
 
         // Increment position
-        let inc = chars.len();
-        self.index += inc;
-        self.column += inc;
+        self.index += len;
+        self.column += len;
 
         Ok(Token {
             token_type: TokenType::Number,
             value: Some(JsonValue::Number(number)),
             line: self.line,
             column: start_column,
+            start: start_index,
+            end: self.index,
         })
     }
 
-    fn lex_boolean(&mut self) -> Result<Token, Error> {
+    fn lex_boolean(&mut self) -> Result<Token<'a>, Error> {
         let keywords = ["true", "false"];
 
         for &keyword in &keywords {
@@ -120,6 +430,7 @@ impl<'a> Lexer<'a> {
                     _ => unreachable!(), // unreachable since keywords are known
                 };
                 let start_column = self.column;
+                let start_index = self.index;
 
                 // Increment position
                 let inc = keyword.len();
@@ -131,18 +442,25 @@ impl<'a> Lexer<'a> {
                     value: Some(json_value),
                     line: self.line,
                     column: start_column,
+                    start: start_index,
+                    end: self.index,
                 });
             }
         }
 
-        let char = self.source.chars().nth(self.index).unwrap();
-        Err(Error::UnexpectedCharacter(char, (self.line, self.column)))
+        let char = self.peek().unwrap();
+        Err(Error::UnexpectedCharacter(
+            char,
+            (self.line, self.column),
+            self.index,
+        ))
     }
 
-    fn lex_null(&mut self) -> Result<Token, Error> {
+    fn lex_null(&mut self) -> Result<Token<'a>, Error> {
         let null = "null";
         if self.source[self.index..].starts_with(null) {
             let start_column = self.column;
+            let start_index = self.index;
 
             // Increment position
             let inc = null.len();
@@ -154,14 +472,20 @@ impl<'a> Lexer<'a> {
                 value: Some(JsonValue::Null),
                 line: self.line,
                 column: start_column,
+                start: start_index,
+                end: self.index,
             })
         } else {
-            let char = self.source.chars().nth(self.index).unwrap();
-            Err(Error::UnexpectedCharacter(char, (self.line, self.column)))
+            let char = self.peek().unwrap();
+            Err(Error::UnexpectedCharacter(
+                char,
+                (self.line, self.column),
+                self.index,
+            ))
         }
     }
 
-    fn lex_syntax(&mut self, char: char) -> Result<Token, Error> {
+    fn lex_syntax(&mut self, char: char) -> Result<Token<'a>, Error> {
         let token_type = match char {
             JSON_COMMA => TokenType::Comma,
             JSON_COLON => TokenType::Colon,
@@ -169,10 +493,17 @@ impl<'a> Lexer<'a> {
             JSON_RIGHTBRACKET => TokenType::RightBracket,
             JSON_LEFTBRACE => TokenType::LeftBrace,
             JSON_RIGHTBRACE => TokenType::RightBrace,
-            c => return Err(Error::UnexpectedCharacter(c, (self.line, self.column))),
+            c => {
+                return Err(Error::UnexpectedCharacter(
+                    c,
+                    (self.line, self.column),
+                    self.index,
+                ))
+            }
         };
 
         let start_column = self.column;
+        let start_index = self.index;
 
         // Increment position
         self.index += 1;
@@ -183,20 +514,396 @@ impl<'a> Lexer<'a> {
             value: None,
             line: self.line,
             column: start_column,
+            start: start_index,
+            end: self.index,
         })
     }
 
+    // Skip a `//` or `/* */` comment, tracking lines within it
+    fn comment(&mut self) -> Result<(), Error> {
+        let rest = &self.source[self.index..];
+        if rest.starts_with("//") {
+            // Leave the line break for whitespace()
+            let len = rest.find(['\n', '\r']).unwrap_or(rest.len());
+            self.index += len;
+            self.column += rest[..len].chars().count();
+        } else if let Some(body) = rest.strip_prefix("/*") {
+            let position = (self.line, self.column);
+            let offset = self.index;
+            let len = match body.find("*/") {
+                Some(end) => end + 4,
+                None => return Err(Error::UnexpectedEndOfComment(position, offset)),
+            };
+            for _ in 0..len {
+                self.step(self.source.as_bytes()[self.index]);
+            }
+        } else {
+            return Err(Error::UnexpectedCharacter(
+                JSON_SLASH,
+                (self.line, self.column),
+                self.index,
+            ));
+        }
+        Ok(())
+    }
+
     // Skip token assignment and increment position
     fn whitespace(&mut self, char: char) {
-        if char == '\n' {
-            self.line += 1;
-            self.index += 1;
-            self.column = 0;
-        } else {
-            self.index += 1;
-            self.column += 1;
+        self.step(char as u8);
+    }
+
+    // Move past one byte outside a token. `\n`, `\r` and `\r\n` each end a
+    // line, as they do in editors, and only the first byte of a character
+    // takes a column.
+    fn step(&mut self, byte: u8) {
+        let after_cr = self.index > 0 && self.source.as_bytes()[self.index - 1] == b'\r';
+        match byte {
+            b'\n' if after_cr => {}
+            b'\n' | b'\r' => {
+                self.line += 1;
+                self.column = 1;
+            }
+            _ if is_continuation(byte) => {}
+            _ => self.column += 1,
+        }
+        self.index += 1;
+    }
+}
+
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Result<Token<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.ended {
+            return None;
+        }
+        let Some(token) = self.lex_token() else {
+            self.ended = true;
+            return self.eof_token.then(|| Ok(self.eof()));
+        };
+        if token.is_err() {
+            // Stop lexing after the first error
+            self.index = self.source.len();
+            self.ended = true;
+        }
+        Some(token)
+    }
+}
+
+// Lexes a stream a token at a time, so memory stays bounded by the largest
+// token rather than the whole input. Each token's bytes are gathered into a
+// small buffer and lexed with `Lexer`, so tokens and errors match the `&str`
+// path, with positions counted from the start of the stream. Tokens own their
+// values since there's no source to borrow from.
+pub struct ReaderLexer<R> {
+    reader: R,
+    buf: Vec<u8>,
+    line: usize,
+    column: usize,
+    offset: usize,
+    started: bool,
+    // Whether the last byte skipped was a `\r`, so a `\n` after it starts no
+    // new line
+    after_cr: bool,
+    done: bool,
+    allow_comments: bool,
+    strict_integers: bool,
+    strict_precision: bool,
+}
+
+const BOM_BYTES: &[u8] = "\u{FEFF}".as_bytes();
+
+// A byte inside a multibyte UTF-8 character, after its first
+fn is_continuation(byte: u8) -> bool {
+    byte & 0xC0 == 0x80
+}
+
+impl<R: BufRead> ReaderLexer<R> {
+    pub fn new(reader: R) -> Self {
+        ReaderLexer {
+            reader,
+            buf: Vec::new(),
+            line: 1,
+            column: 1,
+            offset: 0,
+            started: false,
+            after_cr: false,
+            done: false,
+            allow_comments: false,
+            strict_integers: false,
+            strict_precision: false,
+        }
+    }
+    pub fn allow_comments(mut self, allow: bool) -> Self {
+        self.allow_comments = allow;
+        self
+    }
+    pub fn strict_integers(mut self, strict: bool) -> Self {
+        self.strict_integers = strict;
+        self
+    }
+    pub fn strict_precision(mut self, strict: bool) -> Self {
+        self.strict_precision = strict;
+        self
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>, Error> {
+        Ok(self.reader.fill_buf()?.first().copied())
+    }
+    // Move the next byte onto the token buffer
+    fn take(&mut self) -> Result<Option<u8>, Error> {
+        let byte = self.peek()?;
+        if let Some(byte) = byte {
+            self.reader.consume(1);
+            self.buf.push(byte);
+            self.after_cr = false;
+        }
+        Ok(byte)
+    }
+    // Step over a byte outside any token
+    fn skip(&mut self, byte: u8) {
+        self.reader.consume(1);
+        self.offset += 1;
+        // `\r\n` is one line break, as in `Lexer`
+        match byte {
+            b'\n' if self.after_cr => {}
+            b'\n' | b'\r' => {
+                self.line += 1;
+                self.column = 1;
+            }
+            _ if is_continuation(byte) => {}
+            _ => self.column += 1,
+        }
+        self.after_cr = byte == b'\r';
+    }
+
+    fn lex_token(&mut self) -> Result<Option<Token<'static>>, Error> {
+        self.buf.clear();
+        if !self.started {
+            self.started = true;
+            if self.peek()? == Some(BOM_BYTES[0]) {
+                for _ in BOM_BYTES {
+                    self.take()?;
+                }
+                if self.buf == BOM_BYTES {
+                    // A leading byte order mark takes no column, as with `Lexer`
+                    self.buf.clear();
+                    self.offset += BOM_BYTES.len();
+                }
+            }
+        }
+
+        // Anything left from the check above is already a whole character
+        if self.buf.is_empty() {
+            let first = loop {
+                match self.peek()? {
+                    None => return Ok(None),
+                    Some(byte) if is_json_whitespace(char::from(byte)) => self.skip(byte),
+                    Some(b'/') if self.allow_comments => self.comment()?,
+                    Some(byte) => break byte,
+                }
+            };
+            self.gather(first)?;
+        }
+
+        let text = std::str::from_utf8(&self.buf)
+            .map_err(|e| Error::Io(io::Error::new(io::ErrorKind::InvalidData, e)))?;
+        let width = text.chars().count();
+        let token = Lexer::from(text)
+            .allow_comments(self.allow_comments)
+            .strict_integers(self.strict_integers)
+            .strict_precision(self.strict_precision)
+            .next()
+            .unwrap_or(Err(Error::UnexpectedEndOfInput))
+            .map_err(|e| self.shift(e))?;
+
+        let token = Token {
+            token_type: token.token_type,
+            value: token.value.map(JsonValue::into_owned),
+            line: self.line,
+            column: self.column,
+            start: self.offset + token.start,
+            end: self.offset + token.end,
+        };
+        // `Lexer` counts no lines inside a token, so neither does this
+        self.offset += self.buf.len();
+        self.column += width;
+        Ok(Some(token))
+    }
+
+    // Collect the bytes of the token starting with `first`
+    fn gather(&mut self, first: u8) -> Result<(), Error> {
+        match first {
+            b'"' => {
+                self.take()?;
+                while let Some(byte) = self.take()? {
+                    match byte {
+                        b'"' => break,
+                        b'\\' => {
+                            self.take()?;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            b't' | b'f' | b'n' => {
+                let keyword: &[u8] = match first {
+                    b't' => b"true",
+                    b'f' => b"false",
+                    _ => b"null",
+                };
+                // Stop at the first mismatch, which `Lexer` then reports
+                for &expected in keyword {
+                    if self.peek()? != Some(expected) {
+                        break;
+                    }
+                    self.take()?;
+                }
+            }
+            b'0'..=b'9' | b'.' | b'-' | b'e' | b'E' => {
+                while self
+                    .peek()?
+                    .is_some_and(|byte| byte.is_ascii_digit() || b".-eE".contains(&byte))
+                {
+                    self.take()?;
+                }
+            }
+            _ => {
+                // One whole character, so an unexpected one is reported intact
+                let len = match first {
+                    0xF0.. => 4,
+                    0xE0.. => 3,
+                    0xC0.. => 2,
+                    _ => 1,
+                };
+                for _ in 0..len {
+                    self.take()?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Skip a `//` or `/* */` comment, tracking lines within it
+    fn comment(&mut self) -> Result<(), Error> {
+        let position = (self.line, self.column);
+        let offset = self.offset;
+        self.skip(b'/');
+        match self.peek()? {
+            Some(b'/') => {
+                // Leave the line break for the whitespace loop
+                while let Some(byte) = self.peek()?.filter(|byte| !b"\n\r".contains(byte)) {
+                    self.skip(byte);
+                }
+            }
+            Some(b'*') => {
+                self.skip(b'*');
+                let mut star = false;
+                loop {
+                    let Some(byte) = self.peek()? else {
+                        return Err(Error::UnexpectedEndOfComment(position, offset));
+                    };
+                    self.skip(byte);
+                    if star && byte == b'/' {
+                        break;
+                    }
+                    star = byte == b'*';
+                }
+            }
+            _ => return Err(Error::UnexpectedCharacter(JSON_SLASH, position, offset)),
+        }
+        Ok(())
+    }
+
+    // Move an error's position from the token buffer to the stream
+    fn shift(&self, e: Error) -> Error {
+        let shift = |(_, column): (usize, usize), offset: usize| {
+            ((self.line, self.column + column - 1), self.offset + offset)
+        };
+        match e {
+            Error::UnexpectedCharacter(char, position, offset) => {
+                let (position, offset) = shift(position, offset);
+                Error::UnexpectedCharacter(char, position, offset)
+            }
+            Error::InvalidEscape(sequence, position, offset) => {
+                let (position, offset) = shift(position, offset);
+                Error::InvalidEscape(sequence, position, offset)
+            }
+            Error::DanglingEscape(position, offset) => {
+                let (position, offset) = shift(position, offset);
+                Error::DanglingEscape(position, offset)
+            }
+            Error::NumberOutOfRange(number, position, offset) => {
+                let (position, offset) = shift(position, offset);
+                Error::NumberOutOfRange(number, position, offset)
+            }
+            Error::ImpreciseNumber(number, position, offset) => {
+                let (position, offset) = shift(position, offset);
+                Error::ImpreciseNumber(number, position, offset)
+            }
+            Error::ParseNumber(number, position, offset) => {
+                let (position, offset) = shift(position, offset);
+                Error::ParseNumber(number, position, offset)
+            }
+            e => e,
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for ReaderLexer<R> {
+    type Item = Result<Token<'static>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let token = self.lex_token().transpose();
+        if !matches!(token, Some(Ok(_))) {
+            // Stop after the first error, or at the end of the stream
+            self.done = true;
+        }
+        token
+    }
+}
+
+// Whether `number` matches the RFC 8259 grammar
+// `-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?`
+fn is_json_number(number: &str) -> bool {
+    let bytes = number.as_bytes();
+    let mut i = 0;
+    let digits = |i: &mut usize| {
+        let start = *i;
+        while bytes.get(*i).is_some_and(u8::is_ascii_digit) {
+            *i += 1;
+        }
+        *i > start
+    };
+    if bytes.get(i) == Some(&b'-') {
+        i += 1;
+    }
+    match bytes.get(i) {
+        Some(b'0') => i += 1,
+        Some(b'1'..=b'9') => {
+            digits(&mut i);
+        }
+        _ => return false,
+    }
+    if bytes.get(i) == Some(&b'.') {
+        i += 1;
+        if !digits(&mut i) {
+            return false;
+        }
+    }
+    if matches!(bytes.get(i), Some(b'e' | b'E')) {
+        i += 1;
+        if matches!(bytes.get(i), Some(b'+' | b'-')) {
+            i += 1;
+        }
+        if !digits(&mut i) {
+            return false;
         }
     }
+    i == bytes.len()
 }
 
 #[cfg(test)]
@@ -222,14 +929,18 @@ mod tests {
             Token {
                 token_type: TokenType::LeftBrace,
                 value: None,
-                line: 0,
-                column: 0,
+                line: 1,
+                column: 1,
+                start: 0,
+                end: 1,
             },
             Token {
                 token_type: TokenType::RightBrace,
                 value: None,
-                line: 0,
-                column: 1,
+                line: 1,
+                column: 2,
+                start: 1,
+                end: 2,
             },
         ];
         assert_eq!(expected, tokens.unwrap());
@@ -245,37 +956,48 @@ mod tests {
             Token {
                 token_type: TokenType::LeftBrace,
                 value: None,
-                line: 0,
-                column: 0,
+                line: 1,
+                column: 1,
+                start: 0,
+                end: 1,
             },
             Token {
                 token_type: TokenType::String,
-                value: Some(JsonValue::String("key".to_string())),
-                line: 0,
-                column: 1,
+                value: Some(JsonValue::String("key".into())),
+                line: 1,
+                column: 2,
+                start: 1,
+                end: 6,
             },
             Token {
                 token_type: TokenType::Colon,
                 value: None,
-                line: 0,
-                column: 6,
+                line: 1,
+                column: 7,
+                start: 6,
+                end: 7,
             },
             Token {
                 token_type: TokenType::String,
-                value: Some(JsonValue::String("value".to_string())),
-                line: 0,
-                column: 7,
+                value: Some(JsonValue::String("value".into())),
+                line: 1,
+                column: 8,
+                start: 7,
+                end: 14,
             },
             Token {
                 token_type: TokenType::RightBrace,
                 value: None,
-                line: 0,
-                column: 14,
+                line: 1,
+                column: 15,
+                start: 14,
+                end: 15,
             },
         ];
         assert_eq!(expected, tokens.unwrap());
     }
     #[test]
+    #[allow(clippy::approx_constant)]
     fn lex_numeric_key_value_pair() {
         let input = r#"{"key":3.14}"#;
         let mut lexer = Lexer::from(input);
@@ -286,32 +1008,42 @@ mod tests {
             Token {
                 token_type: TokenType::LeftBrace,
                 value: None,
-                line: 0,
-                column: 0,
+                line: 1,
+                column: 1,
+                start: 0,
+                end: 1,
             },
             Token {
                 token_type: TokenType::String,
-                value: Some(JsonValue::String("key".to_string())),
-                line: 0,
-                column: 1,
+                value: Some(JsonValue::String("key".into())),
+                line: 1,
+                column: 2,
+                start: 1,
+                end: 6,
             },
             Token {
                 token_type: TokenType::Colon,
                 value: None,
-                line: 0,
-                column: 6,
+                line: 1,
+                column: 7,
+                start: 6,
+                end: 7,
             },
             Token {
                 token_type: TokenType::Number,
-                value: Some(JsonValue::Number(3.14)),
-                line: 0,
-                column: 7,
+                value: Some(JsonValue::from(3.14)),
+                line: 1,
+                column: 8,
+                start: 7,
+                end: 11,
             },
             Token {
                 token_type: TokenType::RightBrace,
                 value: None,
-                line: 0,
-                column: 11,
+                line: 1,
+                column: 12,
+                start: 11,
+                end: 12,
             },
         ];
         assert_eq!(expected, tokens.unwrap());
@@ -327,32 +1059,42 @@ mod tests {
             Token {
                 token_type: TokenType::LeftBrace,
                 value: None,
-                line: 0,
-                column: 0,
+                line: 1,
+                column: 1,
+                start: 0,
+                end: 1,
             },
             Token {
                 token_type: TokenType::String,
-                value: Some(JsonValue::String("key".to_string())),
-                line: 0,
-                column: 1,
+                value: Some(JsonValue::String("key".into())),
+                line: 1,
+                column: 2,
+                start: 1,
+                end: 6,
             },
             Token {
                 token_type: TokenType::Colon,
                 value: None,
-                line: 0,
-                column: 6,
+                line: 1,
+                column: 7,
+                start: 6,
+                end: 7,
             },
             Token {
                 token_type: TokenType::Bool,
                 value: Some(JsonValue::Bool(true)),
-                line: 0,
-                column: 7,
+                line: 1,
+                column: 8,
+                start: 7,
+                end: 11,
             },
             Token {
                 token_type: TokenType::RightBrace,
                 value: None,
-                line: 0,
-                column: 11,
+                line: 1,
+                column: 12,
+                start: 11,
+                end: 12,
             },
         ];
         assert_eq!(expected, tokens.unwrap());
@@ -368,32 +1110,42 @@ mod tests {
             Token {
                 token_type: TokenType::LeftBrace,
                 value: None,
-                line: 0,
-                column: 0,
+                line: 1,
+                column: 1,
+                start: 0,
+                end: 1,
             },
             Token {
                 token_type: TokenType::String,
-                value: Some(JsonValue::String("key".to_string())),
-                line: 0,
-                column: 1,
+                value: Some(JsonValue::String("key".into())),
+                line: 1,
+                column: 2,
+                start: 1,
+                end: 6,
             },
             Token {
                 token_type: TokenType::Colon,
                 value: None,
-                line: 0,
-                column: 6,
+                line: 1,
+                column: 7,
+                start: 6,
+                end: 7,
             },
             Token {
                 token_type: TokenType::Null,
                 value: Some(JsonValue::Null),
-                line: 0,
-                column: 7,
+                line: 1,
+                column: 8,
+                start: 7,
+                end: 11,
             },
             Token {
                 token_type: TokenType::RightBrace,
                 value: None,
-                line: 0,
-                column: 11,
+                line: 1,
+                column: 12,
+                start: 11,
+                end: 12,
             },
         ];
         assert_eq!(expected, tokens.unwrap());
@@ -409,44 +1161,58 @@ mod tests {
             Token {
                 token_type: TokenType::LeftBrace,
                 value: None,
-                line: 0,
-                column: 0,
+                line: 1,
+                column: 1,
+                start: 0,
+                end: 1,
             },
             Token {
                 token_type: TokenType::String,
-                value: Some(JsonValue::String("obj".to_string())),
-                line: 0,
-                column: 1,
+                value: Some(JsonValue::String("obj".into())),
+                line: 1,
+                column: 2,
+                start: 1,
+                end: 6,
             },
             Token {
                 token_type: TokenType::Colon,
                 value: None,
-                line: 0,
-                column: 6,
+                line: 1,
+                column: 7,
+                start: 6,
+                end: 7,
             },
             Token {
                 token_type: TokenType::LeftBracket,
                 value: None,
-                line: 0,
-                column: 7,
+                line: 1,
+                column: 8,
+                start: 7,
+                end: 8,
             },
             Token {
                 token_type: TokenType::String,
-                value: Some(JsonValue::String("value".to_string())),
-                line: 0,
-                column: 8,
+                value: Some(JsonValue::String("value".into())),
+                line: 1,
+                column: 9,
+                start: 8,
+                end: 15,
             },
             Token {
                 token_type: TokenType::RightBracket,
                 value: None,
-                line: 0,
-                column: 15,
+                line: 1,
+                column: 16,
+                start: 15,
+                end: 16,
             },
             Token {
                 token_type: TokenType::RightBrace,
                 value: None,
-                line: 0,
-                column: 16,
+                line: 1,
+                column: 17,
+                start: 16,
+                end: 17,
             },
         ];
         assert_eq!(expected, tokens.unwrap());
@@ -462,58 +1228,555 @@ mod tests {
             Token {
                 token_type: TokenType::LeftBrace,
                 value: None,
-                line: 0,
-                column: 0,
+                line: 1,
+                column: 1,
+                start: 0,
+                end: 1,
             },
             Token {
                 token_type: TokenType::String,
-                value: Some(JsonValue::String("obj".to_string())),
-                line: 0,
-                column: 1,
+                value: Some(JsonValue::String("obj".into())),
+                line: 1,
+                column: 2,
+                start: 1,
+                end: 6,
             },
             Token {
                 token_type: TokenType::Colon,
                 value: None,
-                line: 0,
-                column: 6,
+                line: 1,
+                column: 7,
+                start: 6,
+                end: 7,
             },
             Token {
                 token_type: TokenType::LeftBrace,
                 value: None,
-                line: 0,
-                column: 7,
+                line: 1,
+                column: 8,
+                start: 7,
+                end: 8,
             },
             Token {
                 token_type: TokenType::String,
-                value: Some(JsonValue::String("key".to_string())),
-                line: 0,
-                column: 8,
+                value: Some(JsonValue::String("key".into())),
+                line: 1,
+                column: 9,
+                start: 8,
+                end: 13,
             },
             Token {
                 token_type: TokenType::Colon,
                 value: None,
-                line: 0,
-                column: 13,
+                line: 1,
+                column: 14,
+                start: 13,
+                end: 14,
             },
             Token {
                 token_type: TokenType::String,
-                value: Some(JsonValue::String("value".to_string())),
-                line: 0,
-                column: 14,
+                value: Some(JsonValue::String("value".into())),
+                line: 1,
+                column: 15,
+                start: 14,
+                end: 21,
             },
             Token {
                 token_type: TokenType::RightBrace,
                 value: None,
-                line: 0,
-                column: 21,
+                line: 1,
+                column: 22,
+                start: 21,
+                end: 22,
             },
             Token {
                 token_type: TokenType::RightBrace,
                 value: None,
-                line: 0,
-                column: 22,
+                line: 1,
+                column: 23,
+                start: 22,
+                end: 23,
             },
         ];
         assert_eq!(expected, tokens.unwrap());
     }
+    #[test]
+    fn lex_multibyte_before_keywords() {
+        let input = r#"{"café": true, "naïve": null}"#;
+        let mut lexer = Lexer::from(input);
+        let tokens = lexer.lex().unwrap();
+
+        let values = tokens
+            .into_iter()
+            .filter_map(|token| token.value)
+            .collect::<Vec<_>>();
+        let expected = vec![
+            JsonValue::String("café".into()),
+            JsonValue::Bool(true),
+            JsonValue::String("naïve".into()),
+            JsonValue::Null,
+        ];
+        assert_eq!(expected, values);
+    }
+    #[test]
+    fn lex_multibyte_before_syntax_error() {
+        let input = r#"["日本", x]"#;
+        let mut lexer = Lexer::from(input);
+        let result = lexer.lex();
+        assert!(matches!(
+            result,
+            Err(Error::UnexpectedCharacter('x', (1, 8), 11))
+        ));
+    }
+    #[test]
+    fn lex_malformed_number_reports_position() {
+        let result = Lexer::from("[1,\n  1.2.3]").lex();
+        match result {
+            Err(error @ Error::ParseNumber(..)) => {
+                assert_eq!(error.position(), Some((2, 3)));
+                assert_eq!(
+                    error.to_string(),
+                    "Invalid number: 1.2.3, line 2 column 3 (byte 6)"
+                );
+            }
+            other => panic!("expected a number error, got {:?}", other),
+        }
+    }
+    #[test]
+    fn lex_numbers_by_the_json_grammar() {
+        for number in [
+            "0", "-0", "7", "-12", "0.5", "1.25", "1e5", "1E+2", "2e-3", "-0.0E-0",
+        ] {
+            let tokens = Lexer::from(number).lex().unwrap();
+            assert_eq!(tokens[0].token_type, TokenType::Number, "{}", number);
+        }
+        for number in [
+            "01", "-01", ".5", "5.", "1.e5", "-", "1e", "1e+", "1+2", "--1", "e5",
+        ] {
+            assert!(
+                matches!(Lexer::from(number).lex(), Err(Error::ParseNumber(..))),
+                "{}",
+                number
+            );
+        }
+        // A number can't start with a sign other than `-`
+        assert!(matches!(
+            Lexer::from("+1").lex(),
+            Err(Error::UnexpectedCharacter('+', (1, 1), 0))
+        ));
+    }
+    #[test]
+    fn lex_stray_character_after_document() {
+        let result = Lexer::from("{}%").lex();
+        assert!(matches!(
+            result,
+            Err(Error::UnexpectedCharacter('%', (1, 3), 2))
+        ));
+        let result = Lexer::from("{\n}  x").lex();
+        assert!(matches!(
+            result,
+            Err(Error::UnexpectedCharacter('x', (2, 4), 5))
+        ));
+    }
+    #[test]
+    fn lex_rejects_form_feed_and_vertical_tab() {
+        let result = Lexer::from("[1,\x0C2]").lex();
+        assert!(matches!(
+            result,
+            Err(Error::UnexpectedCharacter('\x0C', (1, 4), 3))
+        ));
+        let result = ReaderLexer::new("{}\x0B".as_bytes()).collect::<Result<Vec<_>, _>>();
+        assert!(matches!(
+            result,
+            Err(Error::UnexpectedCharacter('\x0B', (1, 3), 2))
+        ));
+        let error = Lexer::from("\x0C").lex().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unexpected character: U+000C, line 1 column 1 (byte 0)"
+        );
+        assert!(Lexer::from(" \t\r\n[ 1 ,\t2 ]\r\n").lex().is_ok());
+    }
+    #[test]
+    fn lex_counts_crlf_and_lone_cr_as_line_breaks() {
+        for input in ["[1,\r\n2,\r\n  x]", "[1,\r2,\n  x]"] {
+            let error = Lexer::from(input).lex().unwrap_err();
+            assert!(matches!(error, Error::UnexpectedCharacter('x', (3, 3), _)));
+            let error = ReaderLexer::new(input.as_bytes())
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap_err();
+            assert!(matches!(error, Error::UnexpectedCharacter('x', (3, 3), _)));
+        }
+        let input = "/* a\r\nb */ // c\r{\r\n}";
+        let tokens = Lexer::from(input).allow_comments(true).lex().unwrap();
+        let positions = tokens
+            .iter()
+            .map(|t| (t.line, t.column))
+            .collect::<Vec<_>>();
+        assert_eq!(positions, [(3, 1), (4, 1)]);
+        let tokens = ReaderLexer::new(input.as_bytes())
+            .allow_comments(true)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let positions = tokens
+            .iter()
+            .map(|t| (t.line, t.column))
+            .collect::<Vec<_>>();
+        assert_eq!(positions, [(3, 1), (4, 1)]);
+    }
+    #[test]
+    fn lex_backslash_at_end_of_input() {
+        let input = "[\"abc\\";
+        let error = Lexer::from(input).lex().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unterminated string: input ends after the backslash at line 1 column 6 (byte 5)"
+        );
+        let error = ReaderLexer::new(input.as_bytes())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_err();
+        assert!(matches!(error, Error::DanglingEscape((1, 6), 5)));
+        // An escaped quote leaves the string open, which is a different error
+        let result = Lexer::from("\"abc\\\"").lex();
+        assert!(matches!(result, Err(Error::UnexpectedEndOfString)));
+    }
+    #[test]
+    fn lex_eof_token_on_request() {
+        let tokens = Lexer::from("[1,\n 2] ").eof_token(true).lex().unwrap();
+        let last = tokens.last().unwrap();
+        assert_eq!(tokens.len(), 6);
+        assert_eq!(last.token_type, TokenType::Eof);
+        assert_eq!((last.line, last.column, last.start, last.end), (2, 5, 8, 8));
+        assert!(crate::parser::Parser::new(&tokens).parse().is_ok());
+        let (tokens, _) = Lexer::from("").eof_token(true).lex_all();
+        assert_eq!(tokens.len(), 1);
+        // The stream ends at the first error, with no `Eof` after it
+        let results = Lexer::from("[1, ~").eof_token(true).collect::<Vec<_>>();
+        assert_eq!(results.len(), 4);
+        assert!(results[3].is_err());
+        assert_eq!(Lexer::from("[]").lex().unwrap().len(), 2);
+    }
+    #[test]
+    fn columns_after_several_escapes() {
+        let error = Lexer::from(r#"["é\n\t\q"]"#).lex().unwrap_err();
+        assert!(matches!(error, Error::InvalidEscape(_, (1, 8), 8)));
+        let tokens = Lexer::from(r#"["é\n\u00e9x", 1]"#).lex().unwrap();
+        assert_eq!((tokens[3].column, tokens[3].start), (16, 16));
+    }
+    #[test]
+    fn columns_count_characters_after_multibyte_text() {
+        // Each of "é", "日本" and "😀" is one column per character
+        let input = "{\"é\": \"日本\", /* 😀 */ \"k\": ~}";
+        let options = |lexer: Lexer<'static>| lexer.allow_comments(true);
+        let error = options(Lexer::from(input)).lex().unwrap_err();
+        assert!(matches!(
+            error,
+            Error::UnexpectedCharacter('~', (1, 26), 33)
+        ));
+        let error = ReaderLexer::new(input.as_bytes())
+            .allow_comments(true)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            Error::UnexpectedCharacter('~', (1, 26), 33)
+        ));
+
+        let input = "[\"\\u00e9é\", // ü\n  \"ü\\q\"]";
+        let error = options(Lexer::from(input)).lex().unwrap_err();
+        assert!(matches!(error, Error::InvalidEscape(_, (2, 5), _)));
+        let tokens = Lexer::from("[\"é\", 1]").lex().unwrap();
+        let columns = tokens.iter().map(|t| t.column).collect::<Vec<_>>();
+        assert_eq!(columns, [1, 2, 5, 7, 8]);
+    }
+    #[test]
+    fn lex_multibyte_unexpected_character() {
+        let input = "[é]";
+        let mut lexer = Lexer::from(input);
+        let result = lexer.lex();
+        assert!(matches!(
+            result,
+            Err(Error::UnexpectedCharacter('é', (1, 2), 1))
+        ));
+    }
+    #[test]
+    fn lex_string_without_escapes_borrows() {
+        let input = r#""plain""#;
+        let mut lexer = Lexer::from(input);
+        let tokens = lexer.lex().unwrap();
+        match &tokens[0].value {
+            Some(JsonValue::String(Cow::Borrowed(str))) => assert_eq!(*str, "plain"),
+            other => panic!("expected borrowed string, got {:?}", other),
+        }
+    }
+    #[test]
+    fn lex_string_with_escapes() {
+        let input = r#"["a\"b\\c\/d\n\t", "\u00e9\ud83d\ude00"]"#;
+        let mut lexer = Lexer::from(input);
+        let tokens = lexer.lex().unwrap();
+        assert_eq!(
+            tokens[1].value,
+            Some(JsonValue::String(Cow::Owned("a\"b\\c/d\n\t".to_string())))
+        );
+        assert_eq!(tokens[3].value, Some(JsonValue::String("é😀".into())));
+        assert_eq!(tokens[4].column, 40);
+    }
+    #[test]
+    fn lex_string_with_invalid_escape() {
+        let invalid = [r#""\x""#, r#""\u12G4""#, r#""\ud83d""#, r#""\ud83d\u0041""#];
+        for input in invalid {
+            let mut lexer = Lexer::from(input);
+            let result = lexer.lex();
+            assert!(
+                matches!(result, Err(Error::InvalidEscape(_, (1, 2), 1))),
+                "{}: {:?}",
+                input,
+                result
+            );
+        }
+    }
+    #[test]
+    fn lex_lazily_with_iterator() {
+        let input = r#"[1, "two"] ~"#;
+        let mut lexer = Lexer::from(input);
+        assert_eq!(
+            lexer.next().unwrap().unwrap().token_type,
+            TokenType::LeftBracket
+        );
+        assert_eq!(lexer.next().unwrap().unwrap().token_type, TokenType::Number);
+
+        let rest = lexer.collect::<Vec<_>>();
+        assert_eq!(rest.len(), 4);
+        assert!(matches!(
+            rest[3],
+            Err(Error::UnexpectedCharacter('~', (1, 12), 11))
+        ));
+    }
+    #[test]
+    fn lex_iterator_stops_after_error() {
+        let mut lexer = Lexer::from("~ []");
+        assert!(matches!(lexer.next(), Some(Err(_))));
+        assert!(lexer.next().is_none());
+    }
+    #[test]
+    fn lex_large_integers() {
+        let input = "[99999999999999999999, -9223372036854775808, 1e400]";
+        let tokens = Lexer::from(input).lex().unwrap();
+        let numbers = tokens
+            .iter()
+            .filter_map(|token| match &token.value {
+                Some(JsonValue::Number(number)) => Some(number),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(numbers[0].raw(), "99999999999999999999");
+        assert_eq!(numbers[0].as_i64(), None);
+        assert_eq!(numbers[1].as_i64(), Some(i64::MIN));
+        assert!(!numbers[2].is_integer());
+
+        let result = Lexer::from(input).strict_integers(true).lex();
+        assert!(matches!(
+            result,
+            Err(Error::NumberOutOfRange(ref n, (1, 2), 1)) if n == "99999999999999999999"
+        ));
+        let tokens = Lexer::from("[-9223372036854775808, 1e400]")
+            .strict_integers(true)
+            .lex();
+        assert!(tokens.is_ok());
+    }
+    #[test]
+    fn lex_imprecise_integers() {
+        let input = "[9007199254740992, 9007199254740993, 1.0000000000000001, 1e300]";
+        let exact = Lexer::from(input)
+            .lex()
+            .unwrap()
+            .into_iter()
+            .filter_map(|token| match token.value {
+                Some(JsonValue::Number(number)) => Some(number.is_exact()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(exact, [true, false, true, true]);
+
+        let result = Lexer::from(input).strict_precision(true).lex();
+        assert!(matches!(
+            result,
+            Err(Error::ImpreciseNumber(ref n, (1, 20), 19)) if n == "9007199254740993"
+        ));
+        let result = ReaderLexer::new(input.as_bytes())
+            .strict_precision(true)
+            .collect::<Result<Vec<_>, _>>();
+        assert!(matches!(
+            result,
+            Err(Error::ImpreciseNumber(_, (1, 20), 19))
+        ));
+    }
+    #[test]
+    fn lex_all_recovers_after_errors() {
+        let (tokens, errors) = Lexer::from("[nul, \"a\\q\", 1]").lex_all();
+        assert!(matches!(
+            errors[..],
+            [
+                Error::UnexpectedCharacter('n', (1, 2), 1),
+                Error::InvalidEscape(_, (1, 9), 8)
+            ]
+        ));
+        let kinds = tokens.iter().map(|t| t.token_type).collect::<Vec<_>>();
+        use TokenType::*;
+        assert_eq!(
+            kinds,
+            [
+                LeftBracket,
+                Null,
+                Comma,
+                String,
+                Comma,
+                Number,
+                RightBracket
+            ]
+        );
+        assert_eq!((tokens[3].start, tokens[3].end), (6, 11));
+
+        let (tokens, errors) = Lexer::from("[1, \"open").lex_all();
+        assert_eq!(tokens.len(), 3);
+        assert!(matches!(errors[..], [Error::UnexpectedEndOfString]));
+    }
+    #[test]
+    fn lex_reset_restarts_positions() {
+        let mut lexer = Lexer::from("[1,\n 2]").allow_comments(true);
+        assert_eq!(lexer.lex().unwrap().len(), 5);
+
+        lexer.reset("\u{FEFF}/* c */ {\"a\": ~}");
+        let tokens = lexer
+            .by_ref()
+            .take(3)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let positions = tokens
+            .iter()
+            .map(|token| (token.line, token.column, token.start))
+            .collect::<Vec<_>>();
+        assert_eq!(positions, [(1, 9, 11), (1, 10, 12), (1, 13, 15)]);
+        assert!(matches!(
+            lexer.next(),
+            Some(Err(Error::UnexpectedCharacter('~', (1, 15), 17)))
+        ));
+    }
+    #[test]
+    fn lex_skips_leading_bom() {
+        let input = "\u{FEFF}{}";
+        let mut lexer = Lexer::from(input);
+        let tokens = lexer.lex().unwrap();
+        assert_eq!(tokens.len(), 2);
+        assert_eq!(tokens[0].token_type, TokenType::LeftBrace);
+        assert_eq!(tokens[0].column, 1);
+    }
+    #[test]
+    fn lex_rejects_bom_after_start() {
+        let input = "{\u{FEFF}}";
+        let mut lexer = Lexer::from(input);
+        let result = lexer.lex();
+        assert!(matches!(
+            result,
+            Err(Error::UnexpectedCharacter('\u{FEFF}', (1, 2), 1))
+        ));
+    }
+    #[test]
+    fn lex_rejects_comments_by_default() {
+        let input = "// comment\n{}";
+        let mut lexer = Lexer::from(input);
+        let result = lexer.lex();
+        assert!(matches!(
+            result,
+            Err(Error::UnexpectedCharacter('/', (1, 1), 0))
+        ));
+    }
+    #[test]
+    fn lex_skips_line_comments() {
+        let input = "// leading\n{\"a\": 1 // trailing\n}";
+        let mut lexer = Lexer::from(input).allow_comments(true);
+        let tokens = lexer.lex().unwrap();
+        assert_eq!(tokens.len(), 5);
+        assert_eq!((tokens[1].line, tokens[1].column), (2, 2));
+        assert_eq!((tokens[4].line, tokens[4].column), (3, 1));
+    }
+    #[test]
+    fn lex_skips_block_comments() {
+        let input = "/* one\n two\n */ [1, /* inline */ 2]";
+        let mut lexer = Lexer::from(input).allow_comments(true);
+        let tokens = lexer.lex().unwrap();
+        let types = tokens.iter().map(|t| &t.token_type).collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            vec![
+                &TokenType::LeftBracket,
+                &TokenType::Number,
+                &TokenType::Comma,
+                &TokenType::Number,
+                &TokenType::RightBracket
+            ]
+        );
+        assert_eq!((tokens[0].line, tokens[0].column), (3, 5));
+        assert_eq!((tokens[3].line, tokens[3].column), (3, 22));
+    }
+    #[test]
+    fn lex_unterminated_block_comment() {
+        let input = "[1] /* never closed";
+        let mut lexer = Lexer::from(input).allow_comments(true);
+        let result = lexer.lex();
+        assert!(matches!(
+            result,
+            Err(Error::UnexpectedEndOfComment((1, 5), 4))
+        ));
+    }
+    #[test]
+    fn lex_token_spans() {
+        let input = "{\n  \"café\": [true, -1.5e3, null]\n}";
+        let mut lexer = Lexer::from(input);
+        let tokens = lexer.lex().unwrap();
+        let spans = tokens
+            .iter()
+            .map(|token| &input[token.start..token.end])
+            .collect::<Vec<_>>();
+        let expected = vec![
+            "{",
+            "\"café\"",
+            ":",
+            "[",
+            "true",
+            ",",
+            "-1.5e3",
+            ",",
+            "null",
+            "]",
+            "}",
+        ];
+        assert_eq!(expected, spans);
+    }
+    #[test]
+    fn reader_lexer_matches_lexer() {
+        let input = "\u{FEFF}{\n  \"café\": [true, -1.5e3, null, \"a\\u00e9\"]\n}";
+        let streamed = ReaderLexer::new(io::BufReader::with_capacity(2, input.as_bytes()))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(streamed, Lexer::from(input).lex().unwrap());
+
+        let mut lexer = ReaderLexer::new("[1,\n ~]".as_bytes());
+        assert!(matches!(
+            lexer.nth(3),
+            Some(Err(Error::UnexpectedCharacter('~', (2, 2), 5)))
+        ));
+        assert!(lexer.next().is_none());
+    }
+    #[test]
+    fn lex_error_byte_offset() {
+        let input = "[\"é\",\n x]";
+        let mut lexer = Lexer::from(input);
+        let error = lexer.lex().unwrap_err();
+        assert!(matches!(error, Error::UnexpectedCharacter('x', (2, 2), 8)));
+        assert_eq!(
+            error.to_string(),
+            "Unexpected character: x, line 2 column 2 (byte 8)"
+        );
+    }
 }
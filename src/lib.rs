@@ -1,98 +1,1024 @@
-use lexer::Lexer;
-use parser::Parser;
-use types::{Error, Token, TokenType};
+use std::io::{self, BufRead, Write};
 
+use color::Highlight;
+
+pub use canonical::to_canonical;
+pub use csv::to_csv;
+pub use diff::{diff, Change};
+pub use flatten::{flatten, unflatten};
+pub use html::{to_html, STYLESHEET};
+pub use keys::{drop_nulls, omit_keys, select_keys};
+pub use lexer::{Lexer, ReaderLexer};
+pub use merge::{merge, merge_concat, merge_conflict};
+pub use options::{FormatOptions, KeyOrder, Layout, Newline, ParseOptions, Style, Theme};
+pub use parser::Parser;
+pub use path::{select_path, select_type};
+pub use pointer::{delete_pointer, resolve_pointer, set_pointer};
+pub use serializer::{format_into, format_value, format_value_with, serialize_pretty};
+pub use stats::Stats;
+pub use types::{Error, ErrorKind, JsonValue, Number, Token, TokenType, ValueType};
+pub use yaml::to_yaml;
+
+mod canonical;
+mod color;
+mod csv;
+mod diff;
+mod escape;
+mod flatten;
+mod html;
+mod keys;
 mod lexer;
+mod macros;
+mod merge;
+mod options;
 mod parser;
+mod path;
+mod pointer;
 mod prelude;
+#[cfg(feature = "serde")]
+mod serde_support;
+mod serializer;
+mod stats;
 mod types;
+mod yaml;
+
+pub fn parse(
+    input: &str,
+    options: &ParseOptions,
+    format_options: &FormatOptions,
+) -> Result<String, Error> {
+    // Transforms like key sorting work on the parsed value instead of the tokens
+    if format_options.needs_value() {
+        let value = parse_value_with(input, options)?;
+        return Ok(serializer::serialize_pretty(&value, format_options));
+    }
+    let tokens = validate_tokens(input, options)?;
+
+    // Format output
+    format(&tokens, format_options)
+}
+
+// The token stream with positions, for tooling such as syntax highlighters
+pub fn tokenize(input: &str) -> Result<Vec<Token<'_>>, Error> {
+    tokenize_with(input, &ParseOptions::default())
+}
+
+pub fn tokenize_with<'a>(input: &'a str, options: &ParseOptions) -> Result<Vec<Token<'a>>, Error> {
+    lexer(input, options).lex()
+}
+
+fn lexer<'a>(input: &'a str, options: &ParseOptions) -> Lexer<'a> {
+    Lexer::from(input)
+        .allow_comments(options.allow_comments)
+        .strict_integers(options.strict_integers)
+        .strict_precision(options.strict_precision)
+}
+
+// Check that the input is a single valid document without building its value.
+// Tokens are lexed one at a time and checked as they come, so this stops at
+// the first error, reporting the same error as `validate_reader`.
+pub fn validate(input: &str, options: &ParseOptions) -> Result<(), Error> {
+    parser::validate_stream(
+        lexer(input, options),
+        options.allow_trailing_commas,
+        parser::DEFAULT_MAX_DEPTH,
+    )
+}
+
+// Validate a stream without reading it all into memory, for inputs too big
+// to hold as a `String`. `validate` is faster when the input is already loaded.
+pub fn validate_reader<R: BufRead>(reader: R, options: &ParseOptions) -> Result<(), Error> {
+    let tokens = ReaderLexer::new(reader)
+        .allow_comments(options.allow_comments)
+        .strict_integers(options.strict_integers)
+        .strict_precision(options.strict_precision);
+    parser::validate_stream(
+        tokens,
+        options.allow_trailing_commas,
+        parser::DEFAULT_MAX_DEPTH,
+    )
+}
+
+// Every lexical and syntax error in the input, in order of position; empty when
+// the input is valid. Slower than `validate`, which stops at the first error.
+pub fn validate_all(input: &str, options: &ParseOptions) -> Vec<Error> {
+    let (tokens, mut errors) = lexer(input, options).lex_all();
+    // A character the lexer rejected is reported by the lexer alone, not again
+    // by the parser when it meets the placeholder token left in its place
+    let rejected = errors.iter().map(Error::position).collect::<Vec<_>>();
+    errors.extend(
+        Parser::new(&tokens)
+            .allow_trailing_commas(options.allow_trailing_commas)
+            .collect_errors()
+            .into_iter()
+            .filter(|e| e.position().is_none() || !rejected.contains(&e.position())),
+    );
+    errors.sort_by_key(|e| e.position().unwrap_or((usize::MAX, usize::MAX)));
+    errors
+}
+
+// As `validate_all`, for each line of NDJSON
+pub fn validate_all_ndjson(input: &str, options: &ParseOptions) -> Vec<Error> {
+    ndjson_lines(input)
+        .flat_map(|(line, start, document)| {
+            validate_all(document, options)
+                .into_iter()
+                .map(move |e| e.on_line(line, start))
+        })
+        .collect()
+}
+
+pub fn parse_ndjson(
+    input: &str,
+    options: &ParseOptions,
+    format_options: &FormatOptions,
+) -> Result<String, Error> {
+    let documents = ndjson_documents(input, |document| parse(document, options, format_options))?;
+    Ok(documents.join(format_options.eol.as_str()))
+}
+
+pub fn validate_ndjson(input: &str, options: &ParseOptions) -> Result<(), Error> {
+    ndjson_documents(input, |document| validate(document, options)).map(|_| ())
+}
+
+// Format documents concatenated with nothing but optional whitespace between
+// them, one after another and separated by a line break. Unlike NDJSON a
+// document may span lines, and several may share one.
+pub fn parse_multi(
+    input: &str,
+    options: &ParseOptions,
+    format_options: &FormatOptions,
+) -> Result<String, Error> {
+    let documents = parse_documents(input, options)?
+        .iter()
+        .map(|value| serializer::serialize_pretty(value, format_options))
+        .collect::<Vec<_>>();
+    Ok(documents.join(format_options.eol.as_str()))
+}
+
+pub fn validate_multi(input: &str, options: &ParseOptions) -> Result<(), Error> {
+    parse_documents(input, options).map(|_| ())
+}
+
+// Each of the concatenated documents in the input, as for `parse_multi`
+pub fn parse_documents<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> Result<Vec<JsonValue<'a>>, Error> {
+    let tokens = tokenize_with(input, options)?;
+    Parser::new(&tokens)
+        .allow_trailing_commas(options.allow_trailing_commas)
+        .parse_many()
+}
+
+// Handle each non-empty line as its own document, tagging errors with the line
+fn ndjson_documents<T>(
+    input: &str,
+    handle: impl Fn(&str) -> Result<T, Error>,
+) -> Result<Vec<T>, Error> {
+    ndjson_lines(input)
+        .map(|(line, start, document)| handle(document).map_err(|e| e.on_line(line, start)))
+        .collect()
+}
 
-pub fn parse(input: &str) -> Result<(), Error> {
+// The non-empty lines of NDJSON input, each with its 1-based line number and
+// the byte offset it starts at, split as `str::lines` does
+fn ndjson_lines(input: &str) -> impl Iterator<Item = (usize, usize, &str)> {
+    input
+        .split_inclusive('\n')
+        .scan(0, |start, line| {
+            let line_start = *start;
+            *start += line.len();
+            let line = line.strip_suffix('\n').unwrap_or(line);
+            Some((line_start, line.strip_suffix('\r').unwrap_or(line)))
+        })
+        .enumerate()
+        .filter(|(_, (_, document))| !document.trim().is_empty())
+        .map(|(line, (start, document))| (line + 1, start, document))
+}
+
+fn validate_tokens<'a>(input: &'a str, options: &ParseOptions) -> Result<Vec<Token<'a>>, Error> {
     // Lexical analysis
-    let mut lexer = Lexer::from(input);
-    let tokens = lexer.lex()?;
+    let tokens = lexer(input, options)
+        .preserve_comments(options.preserve_comments)
+        .lex()?;
 
-    // Syntactic analysis
-    let mut parser = Parser::new(&tokens);
-    parser.parse()?;
+    // Syntactic analysis, which comments take no part in
+    if options.preserve_comments {
+        let values = tokens
+            .iter()
+            .filter(|token| token.token_type != TokenType::Comment)
+            .cloned()
+            .collect::<Vec<_>>();
+        parse_tokens(&values, options)?;
+    } else {
+        parse_tokens(&tokens, options)?;
+    }
 
-    // Format output
-    let json = format(&tokens, 4)?;
-    println!("{}", json);
+    Ok(tokens)
+}
+
+fn parse_tokens<'a>(tokens: &[Token<'a>], options: &ParseOptions) -> Result<JsonValue<'a>, Error> {
+    Parser::new(tokens)
+        .allow_trailing_commas(options.allow_trailing_commas)
+        .parse()
+}
+
+pub fn parse_value(input: &str) -> Result<JsonValue<'_>, Error> {
+    parse_value_with(input, &ParseOptions::default())
+}
+
+pub fn parse_value_with<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> Result<JsonValue<'a>, Error> {
+    let tokens = tokenize_with(input, options)?;
+    parse_tokens(&tokens, options)
+}
+
+pub fn stats(input: &str, options: &ParseOptions) -> Result<Stats, Error> {
+    parse_value_with(input, options).map(|value| Stats::from_value(&value))
+}
 
+pub fn stats_ndjson(input: &str, options: &ParseOptions) -> Result<Stats, Error> {
+    let documents = ndjson_documents(input, |document| stats(document, options))?;
+    let mut total = Stats::default();
+    for document in &documents {
+        total.add(document);
+    }
+    Ok(total)
+}
+
+// Format straight into a writer, so large documents are never held as one big `String`
+pub fn parse_to<W: Write>(
+    w: &mut W,
+    input: &str,
+    options: &ParseOptions,
+    format_options: &FormatOptions,
+) -> Result<(), Error> {
+    if format_options.needs_value() {
+        let value = parse_value_with(input, options)?;
+        w.write_all(serializer::serialize_pretty(&value, format_options).as_bytes())?;
+        return Ok(());
+    }
+    let tokens = validate_tokens(input, options)?;
+    write_formatted(w, &tokens, format_options)?;
     Ok(())
 }
 
-fn format(tokens: &[Token], indent: usize) -> Result<String, Error> {
+pub fn query(
+    input: &str,
+    pointer: &str,
+    options: &ParseOptions,
+    format_options: &FormatOptions,
+) -> Result<String, Error> {
+    let value = parse_value_with(input, options)?;
+
+    // Format only the subvalue the pointer refers to
+    let target = resolve_pointer(&value, pointer)?;
+    Ok(serializer::serialize_pretty(target, format_options))
+}
+
+// Format each value matched by a JSONPath on its own line
+pub fn query_path(
+    input: &str,
+    path: &str,
+    options: &ParseOptions,
+    format_options: &FormatOptions,
+) -> Result<String, Error> {
+    let value = parse_value_with(input, options)?;
+    let matches = select_path(&value, path)?
+        .into_iter()
+        .map(|target| serializer::serialize_pretty(target, format_options))
+        .collect::<Vec<_>>();
+    Ok(matches.join(format_options.eol.as_str()))
+}
+
+fn format(tokens: &[Token], options: &FormatOptions) -> Result<String, Error> {
+    let mut out = Vec::new();
+    write_formatted(&mut out, tokens, options)?;
+    String::from_utf8(out).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e).into())
+}
+
+// A writer that only counts the bytes written to it
+#[derive(Default)]
+struct ByteCount(usize);
+
+impl Write for ByteCount {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Indentation text, grown as needed and sliced so no line allocates
+struct Indent {
+    fill: String,
+    per_level: usize,
+    text: String,
+}
+
+impl Indent {
+    fn new(options: &FormatOptions) -> Self {
+        let (fill, per_level) = options.indent_unit();
+        Self {
+            fill: fill.to_string(),
+            per_level,
+            text: String::new(),
+        }
+    }
+    // Indentation for the given nesting depth
+    fn get(&mut self, offset: usize) -> &str {
+        let width = self.fill.len() * self.per_level * offset;
+        while self.text.len() < width {
+            self.text.push_str(&self.fill);
+        }
+        &self.text[..width]
+    }
+}
+
+// Write formatted tokens incrementally rather than building the whole output in memory
+fn write_formatted<W: Write>(
+    w: &mut W,
+    tokens: &[Token],
+    options: &FormatOptions,
+) -> Result<(), Error> {
+    if options.compact_threshold.is_some() {
+        let mut compact = ByteCount::default();
+        write_formatted(&mut compact, tokens, &options.measuring())?;
+        return write_formatted(w, tokens, &options.settle_threshold(compact.0));
+    }
+
+    let mut indent = Indent::new(options);
     let mut offset = 0;
     let mut skip_indent = false;
     let mut skip_newline = false;
+    // Whether the output is at the start of a line, with any indent written
+    let mut line_start = true;
+    // A comment already written at the end of the line before it
+    let mut written_comment = None;
 
-    let json = tokens
-        .windows(2)
-        .map(|window| {
-            let token = &window[0];
-            let next = &window[1];
-            match token.token_type {
-                TokenType::LeftBrace | TokenType::LeftBracket => {
-                    let str = {
-                        if token.token_type == TokenType::LeftBrace
-                            && next.token_type == TokenType::RightBrace
-                            || token.token_type == TokenType::LeftBracket
-                                && next.token_type == TokenType::RightBracket
-                        {
-                            skip_newline = true;
-                            format!("{}", token.token_type)
-                        } else if skip_indent {
-                            format!(
-                                "{}\n{}",
-                                token.token_type,
-                                " ".repeat(indent * (offset + 1))
-                            )
-                        } else {
-                            format!(
-                                "{}{}\n{}",
-                                " ".repeat(indent * offset),
-                                token.token_type,
-                                " ".repeat(indent * (offset + 1))
-                            )
-                        }
-                    };
-                    offset += 1;
-                    skip_indent = false;
-                    str
+    for (i, token) in tokens.iter().enumerate() {
+        // Look ahead; the last token has nothing after it
+        let is_at = |i: usize, token_type: TokenType| {
+            tokens
+                .get(i)
+                .is_some_and(|next| next.token_type == token_type)
+        };
+        let next_is = |token_type: TokenType| is_at(i + 1, token_type);
+        let punctuation = token.token_type.as_str().as_bytes();
+        if token.token_type != TokenType::Comment {
+            line_start = false;
+        }
+        match token.token_type {
+            TokenType::LeftBrace | TokenType::LeftBracket => {
+                let close = if token.token_type == TokenType::LeftBrace {
+                    TokenType::RightBrace
+                } else {
+                    TokenType::RightBracket
+                };
+                if next_is(close) {
+                    skip_newline = true;
+                    w.write_all(punctuation)?;
+                } else {
+                    if !skip_indent {
+                        w.write_all(indent.get(offset).as_bytes())?;
+                    }
+                    w.write_all(punctuation)?;
+                    if write_trailing_comment(w, tokens, i, options)? {
+                        written_comment = Some(i + 1);
+                    }
+                    if written_comment == Some(i + 1) && is_at(i + 2, close) {
+                        // Only a comment inside, so the close goes on the next line
+                        line_break(w, options, &mut indent, offset)?;
+                        skip_newline = true;
+                    } else {
+                        line_break(w, options, &mut indent, offset + 1)?;
+                    }
+                    line_start = true;
                 }
-                TokenType::RightBrace | TokenType::RightBracket => {
-                    offset -= 1;
-                    let str = {
-                        if skip_newline {
-                            format!("{}", token.token_type)
-                        } else {
-                            format!("\n{}{}", " ".repeat(indent * offset), token.token_type)
+                offset += 1;
+                // The opener already wrote the newline and indent for what follows
+                skip_indent = true;
+            }
+            TokenType::RightBrace | TokenType::RightBracket => {
+                // Tokens may not have been validated, so never underflow on a stray close
+                offset = offset.checked_sub(1).ok_or_else(|| {
+                    Error::UnexpectedToken(
+                        format!(
+                            "Unbalanced {} at line {}, col {}",
+                            token.token_type, token.line, token.column
+                        ),
+                        (token.line, token.column),
+                        token.start,
+                    )
+                })?;
+                if !skip_newline {
+                    line_break(w, options, &mut indent, offset)?;
+                }
+                w.write_all(punctuation)?;
+                skip_indent = false;
+                skip_newline = false;
+            }
+            TokenType::Comma => {
+                skip_indent = true;
+                w.write_all(punctuation)?;
+                match options.style {
+                    Style::Pretty => {
+                        if write_trailing_comment(w, tokens, i, options)? {
+                            written_comment = Some(i + 1);
                         }
-                    };
-                    skip_indent = false;
-                    skip_newline = false;
-                    str
+                        line_break(w, options, &mut indent, offset)?;
+                        line_start = true;
+                    }
+                    Style::Spaced => w.write_all(b" ")?,
+                    Style::Compact => {}
                 }
-                TokenType::Comma => {
-                    skip_indent = true;
-                    format!("{}\n{}", token.token_type, " ".repeat(indent * offset))
+            }
+            // Other layouts have no lines for a comment to end
+            TokenType::Comment if options.style != Style::Pretty => {}
+            TokenType::Comment if written_comment == Some(i) => {}
+            TokenType::Comment => {
+                let text = token_text(token);
+                let trailing = !line_start && i > 0 && tokens[i - 1].line == token.line;
+                if trailing {
+                    w.write_all(b" ")?;
+                } else if !line_start {
+                    line_break(w, options, &mut indent, offset)?;
                 }
-                TokenType::Colon => {
+                w.write_all(text.as_bytes())?;
+                line_start = false;
+                // A comment on its own line keeps the line to itself, and a line
+                // comment runs to the end of its line. Closers start their own line.
+                let closes = next_is(TokenType::RightBrace)
+                    || next_is(TokenType::RightBracket)
+                    || next_is(TokenType::Comment);
+                if i + 1 < tokens.len() && !closes && (!trailing || text.starts_with("//")) {
+                    line_break(w, options, &mut indent, offset)?;
+                    line_start = true;
                     skip_indent = true;
-                    format!("{} ", token.token_type)
                 }
-                _ => {
-                    if let Some(value) = &token.value {
-                        format!("{}", value)
-                    } else {
-                        format!("{}", token.token_type)
-                    }
+            }
+            TokenType::Colon => {
+                skip_indent = true;
+                w.write_all(punctuation)?;
+                if options.style != Style::Compact {
+                    w.write_all(b" ")?;
                 }
             }
-        })
-        .collect::<String>();
+            _ => {
+                let str = if let Some(JsonValue::String(string)) = &token.value {
+                    escape::quote_for(string, options)
+                } else if let Some(value) = &token.value {
+                    format!("{}", value)
+                } else {
+                    format!("{}", token.token_type)
+                };
+                let kind = match token.token_type {
+                    TokenType::String if next_is(TokenType::Colon) => Highlight::Key,
+                    TokenType::String => Highlight::String,
+                    TokenType::Number => Highlight::Number,
+                    TokenType::Bool => Highlight::Bool,
+                    _ => Highlight::Null,
+                };
+                match kind.code(options.theme).filter(|_| options.color) {
+                    Some(code) => w.write_all(color::paint(code, &str).as_bytes())?,
+                    None => w.write_all(str.as_bytes())?,
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// The text of a comment token, delimiters included
+fn token_text<'t>(token: &'t Token) -> &'t str {
+    match &token.value {
+        Some(JsonValue::String(text)) => text,
+        _ => "",
+    }
+}
+
+// Write the comment after token `i` when it is on the same line, so it stays at
+// the end of that line. Returns whether there was one.
+fn write_trailing_comment<W: Write>(
+    w: &mut W,
+    tokens: &[Token],
+    i: usize,
+    options: &FormatOptions,
+) -> io::Result<bool> {
+    match tokens.get(i + 1) {
+        Some(next)
+            if options.style == Style::Pretty
+                && next.token_type == TokenType::Comment
+                && next.line == tokens[i].line =>
+        {
+            w.write_all(b" ")?;
+            w.write_all(token_text(next).as_bytes())?;
+            Ok(true)
+        }
+        _ => Ok(false),
+    }
+}
+
+// Start a new line at the given depth; spaced output stays on one line
+fn line_break<W: Write>(
+    w: &mut W,
+    options: &FormatOptions,
+    indent: &mut Indent,
+    offset: usize,
+) -> io::Result<()> {
+    if options.style == Style::Pretty {
+        w.write_all(options.eol.as_str().as_bytes())?;
+        w.write_all(indent.get(offset).as_bytes())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    Ok(json)
+    fn format_str(input: &str, color: bool) -> String {
+        let tokens = Lexer::from(input).lex().unwrap();
+        let options = FormatOptions {
+            color,
+            ..FormatOptions::default()
+        };
+        format(&tokens, &options).unwrap()
+    }
+
+    fn format_jsonc(input: &str) -> String {
+        let options = ParseOptions {
+            allow_comments: true,
+            preserve_comments: true,
+            ..ParseOptions::default()
+        };
+        let format_options = FormatOptions {
+            indent: 2,
+            ..FormatOptions::default()
+        };
+        parse(input, &options, &format_options).unwrap()
+    }
+
+    #[test]
+    fn preserve_leading_and_trailing_comments() {
+        let input = "// Editor settings\n{\"tabs\": false, // spaces only\n\"size\":   4 /* columns */,\n  /* font */ \"font\": \"mono\"}";
+        let expected = "// Editor settings\n{\n  \"tabs\": false, // spaces only\n  \"size\": 4 /* columns */,\n  /* font */\n  \"font\": \"mono\"\n}";
+        assert_eq!(format_jsonc(input), expected);
+        assert_eq!(format_jsonc(&format_jsonc(input)), expected);
+    }
+    #[test]
+    fn preserve_comments_around_containers() {
+        let input = "{\"a\": [ // items\n1,\n// last\n2 // two\n], \"b\": { /* none */ }, \"c\": {\n// empty\n}}\n// end";
+        let expected = "{\n  \"a\": [ // items\n    1,\n    // last\n    2 // two\n  ],\n  \"b\": { /* none */\n  },\n  \"c\": {\n    // empty\n  }\n}\n// end";
+        assert_eq!(format_jsonc(input), expected);
+        // Parsing to a value still ignores them
+        let options = ParseOptions {
+            allow_comments: true,
+            preserve_comments: true,
+            ..ParseOptions::default()
+        };
+        assert!(parse_value_with(input, &options).is_ok());
+        assert!(validate(input, &options).is_ok());
+    }
+    #[test]
+    fn format_without_color() {
+        let output = format_str(r#"{"key":"value"}"#, false);
+        assert_eq!(output, "{\n    \"key\": \"value\"\n}");
+    }
+    #[test]
+    fn format_with_color() {
+        let output = format_str(r#"{"key":"value","n":1,"b":true,"z":null}"#, true);
+        assert!(output.contains(&color::paint(color::KEY, r#""key""#)));
+        assert!(output.contains(&color::paint(color::STRING, r#""value""#)));
+        assert!(output.contains(&color::paint(color::NUMBER, "1")));
+        assert!(output.contains(&color::paint(color::BOOL, "true")));
+        assert!(output.contains(&color::paint(color::NULL, "null")));
+    }
+    #[test]
+    fn format_escapes_strings() {
+        let output = format_str(r#"{"a\"b":"he said \"hi\"\n"}"#, false);
+        let expected = r#"{
+    "a\"b": "he said \"hi\"\n"
+}"#;
+        assert_eq!(output, expected);
+    }
+    #[test]
+    fn escaped_strings_round_trip() {
+        let input = r#"{"quote\"": "he said \"hi\"\nthen left\u0001"}"#;
+        let options = ParseOptions::default();
+        let output = query(input, "", &options, &FormatOptions::default()).unwrap();
+        assert_eq!(parse_value(&output).unwrap(), parse_value(input).unwrap());
+    }
+    #[test]
+    fn format_ascii_escapes_non_ascii() {
+        let tokens = Lexer::from(r#"{"café":"😀"}"#).lex().unwrap();
+        let options = FormatOptions {
+            ascii: true,
+            ..FormatOptions::default()
+        };
+        let output = format(&tokens, &options).unwrap();
+        let expected = r#"{
+    "caf\u00e9": "\ud83d\ude00"
+}"#;
+        assert_eq!(output, expected);
+    }
+    #[test]
+    fn parse_to_writer_matches_string_output() {
+        let input = r#"{"a": [1, {"b": null}], "c": "d"}"#;
+        let options = ParseOptions::default();
+        let format_options = FormatOptions::default();
+        let mut out = Vec::new();
+        parse_to(&mut out, input, &options, &format_options).unwrap();
+        let expected = parse(input, &options, &format_options).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), expected);
+    }
+    #[test]
+    fn format_rejects_unbalanced_tokens() {
+        let tokens = Lexer::from("]]").lex().unwrap();
+        let result = format(&tokens, &FormatOptions::default());
+        match result {
+            Err(Error::UnexpectedToken(msg, _, offset)) => {
+                assert_eq!(msg, "Unbalanced ] at line 1, col 1");
+                assert_eq!(offset, 0);
+            }
+            other => panic!("expected unbalanced token error, got {:?}", other),
+        }
+    }
+    fn token_kinds<'a>(tokens: &[Token<'a>]) -> Vec<(String, Option<JsonValue<'a>>)> {
+        tokens
+            .iter()
+            .map(|token| (token.token_type.to_string(), token.value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn format_emits_every_token() {
+        let inputs = [
+            r#"{"a": [1, 2, {"b": null}], "c": {}}"#,
+            "[[], [true, false]]",
+            r#""scalar""#,
+            "42",
+        ];
+        for input in inputs {
+            let output = format_str(input, false);
+            let expected = Lexer::from(input).lex().unwrap();
+            let actual = Lexer::from(output.as_str()).lex().unwrap();
+            assert_eq!(token_kinds(&actual), token_kinds(&expected), "{}", input);
+        }
+    }
+    // Valid documents formatting must leave unchanged on a second pass
+    const IDEMPOTENCY_CORPUS: &[&str] = &[
+        "{}",
+        "[]",
+        "[{}, [], {\"a\": []}]",
+        r#"{"a": {"b": {"c": [1, 2, {"d": null}]}}}"#,
+        r#"[[[["deep"]]], [true, false], -1.5e3]"#,
+        r#"{"escaped \"key\"": "line\nbreak\ttab \\ slash \u0001"}"#,
+        r#"{"unicode": "café 😀", "empty": "", "zero": 0}"#,
+        r#""scalar""#,
+        "3.25",
+        "true",
+        "null",
+    ];
+
+    #[test]
+    fn formatting_is_idempotent() {
+        for input in IDEMPOTENCY_CORPUS {
+            let once = format_str(input, false);
+            let twice = format_str(&once, false);
+            assert_eq!(once, twice, "{}", input);
+
+            // The value serializer must agree with the token formatter
+            let options = ParseOptions::default();
+            let serialized = query(input, "", &options, &FormatOptions::default()).unwrap();
+            assert_eq!(once, serialized, "{}", input);
+        }
+    }
+    #[test]
+    fn format_spaced_style() {
+        let input = r#"{"a":1,"b":[2,3],"c":{},"d":[{"e":null}]}"#;
+        let tokens = Lexer::from(input).lex().unwrap();
+        let options = FormatOptions {
+            style: Style::Spaced,
+            ..FormatOptions::default()
+        };
+        let expected = r#"{"a": 1, "b": [2, 3], "c": {}, "d": [{"e": null}]}"#;
+        assert_eq!(format(&tokens, &options).unwrap(), expected);
+
+        let value = parse_value(input).unwrap();
+        assert_eq!(serializer::serialize_pretty(&value, &options), expected);
+    }
+    #[test]
+    fn numbers_keep_full_precision() {
+        let input = "[1.0000000000000001, 1234567890123456789012345, -0.0, 1E2]";
+        let expected =
+            "[\n    1.0000000000000001,\n    1234567890123456789012345,\n    -0.0,\n    1E2\n]";
+        assert_eq!(format_str(input, false), expected);
+        let options = ParseOptions::default();
+        let serialized = query(input, "", &options, &FormatOptions::default()).unwrap();
+        assert_eq!(serialized, expected);
+        let value = parse_value(input).unwrap();
+        assert_eq!(value.get_index(3).and_then(JsonValue::as_f64), Some(100.0));
+    }
+    #[test]
+    fn negative_zero_keeps_its_sign() {
+        let input = "[-0, -0.0, 0, -0e0]";
+        let options = FormatOptions {
+            style: Style::Spaced,
+            ..FormatOptions::default()
+        };
+        let tokens = Lexer::from(input).lex().unwrap();
+        assert_eq!(format(&tokens, &options).unwrap(), input);
+        let serialized = query(input, "", &ParseOptions::default(), &options).unwrap();
+        assert_eq!(serialized, input);
+        assert_eq!(
+            to_yaml(&parse_value(input).unwrap()),
+            "- -0\n- -0.0\n- 0\n- -0e0"
+        );
+
+        // Comparisons go by value, so `-0` still equals `0`
+        let value = parse_value(input).unwrap();
+        assert_eq!(value.get_index(0), value.get_index(2));
+        assert_eq!(JsonValue::from(-0.0).to_string(), "-0");
+    }
+    #[test]
+    fn format_compact_style() {
+        let input = r#"{"a": 1, "b": [2, 3], "c": {}, "d": [{"e": null}]}"#;
+        let tokens = Lexer::from(input).lex().unwrap();
+        let options = FormatOptions {
+            style: Style::Compact,
+            ..FormatOptions::default()
+        };
+        let expected = r#"{"a":1,"b":[2,3],"c":{},"d":[{"e":null}]}"#;
+        assert_eq!(format(&tokens, &options).unwrap(), expected);
+    }
+    #[test]
+    fn compact_threshold_picks_style_by_size() {
+        // The compact form, {"a":[1,2]}, is 11 bytes
+        let tokens = Lexer::from(r#"{"a": [1, 2]}"#).lex().unwrap();
+        let options = |threshold| FormatOptions {
+            compact_threshold: Some(threshold),
+            ..FormatOptions::default()
+        };
+        assert_eq!(format(&tokens, &options(12)).unwrap(), r#"{"a":[1,2]}"#);
+        let pretty = "{\n    \"a\": [\n        1,\n        2\n    ]\n}";
+        assert_eq!(format(&tokens, &options(11)).unwrap(), pretty);
+
+        let value = parse_value(r#"{"a": [1, 2]}"#).unwrap();
+        let sorted = |threshold| FormatOptions {
+            sort_keys: KeyOrder::Asc,
+            ..options(threshold)
+        };
+        assert_eq!(serialize_pretty(&value, &sorted(12)), r#"{"a":[1,2]}"#);
+        assert_eq!(serialize_pretty(&value, &sorted(11)), pretty);
+    }
+    #[test]
+    fn format_with_tab_indent() {
+        let tokens = Lexer::from(r#"{"a": [1], "b": {}}"#).lex().unwrap();
+        let options = FormatOptions {
+            tabs: true,
+            ..FormatOptions::default()
+        };
+        let expected = "{\n\t\"a\": [\n\t\t1\n\t],\n\t\"b\": {}\n}";
+        assert_eq!(format(&tokens, &options).unwrap(), expected);
+    }
+    #[test]
+    fn format_with_indent_string() {
+        let input = r#"{"a": [1], "b": {}}"#;
+        let tokens = Lexer::from(input).lex().unwrap();
+        for (unit, expected) in [
+            ("  ", "{\n  \"a\": [\n    1\n  ],\n  \"b\": {}\n}"),
+            ("\t", "{\n\t\"a\": [\n\t\t1\n\t],\n\t\"b\": {}\n}"),
+        ] {
+            let options = FormatOptions {
+                indent_string: Some(unit.to_string()),
+                ..FormatOptions::default()
+            };
+            assert_eq!(format(&tokens, &options).unwrap(), expected);
+            let sorted = FormatOptions {
+                sort_keys: KeyOrder::Asc,
+                ..options
+            };
+            let output = parse(input, &ParseOptions::default(), &sorted).unwrap();
+            assert_eq!(output, expected);
+        }
+    }
+    #[test]
+    fn both_formatting_paths_keep_key_order() {
+        let input = r#"{"zeta": 1, "alpha": {"m": 2, "b": 3, "x": 4}, "mid": [{"q": 5, "c": 6}]}"#;
+        let expected = r#"{"zeta":1,"alpha":{"m":2,"b":3,"x":4},"mid":[{"q":5,"c":6}]}"#;
+        let options = FormatOptions {
+            style: Style::Compact,
+            ..FormatOptions::default()
+        };
+        let tokens = Lexer::from(input).lex().unwrap();
+        assert_eq!(format(&tokens, &options).unwrap(), expected);
+        let value = parse_value(input).unwrap();
+        assert_eq!(format_value(&value, &options).trim_end(), expected);
+    }
+    #[test]
+    fn parse_sorts_keys_through_serializer() {
+        let options = FormatOptions {
+            sort_keys: KeyOrder::Asc,
+            ..FormatOptions::default()
+        };
+        let output = parse(r#"{"b": 1, "a": 2}"#, &ParseOptions::default(), &options).unwrap();
+        assert_eq!(output, "{\n    \"a\": 2,\n    \"b\": 1\n}");
+    }
+    #[test]
+    fn format_with_crlf() {
+        let tokens = Lexer::from(r#"{"a":[1,2],"b":{}}"#).lex().unwrap();
+        let options = FormatOptions {
+            eol: Newline::Crlf,
+            ..FormatOptions::default()
+        };
+        let expected =
+            "{\r\n    \"a\": [\r\n        1,\r\n        2\r\n    ],\r\n    \"b\": {}\r\n}";
+        let output = format(&tokens, &options).unwrap();
+        assert_eq!(output, expected);
+        assert_eq!(output.matches('\n').count(), output.matches("\r\n").count());
+
+        let value = parse_value(r#"{"a":[1,2],"b":{}}"#).unwrap();
+        assert_eq!(serializer::serialize_pretty(&value, &options), expected);
+    }
+    #[test]
+    fn tokenize_reports_positions() {
+        let tokens = tokenize("{\n  \"a\": [true]\n}").unwrap();
+        let positions = tokens
+            .iter()
+            .map(|token| (token.token_type, token.line, token.column))
+            .collect::<Vec<_>>();
+        let expected = vec![
+            (TokenType::LeftBrace, 1, 1),
+            (TokenType::String, 2, 3),
+            (TokenType::Colon, 2, 6),
+            (TokenType::LeftBracket, 2, 8),
+            (TokenType::Bool, 2, 9),
+            (TokenType::RightBracket, 2, 13),
+            (TokenType::RightBrace, 3, 1),
+        ];
+        assert_eq!(positions, expected);
+
+        let options = ParseOptions {
+            allow_comments: true,
+            ..ParseOptions::default()
+        };
+        assert_eq!(tokenize_with("// note\n1", &options).unwrap().len(), 1);
+        assert!(tokenize("// note\n1").is_err());
+    }
+    #[test]
+    fn validate_all_reports_every_error() {
+        let input = "{\n  \"a\": [1, ~],\n  \"b\" 2,\n  \"c\": tru\n}";
+        let errors = validate_all(input, &ParseOptions::default())
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>();
+        let expected = [
+            "Unexpected character: ~, line 2 column 12 (byte 13)",
+            "Expected ':' after the object key, found number at line 3 column 7 (byte 23)",
+            "Unexpected character: t, line 4 column 8 (byte 33)",
+        ];
+        assert_eq!(errors, expected);
+        assert!(validate_all(r#"{"a": [1, 2]}"#, &ParseOptions::default()).is_empty());
+        let errors = validate_all("{}%", &ParseOptions::default());
+        assert!(
+            matches!(errors[..], [Error::UnexpectedCharacter('%', (1, 3), 2)]),
+            "{:?}",
+            errors
+        );
+
+        let errors = validate_all_ndjson("[1 2]\n{}\n{\"a\"}", &ParseOptions::default());
+        assert!(matches!(
+            errors[..],
+            [Error::InvalidLine(1, _), Error::InvalidLine(3, _)]
+        ));
+    }
+    #[test]
+    fn validate_reader_matches_validate() {
+        let inputs = [
+            "{\n  \"a\": [1, \"b\\\"c\", true, null],\n  \"d\": {}\n}",
+            "\u{FEFF}[1, 2]",
+            "[1, 2,]",
+            "{\"a\": 1,}",
+            "{\"a\" 1}",
+            "[1 2]",
+            "[1, tru]",
+            "[\"a\\x\"]",
+            "{\"é\": ~}",
+            "[1] 2",
+            "[[",
+            "{\"a\":",
+            "  \n ",
+            "[\"unterminated",
+            "/* note */ [1]",
+        ];
+        for allow in [false, true] {
+            let options = ParseOptions {
+                allow_comments: allow,
+                allow_trailing_commas: allow,
+                ..ParseOptions::default()
+            };
+            for input in inputs {
+                // A one-byte buffer puts a read boundary inside every token
+                let reader = io::BufReader::with_capacity(1, input.as_bytes());
+                let streamed = validate_reader(reader, &options).map_err(|e| e.to_string());
+                let loaded = validate(input, &options).map_err(|e| e.to_string());
+                assert_eq!(streamed, loaded, "{:?}", input);
+            }
+        }
+        let deep = "[".repeat(200);
+        assert!(matches!(
+            validate_reader(deep.as_bytes(), &ParseOptions::default()),
+            Err(Error::MaxDepthExceeded(_))
+        ));
+    }
+    #[test]
+    fn validate_without_formatting() {
+        let options = ParseOptions::default();
+        assert!(validate(r#"{"key": [1, 2]}"#, &options).is_ok());
+        assert!(validate(r#"{"key": [1, 2}"#, &options).is_err());
+    }
+    #[test]
+    fn validate_stops_at_the_first_error() {
+        let options = ParseOptions::default();
+        let message = |input| validate(input, &options).unwrap_err().to_string();
+        // The syntax error comes first, so the bad character after it is never lexed
+        assert_eq!(
+            message("[1 2] ~"),
+            "Expected ',' or ']', found number at line 1 column 4 (byte 3)"
+        );
+        assert_eq!(
+            message("{\"a\": [tru]}"),
+            "Unexpected character: t, line 1 column 8 (byte 7)"
+        );
+        assert!(matches!(validate("", &options), Err(Error::EmptyDocument)));
+        assert!(matches!(
+            validate("[1,", &options),
+            Err(Error::UnexpectedEndOfArray)
+        ));
+        let deep = "[".repeat(parser::DEFAULT_MAX_DEPTH + 1);
+        assert!(matches!(
+            validate(&deep, &options),
+            Err(Error::MaxDepthExceeded(_))
+        ));
+    }
+    #[test]
+    fn ndjson_formats_each_line() {
+        let input = "{\"a\": 1}\n\n[true, null]\n{}\n";
+        let options = ParseOptions::default();
+        let output = parse_ndjson(input, &options, &FormatOptions::default()).unwrap();
+        let expected = "{\n    \"a\": 1\n}\n[\n    true,\n    null\n]\n{}";
+        assert_eq!(output, expected);
+        assert!(validate_ndjson(input, &options).is_ok());
+    }
+    #[test]
+    fn multi_formats_concatenated_documents() {
+        let options = ParseOptions::default();
+        let compact = FormatOptions {
+            style: Style::Compact,
+            ..FormatOptions::default()
+        };
+        let output = parse_multi(r#"{"a":1}{"b": [2]}"#, &options, &compact).unwrap();
+        assert_eq!(output, "{\"a\":1}\n{\"b\":[2]}");
+        let output = parse_multi(
+            "{\"a\":\n1} [true,\nnull]",
+            &options,
+            &FormatOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(output, "{\n    \"a\": 1\n}\n[\n    true,\n    null\n]");
+        assert_eq!(parse_documents("1 \"x\"null", &options).unwrap().len(), 3);
+        assert_eq!(parse_multi("  ", &options, &compact).unwrap(), "");
+        let error = validate_multi(r#"{"a":1}{"b"}"#, &options).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Expected ':' after the object key, found '}' at line 1 column 12 (byte 11)"
+        );
+    }
+    #[test]
+    fn ndjson_reports_bad_line() {
+        let input = "{\"a\": 1}\n[1, 2]\n{\"b\": }\n[3]";
+        let options = ParseOptions::default();
+        let result = parse_ndjson(input, &options, &FormatOptions::default());
+        assert!(matches!(result, Err(Error::InvalidLine(3, _))));
+        let result = validate_ndjson(input, &options);
+        assert!(matches!(result, Err(Error::InvalidLine(3, _))));
+        // Positions are in the whole input, not the line
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Line 3: Expected a value (object, array, string, number, boolean, or null), \
+             found '}' at line 3 column 7 (byte 22)"
+        );
+        let result = validate_ndjson("1\r\n2\r\n[~]", &options);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "Line 3: Unexpected character: ~, line 3 column 2 (byte 7)"
+        );
+    }
 }
@@ -0,0 +1,116 @@
+/// Build a `JsonValue` from JSON-like syntax. Values other than `null`, arrays
+/// and objects may be any expression with a `JsonValue::from` conversion, and
+/// object keys are string literals or any other `&str` or `String` in
+/// parentheses.
+///
+/// ```
+/// use jp::{json, serialize_pretty, FormatOptions, Style};
+///
+/// let name = "jp";
+/// let value = json!({
+///     "name": name,
+///     "nums": [1, 2, -3.5],
+///     "nested": {"ok": true, "none": null},
+/// });
+/// let options = FormatOptions {
+///     style: Style::Compact,
+///     ..FormatOptions::default()
+/// };
+/// assert_eq!(
+///     serialize_pretty(&value, &options),
+///     r#"{"name":"jp","nums":[1,2,-3.5],"nested":{"ok":true,"none":null}}"#
+/// );
+/// ```
+#[macro_export]
+macro_rules! json {
+    // Array elements, one at a time, so each can be any expression
+    (@array [$($done:expr,)*]) => {
+        $crate::JsonValue::Array(vec![$($done,)*])
+    };
+    (@array [$($done:expr,)*] null $(, $($rest:tt)*)?) => {
+        $crate::json!(@array [$($done,)* $crate::JsonValue::Null,] $($($rest)*)?)
+    };
+    (@array [$($done:expr,)*] [$($array:tt)*] $(, $($rest:tt)*)?) => {
+        $crate::json!(@array [$($done,)* $crate::json!([$($array)*]),] $($($rest)*)?)
+    };
+    (@array [$($done:expr,)*] {$($object:tt)*} $(, $($rest:tt)*)?) => {
+        $crate::json!(@array [$($done,)* $crate::json!({$($object)*}),] $($($rest)*)?)
+    };
+    (@array [$($done:expr,)*] $next:expr $(, $($rest:tt)*)?) => {
+        $crate::json!(@array [$($done,)* $crate::JsonValue::from($next),] $($($rest)*)?)
+    };
+
+    // Object entries, likewise
+    (@object [$($done:expr,)*]) => {
+        $crate::JsonValue::Object(vec![$($done,)*])
+    };
+    (@object [$($done:expr,)*] $key:tt : null $(, $($rest:tt)*)?) => {
+        $crate::json!(@object [$($done,)* $crate::json!(@key $key $crate::JsonValue::Null),] $($($rest)*)?)
+    };
+    (@object [$($done:expr,)*] $key:tt : [$($array:tt)*] $(, $($rest:tt)*)?) => {
+        $crate::json!(@object [$($done,)* $crate::json!(@key $key $crate::json!([$($array)*])),] $($($rest)*)?)
+    };
+    (@object [$($done:expr,)*] $key:tt : {$($object:tt)*} $(, $($rest:tt)*)?) => {
+        $crate::json!(@object [$($done,)* $crate::json!(@key $key $crate::json!({$($object)*})),] $($($rest)*)?)
+    };
+    (@object [$($done:expr,)*] $key:tt : $value:expr $(, $($rest:tt)*)?) => {
+        $crate::json!(@object [$($done,)* $crate::json!(@key $key $crate::JsonValue::from($value)),] $($($rest)*)?)
+    };
+    (@key $key:tt $value:expr) => {
+        (::std::borrow::Cow::from($key), $value)
+    };
+
+    (null) => {
+        $crate::JsonValue::Null
+    };
+    ([$($array:tt)*]) => {
+        $crate::json!(@array [] $($array)*)
+    };
+    ({$($object:tt)*}) => {
+        $crate::json!(@object [] $($object)*)
+    };
+    ($other:expr) => {
+        $crate::JsonValue::from($other)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{parse_value, JsonValue};
+
+    #[test]
+    fn json_builds_literals_and_nesting() {
+        assert_eq!(json!(null), JsonValue::Null);
+        assert_eq!(json!("text"), JsonValue::String("text".into()));
+        assert_eq!(json!([]), JsonValue::Array(vec![]));
+        assert_eq!(json!({}), JsonValue::Object(vec![]));
+        let value = json!({
+            "name": "x",
+            "nums": [1, 2, 3],
+            "nested": {"deep": [[], {}, [null, false]], "n": -1.5e3},
+        });
+        let expected = r#"{"name": "x", "nums": [1, 2, 3],
+            "nested": {"deep": [[], {}, [null, false]], "n": -1500}}"#;
+        assert_eq!(value, parse_value(expected).unwrap());
+    }
+    #[test]
+    fn json_interpolates_expressions() {
+        let tags = ["a", "b"];
+        let key = String::from("computed");
+        let inner = json!([true]);
+        let value = json!({
+            "count": tags.len(),
+            "sum": 1 + 2,
+            "first": tags[0],
+            (key.as_str()): inner,
+            (format!("k{}", 2)): i64::MAX,
+        });
+        let expected = r#"{"count": 2, "sum": 3, "first": "a", "computed": [true],
+            "k2": 9223372036854775807}"#;
+        assert_eq!(value, parse_value(expected).unwrap());
+        assert_eq!(
+            json!([tags.len() * 2, "x"]),
+            parse_value(r#"[4, "x"]"#).unwrap()
+        );
+    }
+}
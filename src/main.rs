@@ -1,43 +1,1962 @@
-use std::fs::File;
-use std::io::{self, IsTerminal, Read};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, IsTerminal, Read, Write};
 
-use jp::parse;
+#[cfg(feature = "http")]
+mod fetch;
 
-const USAGE: &str = "Usage: jp [FILE]";
+use jp::{
+    delete_pointer, diff, drop_nulls, flatten, merge, merge_concat, merge_conflict, omit_keys,
+    parse, parse_multi, parse_ndjson, parse_to, parse_value_with, query, query_path,
+    resolve_pointer, select_keys, select_type, serialize_pretty, set_pointer, stats, stats_ndjson,
+    to_canonical, to_csv, to_html, to_yaml, tokenize_with, unflatten, validate, validate_all,
+    validate_all_ndjson, validate_multi, validate_ndjson, validate_reader, Error, ErrorKind,
+    FormatOptions, JsonValue, KeyOrder, Newline, ParseOptions, Style, Theme, TokenType, ValueType,
+    STYLESHEET,
+};
 
-fn print_usage() {
-    println!("{}", USAGE);
+const USAGE: &str = "Usage: jp [--color <auto|always|never>] [--theme <default|dark|light|none>] [--pointer POINTER] [--path PATH] [--only TYPE [--with-paths]] [-r|--raw-output] [--set POINTER VALUE [--create]] [--delete POINTER] [--select-keys LIST] [--omit-keys LIST] [--drop-nulls] [--pick POINTER=NAME [--lenient]] [-o|--output FILE] [--files-from LIST] [--recursive] [--ext LIST] [--max-size BYTES] [--write [--dry-run]|--check|--validate|--all-errors|--diff|--merge|--merge-arrays] [--fail-on-conflict] [--pretty-errors] [--error-format <text|json>] [--stats] [--count] [--type] [--pointer-exists POINTER [-v|--verbose]] [--quiet] [--ascii] [--escape-slashes] [--style <pretty|spaced|compact>] [-c|--compact] [--compact-threshold BYTES] [--indent <N|tab>] [--indent-string TEXT] [--tab-size N] [--preserve-order] [--sort-keys] [--sort-keys-by <asc|desc|length>] [--canonical] [--depth N] [--head N] [--line-width N] [--inline-scalar-arrays|--compact-arrays] [--trailing-comma] [--numbers-as-strings] [--eol <lf|crlf>] [--no-trailing-newline] [--to <json|yaml|csv|html>] [--css] [--flatten|--unflatten] [--ndjson|--multi] [--jsonc [--preserve-comments]] [--allow-trailing-commas] [--strict-integers] [--warn-precision|--strict-precision] [-h|--help] [--version] [--] [FILE|-]...";
+
+const HELP: &str = r"Format, validate and convert JSON from FILEs or stdin.
+
+Options:
+--color <auto|always|never>  Colorize output (default: auto, only when stdout is a terminal)
+--theme <default|dark|light|none>
+                             Colors to use: the default set, brighter ones for dark backgrounds,
+                             darker ones for light backgrounds, or none at all
+--pointer POINTER            Print only the value at an RFC 6901 JSON Pointer, e.g. /foo/0/bar
+--path PATH                  Print each value matched by a JSONPath, one per line, e.g. $..author
+--only TYPE                  Print every value of one type at any depth, one per line:
+                             string, number, bool, null, object or array
+--with-paths                 Prefix each --only value with its JSON Pointer
+-r, --raw-output             Print a string result as its text, unquoted and unescaped, like jq -r
+--set POINTER VALUE          Replace the value at POINTER with the JSON VALUE before printing; repeatable
+--create                     With --set, add object keys missing along POINTER instead of failing
+--delete POINTER             Remove the object key or array element at POINTER before printing; repeatable
+--select-keys LIST           Keep only these comma-separated keys of the top-level object, or of each object in a top-level array
+--omit-keys LIST             Remove entries with these comma-separated keys from every object, at any depth
+--drop-nulls                 Remove object entries whose value is null, at any depth; nulls in arrays stay
+--pick POINTER=NAME          Print an object with the value at each POINTER under its NAME; repeatable
+--lenient                    With --pick, leave out pointers that don't resolve instead of failing
+-o, --output FILE            Write formatted output to FILE instead of stdout
+--write                      Format each FILE in place, leaving invalid or unchanged files untouched
+--dry-run                    With --write, list the files it would change and how many, without writing
+--check                      List each FILE that isn't already formatted and exit 1 if any are found
+--files-from LIST            Also process each file named on a line of LIST, or of stdin when LIST is -
+--recursive                  Replace each directory FILE with the files below it that match --ext
+--ext LIST                   Comma-separated extensions for --recursive (default: json), e.g. json,jsonc
+--max-size BYTES             Refuse any input larger than BYTES, without reading the rest of it
+--diff A B                   Print structural differences between two files: added (+), removed (-) and changed (~) values
+--merge                      Deep-merge object FILEs in order, later files winning; arrays are replaced
+--merge-arrays               Like --merge, but concatenate arrays found at the same path
+--fail-on-conflict           With --merge, fail instead of replacing a value with a different one
+-q, --validate               Only validate input: print nothing on success, the error on failure
+--all-errors                 Like --validate, but keep going after an error and report every one found
+--pretty-errors              Follow each error with the line of input it is on and a ^ under its column
+--error-format <text|json>   Report errors in the input as text (default) or as one JSON object per line
+--stats                      Print counts of each value type, keys and maximum depth to stderr
+--count                      Print the number of tokens and bytes in each input instead of formatting it
+--pointer-exists POINTER     Print nothing, and exit 0 if POINTER resolves in every input or 1 if not
+-v, --verbose                With --pointer-exists, print the value found
+--type                       Print the type of each input's value: object, array, string, number, boolean or null
+--quiet                      Print only the output and errors: no per-file headers, and no stats even with --stats
+                             Long-only, since -q is short for --validate
+--ascii                      Escape every non-ASCII character in strings as \uXXXX
+--escape-slashes             Write / in strings as \/, for JSON embedded in an HTML <script> tag
+--style <pretty|spaced|compact>
+                             Layout: indented lines (default), one line with spaces after , and :,
+                             or one line with no extra whitespace
+-c, --compact                Same as --style compact
+--compact-threshold BYTES    Print documents compactly when that takes under BYTES bytes, otherwise in the chosen style
+--indent <N|tab>             Indent each level by N spaces (default: 4, or $JP_INDENT) or by one tab
+--indent-string TEXT         Indent each level by TEXT, which may hold only spaces, tabs, CR and LF
+--tab-size N                 Columns a tab indent counts as for --line-width (default: 4)
+--preserve-order             Keep object keys in input order, and refuse options that would reorder them
+--sort-keys                  Sort object keys
+--sort-keys-by <asc|desc|length>
+                             Sort object keys ascending, descending, or shortest first; --sort-keys is asc
+--canonical                  Print RFC 8785 canonical JSON: sorted keys, no whitespace, normalized numbers
+--line-width N               Keep arrays and objects on one line when they fit in N columns (pretty style)
+--inline-scalar-arrays       Keep arrays of only strings, numbers, booleans and nulls on one line (pretty style)
+--compact-arrays             Same as --inline-scalar-arrays
+--trailing-comma             End every multi-line array and object of 2+ elements with a comma (pretty style; not valid JSON)
+--numbers-as-strings         Write every number as a string of its digits from the input, so none are rounded
+--depth N                    Show N levels and collapse anything deeper into a summary like {…3 keys}
+--head N                     Show the first N elements of an array and a summary like …3 more items of the rest
+--eol <lf|crlf>, --crlf      Line ending for output (default: lf)
+--no-trailing-newline        Don't end output with a line ending
+--to <json|yaml|csv|html>    Output format (default: json); csv needs an array of objects
+--css                        With --to html, start with a <style> block for the json-* classes
+--flatten                    Flatten nested values into one object keyed by dotted paths like a.b.0
+--unflatten                  Rebuild nested values from dotted keys, the inverse of --flatten
+--ndjson                     Treat each non-empty line as a separate JSON document
+--multi                      Read a series of JSON documents written back to back, like {...}{...}[...]
+--jsonc, --allow-comments    Allow // line and /* */ block comments
+--preserve-comments          With --jsonc, keep comments in the formatted output (pretty style)
+--allow-trailing-commas      Allow a comma after the last array element or object entry
+--strict-integers            Reject integers that don't fit a signed 64-bit integer
+--warn-precision             Warn about integers that a double can't hold exactly, such as 9007199254740993
+--strict-precision           Reject integers that a double can't hold exactly
+--                           Treat every later argument as a FILE; a FILE of - reads stdin
+-h, --help                   Print this help and exit
+--version                    Print the version and exit";
+
+// Exit codes, so scripts can tell why jp failed without parsing its messages.
+// 1 covers any input that can't be parsed or processed, including a --check or
+// --diff that finds differences.
+const EXIT_INVALID: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+const EXIT_IO: i32 = 3;
+
+// An error already formatted for stderr, with the exit code it should cause
+#[derive(Debug)]
+struct Failure {
+    code: i32,
+    message: String,
+}
+
+impl Failure {
+    fn invalid(message: String) -> Self {
+        Self {
+            code: EXIT_INVALID,
+            message,
+        }
+    }
+    fn usage(message: String) -> Self {
+        Self {
+            code: EXIT_USAGE,
+            message,
+        }
+    }
+    fn io(message: String) -> Self {
+        Self {
+            code: EXIT_IO,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+fn exit_code(e: &Error) -> i32 {
+    match e.kind() {
+        ErrorKind::Io => EXIT_IO,
+        _ => EXIT_INVALID,
+    }
+}
+
+// Output format chosen with --to
+#[derive(Clone, Copy, Default, PartialEq)]
+enum Output {
+    #[default]
+    Json,
+    Yaml,
+    Csv,
+    Html,
+}
+
+// How errors in the input are reported, chosen with --error-format
+#[derive(Clone, Copy, Default, PartialEq)]
+enum ErrorFormat {
+    #[default]
+    Text,
+    // One JSON object per error, for tools to read
+    Json,
+}
+
+// A change to the document made before it is printed
+enum Edit {
+    Set(String, JsonValue<'static>),
+    Delete(String),
+    SelectKeys(Vec<String>),
+    OmitKeys(Vec<String>),
+    DropNulls,
+}
+
+#[derive(Default)]
+struct Options {
+    files: Vec<String>,
+    output: Option<String>,
+    color: Option<String>,
+    pointer: Option<String>,
+    path: Option<String>,
+    only: Option<ValueType>,
+    with_paths: bool,
+    // Changes from --set and --delete, in the order given
+    edits: Vec<Edit>,
+    create: bool,
+    // Pointers and the names to print their values under, from --pick
+    picks: Vec<(String, String)>,
+    lenient: bool,
+    write: bool,
+    dry_run: bool,
+    check: bool,
+    diff: bool,
+    merge: bool,
+    merge_arrays: bool,
+    fail_on_conflict: bool,
+    validate: bool,
+    all_errors: bool,
+    stats: bool,
+    count: bool,
+    print_type: bool,
+    // Refuse options that reorder object keys, from --preserve-order
+    preserve_order: bool,
+    // Exit 0 or 1 for whether this pointer resolves, from --pointer-exists
+    pointer_exists: Option<String>,
+    verbose: bool,
+    quiet: bool,
+    ndjson: bool,
+    // Documents follow each other with no delimiter, from --multi
+    multi: bool,
+    to: Output,
+    css: bool,
+    canonical: bool,
+    flatten: bool,
+    unflatten: bool,
+    recursive: bool,
+    extensions: Vec<String>,
+    // A file listing more FILEs, one per line, or `-` for stdin
+    files_from: Option<String>,
+    warn_precision: bool,
+    pretty_errors: bool,
+    error_format: ErrorFormat,
+    max_size: Option<usize>,
+    parse: ParseOptions,
+    format: FormatOptions,
+}
+
+fn print_help() {
+    println!("{}\n\n{}", USAGE, HELP);
     std::process::exit(0);
 }
 
-fn main() {
-    let mut buffer = String::new();
-    let args: Vec<String> = std::env::args().skip(1).collect();
+// Take the value following a flag, exiting if it is missing
+fn flag_value(args: &mut impl Iterator<Item = String>, flag: &str) -> String {
+    args.next().unwrap_or_else(|| {
+        eprintln!("jp: {} requires a value", flag);
+        std::process::exit(EXIT_USAGE);
+    })
+}
+
+// Apply an --indent value, N spaces or `tab`, naming `source` if it is invalid
+fn set_indent(format: &mut FormatOptions, value: &str, source: &str) {
+    format.indent_string = None;
+    if value == "tab" {
+        format.tabs = true;
+    } else {
+        format.tabs = false;
+        format.indent = value.parse().unwrap_or_else(|_| {
+            eprintln!("jp: invalid {} value: {}", source, value);
+            std::process::exit(EXIT_USAGE);
+        });
+    }
+}
+
+// Split a comma-separated list of object keys
+fn key_list(list: &str) -> Vec<String> {
+    list.split(',').map(str::to_string).collect()
+}
+
+fn as_strs(strings: &[String]) -> Vec<&str> {
+    strings.iter().map(String::as_str).collect()
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Options {
+    let mut options = Options::default();
+    // A default for --indent, so a house style needn't be passed every time
+    match std::env::var("JP_INDENT") {
+        Ok(value) => set_indent(&mut options.format, &value, "JP_INDENT"),
+        Err(std::env::VarError::NotUnicode(value)) => {
+            eprintln!("jp: invalid JP_INDENT value: {}", value.to_string_lossy());
+            std::process::exit(EXIT_USAGE);
+        }
+        Err(std::env::VarError::NotPresent) => {}
+    }
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--help" {
+            print_help();
+        } else if arg == "--version" {
+            println!("jp {}", env!("CARGO_PKG_VERSION"));
+            std::process::exit(0);
+        } else if arg == "--theme" {
+            options.format.theme = match flag_value(&mut args, &arg).as_str() {
+                "default" => Theme::Default,
+                "dark" => Theme::Dark,
+                "light" => Theme::Light,
+                "none" => Theme::None,
+                other => {
+                    eprintln!("jp: invalid --theme value: {}", other);
+                    std::process::exit(EXIT_USAGE);
+                }
+            };
+        } else if arg == "--color" {
+            options.color = Some(flag_value(&mut args, &arg));
+        } else if let Some(value) = arg.strip_prefix("--color=") {
+            options.color = Some(value.to_string());
+        } else if arg == "--pointer" {
+            options.pointer = Some(flag_value(&mut args, &arg));
+        } else if arg == "--path" {
+            options.path = Some(flag_value(&mut args, &arg));
+        } else if arg == "--only" {
+            options.only = Some(match flag_value(&mut args, &arg).as_str() {
+                "string" => ValueType::String,
+                "number" => ValueType::Number,
+                "bool" => ValueType::Bool,
+                "null" => ValueType::Null,
+                "object" => ValueType::Object,
+                "array" => ValueType::Array,
+                other => {
+                    eprintln!("jp: invalid --only value: {}", other);
+                    std::process::exit(EXIT_USAGE);
+                }
+            });
+        } else if arg == "--with-paths" {
+            options.with_paths = true;
+        } else if arg == "--set" {
+            let pointer = flag_value(&mut args, &arg);
+            let value = flag_value(&mut args, &arg);
+            let value = value.parse::<JsonValue>().unwrap_or_else(|e| {
+                eprintln!("jp: invalid --set value for {}: {}", pointer, describe(&e));
+                std::process::exit(EXIT_USAGE);
+            });
+            options.edits.push(Edit::Set(pointer, value));
+        } else if arg == "--delete" {
+            options
+                .edits
+                .push(Edit::Delete(flag_value(&mut args, &arg)));
+        } else if arg == "--select-keys" {
+            options
+                .edits
+                .push(Edit::SelectKeys(key_list(&flag_value(&mut args, &arg))));
+        } else if arg == "--omit-keys" {
+            options
+                .edits
+                .push(Edit::OmitKeys(key_list(&flag_value(&mut args, &arg))));
+        } else if arg == "--drop-nulls" {
+            options.edits.push(Edit::DropNulls);
+        } else if arg == "--create" {
+            options.create = true;
+        } else if arg == "--pick" {
+            let value = flag_value(&mut args, &arg);
+            // Split at the last '=', since keys in the pointer may contain one
+            match value.rsplit_once('=') {
+                Some((pointer, name))
+                    if !name.is_empty() && (pointer.is_empty() || pointer.starts_with('/')) =>
+                {
+                    options.picks.push((pointer.to_string(), name.to_string()))
+                }
+                _ => {
+                    eprintln!(
+                        "jp: invalid --pick value: {} (expected POINTER=NAME, e.g. /a/0=first)",
+                        value
+                    );
+                    std::process::exit(EXIT_USAGE);
+                }
+            }
+        } else if arg == "--lenient" {
+            options.lenient = true;
+        } else if arg == "-o" || arg == "--output" {
+            options.output = Some(flag_value(&mut args, &arg));
+        } else if arg == "--write" {
+            options.write = true;
+        } else if arg == "--dry-run" {
+            options.dry_run = true;
+        } else if arg == "--check" {
+            options.check = true;
+        } else if arg == "--files-from" {
+            options.files_from = Some(flag_value(&mut args, &arg));
+        } else if arg == "--recursive" {
+            options.recursive = true;
+        } else if arg == "--ext" {
+            options.extensions = flag_value(&mut args, &arg)
+                .split(',')
+                .map(|ext| ext.trim_start_matches('.').to_string())
+                .filter(|ext| !ext.is_empty())
+                .collect();
+        } else if arg == "--diff" {
+            options.diff = true;
+        } else if arg == "--merge" {
+            options.merge = true;
+        } else if arg == "--merge-arrays" {
+            options.merge = true;
+            options.merge_arrays = true;
+        } else if arg == "--fail-on-conflict" {
+            options.fail_on_conflict = true;
+        } else if arg == "--validate" || arg == "-q" {
+            options.validate = true;
+        } else if arg == "--all-errors" {
+            options.all_errors = true;
+        } else if arg == "--stats" {
+            options.stats = true;
+        } else if arg == "--count" {
+            options.count = true;
+        } else if arg == "--type" {
+            options.print_type = true;
+        } else if arg == "--pointer-exists" {
+            let pointer = flag_value(&mut args, &arg);
+            if !pointer.is_empty() && !pointer.starts_with('/') {
+                eprintln!("jp: JSON Pointer must start with '/': {}", pointer);
+                std::process::exit(EXIT_USAGE);
+            }
+            options.pointer_exists = Some(pointer);
+        } else if arg == "-v" || arg == "--verbose" {
+            options.verbose = true;
+        } else if arg == "--quiet" {
+            options.quiet = true;
+        } else if arg == "--ascii" {
+            options.format.ascii = true;
+        } else if arg == "--escape-slashes" {
+            options.format.escape_slashes = true;
+        } else if arg == "--style" {
+            options.format.style = match flag_value(&mut args, &arg).as_str() {
+                "pretty" => Style::Pretty,
+                "spaced" => Style::Spaced,
+                "compact" => Style::Compact,
+                other => {
+                    eprintln!("jp: invalid --style value: {}", other);
+                    std::process::exit(EXIT_USAGE);
+                }
+            };
+        } else if arg == "-c" || arg == "--compact" {
+            options.format.style = Style::Compact;
+        } else if arg == "--compact-threshold" {
+            let value = flag_value(&mut args, &arg);
+            options.format.compact_threshold = Some(value.parse().unwrap_or_else(|_| {
+                eprintln!("jp: invalid --compact-threshold value: {}", value);
+                std::process::exit(EXIT_USAGE);
+            }));
+        } else if arg == "--indent" {
+            let value = flag_value(&mut args, &arg);
+            set_indent(&mut options.format, &value, &arg);
+        } else if arg == "--indent-string" {
+            let value = flag_value(&mut args, &arg);
+            if !value.chars().all(|c| " \t\r\n".contains(c)) {
+                eprintln!(
+                    "jp: invalid --indent-string value: {:?} (only spaces, tabs, CR and LF keep the output valid JSON)",
+                    value
+                );
+                std::process::exit(EXIT_USAGE);
+            }
+            options.format.indent_string = Some(value);
+        } else if arg == "--tab-size" {
+            let value = flag_value(&mut args, &arg);
+            options.format.tab_size = value.parse().unwrap_or_else(|_| {
+                eprintln!("jp: invalid --tab-size value: {}", value);
+                std::process::exit(EXIT_USAGE);
+            });
+        } else if arg == "--eol" {
+            options.format.eol = match flag_value(&mut args, &arg).as_str() {
+                "lf" => Newline::Lf,
+                "crlf" => Newline::Crlf,
+                other => {
+                    eprintln!("jp: invalid --eol value: {}", other);
+                    std::process::exit(EXIT_USAGE);
+                }
+            };
+        } else if arg == "--crlf" {
+            options.format.eol = Newline::Crlf;
+        } else if arg == "--no-trailing-newline" {
+            options.format.trailing_newline = false;
+        } else if arg == "--preserve-order" {
+            options.preserve_order = true;
+        } else if arg == "--sort-keys" {
+            options.format.sort_keys = KeyOrder::Asc;
+        } else if arg == "--sort-keys-by" {
+            options.format.sort_keys = match flag_value(&mut args, &arg).as_str() {
+                "asc" => KeyOrder::Asc,
+                "desc" => KeyOrder::Desc,
+                "length" => KeyOrder::Length,
+                other => {
+                    eprintln!("jp: invalid --sort-keys-by value: {}", other);
+                    std::process::exit(EXIT_USAGE);
+                }
+            };
+        } else if arg == "--line-width" {
+            let value = flag_value(&mut args, &arg);
+            options.format.line_width = Some(value.parse().unwrap_or_else(|_| {
+                eprintln!("jp: invalid --line-width value: {}", value);
+                std::process::exit(EXIT_USAGE);
+            }));
+        } else if arg == "-r" || arg == "--raw-output" {
+            options.format.raw_strings = true;
+        } else if arg == "--inline-scalar-arrays" || arg == "--compact-arrays" {
+            options.format.inline_scalar_arrays = true;
+        } else if arg == "--trailing-comma" {
+            options.format.trailing_commas = true;
+        } else if arg == "--numbers-as-strings" {
+            options.format.numbers_as_strings = true;
+        } else if arg == "--max-size" {
+            let value = flag_value(&mut args, &arg);
+            options.max_size = Some(value.parse().unwrap_or_else(|_| {
+                eprintln!("jp: invalid --max-size value: {}", value);
+                std::process::exit(EXIT_USAGE);
+            }));
+        } else if arg == "--depth" {
+            let value = flag_value(&mut args, &arg);
+            options.format.depth = Some(value.parse().unwrap_or_else(|_| {
+                eprintln!("jp: invalid --depth value: {}", value);
+                std::process::exit(EXIT_USAGE);
+            }));
+        } else if arg == "--head" {
+            let value = flag_value(&mut args, &arg);
+            options.format.head = Some(value.parse().unwrap_or_else(|_| {
+                eprintln!("jp: invalid --head value: {}", value);
+                std::process::exit(EXIT_USAGE);
+            }));
+        } else if arg == "--to" {
+            options.to = match flag_value(&mut args, &arg).as_str() {
+                "json" => Output::Json,
+                "yaml" => Output::Yaml,
+                "csv" => Output::Csv,
+                "html" => Output::Html,
+                other => {
+                    eprintln!("jp: invalid --to value: {}", other);
+                    std::process::exit(EXIT_USAGE);
+                }
+            };
+        } else if arg == "--canonical" {
+            options.canonical = true;
+        } else if arg == "--css" {
+            options.css = true;
+        } else if arg == "--flatten" {
+            options.flatten = true;
+        } else if arg == "--unflatten" {
+            options.unflatten = true;
+        } else if arg == "--ndjson" {
+            options.ndjson = true;
+        } else if arg == "--multi" {
+            options.multi = true;
+        } else if arg == "--jsonc" || arg == "--allow-comments" {
+            options.parse.allow_comments = true;
+        } else if arg == "--preserve-comments" {
+            options.parse.preserve_comments = true;
+        } else if arg == "--allow-trailing-commas" {
+            options.parse.allow_trailing_commas = true;
+        } else if arg == "--strict-integers" {
+            options.parse.strict_integers = true;
+        } else if arg == "--strict-precision" {
+            options.parse.strict_precision = true;
+        } else if arg == "--warn-precision" {
+            options.warn_precision = true;
+        } else if arg == "--pretty-errors" {
+            options.pretty_errors = true;
+        } else if arg == "--error-format" {
+            options.error_format = match flag_value(&mut args, &arg).as_str() {
+                "text" => ErrorFormat::Text,
+                "json" => ErrorFormat::Json,
+                other => {
+                    eprintln!("jp: invalid --error-format value: {}", other);
+                    std::process::exit(EXIT_USAGE);
+                }
+            };
+        } else if arg == "--" {
+            // Everything after `--` is a file, even if it starts with `-`
+            options.files.extend(&mut args);
+        } else if arg.starts_with('-') && arg != "-" {
+            eprintln!("jp: unknown option: {}", arg);
+            eprintln!("{}", USAGE);
+            std::process::exit(EXIT_USAGE);
+        } else {
+            options.files.push(arg);
+        }
+    }
+
+    // Quiet leaves only the output itself and errors
+    if options.quiet {
+        options.stats = false;
+    }
+    if options.extensions.is_empty() {
+        options.extensions = vec!["json".to_string()];
+    }
+    options
+}
+
+// The non-empty lines of a --files-from list, each naming a file
+fn list_files(list: &str) -> Result<Vec<String>, Failure> {
+    let contents = if list == "-" {
+        io::read_to_string(io::stdin())
+            .map_err(|e| Failure::io(format!("Error reading from stdin: {}", e)))?
+    } else {
+        let mut file = File::open(list)
+            .map_err(|_| Failure::io(format!("jp: {}: No such file or directory", list)))?;
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)
+            .map_err(|e| Failure::io(format!("Error reading from file {}: {}", list, e)))?;
+        contents
+    };
+    Ok(contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(String::from)
+        .collect())
+}
+
+// Replace each directory argument with the files below it whose extension is
+// listed, in sorted order. Files named directly are always kept.
+fn expand_files(files: &[String], extensions: &[String]) -> Result<Vec<String>, Failure> {
+    let mut expanded = Vec::new();
+    for filename in files {
+        let path = std::path::Path::new(filename);
+        if path.is_dir() {
+            collect_files(path, extensions, &mut expanded)?;
+        } else {
+            expanded.push(filename.clone());
+        }
+    }
+    Ok(expanded)
+}
+
+fn collect_files(
+    dir: &std::path::Path,
+    extensions: &[String],
+    files: &mut Vec<String>,
+) -> Result<(), Failure> {
+    let entries =
+        fs::read_dir(dir).map_err(|e| Failure::io(format!("jp: {}: {}", dir.display(), e)))?;
+    let mut paths = entries
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| Failure::io(format!("jp: {}: {}", dir.display(), e)))?;
+    paths.sort();
+
+    for path in paths {
+        // Don't follow symlinked directories, which could loop
+        if path.symlink_metadata().is_ok_and(|meta| meta.is_dir()) {
+            collect_files(&path, extensions, files)?;
+        } else if path.is_file()
+            && path
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| extensions.iter().any(|wanted| wanted == ext))
+        {
+            files.push(path.to_string_lossy().into_owned());
+        }
+    }
+    Ok(())
+}
+
+// `-` names stdin, so it can be mixed with files
+fn read_file(filename: &str, options: &Options) -> Result<String, Failure> {
+    let refuse = || Failure::io(format!("jp: {}: {}", filename, too_large(options)));
+    if is_url(filename) {
+        return read_url(filename, options)?.ok_or_else(refuse);
+    }
+    if filename == "-" {
+        return read_to_limit(io::stdin(), options)
+            .map_err(|e| Failure::io(format!("Error reading from stdin: {}", e)))?
+            .ok_or_else(refuse);
+    }
+    let file = File::open(filename)
+        .map_err(|_| Failure::io(format!("jp: {}: No such file or directory", filename)))?;
+    read_to_limit(file, options)
+        .map_err(|e| Failure::io(format!("Error reading from file {}: {}", filename, e)))?
+        .ok_or_else(refuse)
+}
+
+// All of `reader` as text, or `None` as soon as it passes --max-size, before
+// the rest is read
+fn read_to_limit(mut reader: impl Read, options: &Options) -> io::Result<Option<String>> {
+    let mut bytes = Vec::new();
+    match options.max_size {
+        Some(limit) => {
+            reader.take(limit as u64 + 1).read_to_end(&mut bytes)?;
+            if bytes.len() > limit {
+                return Ok(None);
+            }
+        }
+        None => {
+            reader.read_to_end(&mut bytes)?;
+        }
+    }
+    String::from_utf8(bytes).map(Some).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "stream did not contain valid UTF-8",
+        )
+    })
+}
+
+fn too_large(options: &Options) -> String {
+    format!(
+        "input is larger than the --max-size limit of {} bytes",
+        options.max_size.unwrap_or_default()
+    )
+}
+
+fn is_url(filename: &str) -> bool {
+    filename.starts_with("http://") || filename.starts_with("https://")
+}
+
+// The body at `url`, or `None` as soon as it passes --max-size, as for
+// `read_to_limit`
+#[cfg(feature = "http")]
+fn read_url(url: &str, options: &Options) -> Result<Option<String>, Failure> {
+    fetch::fetch(url, options.max_size).map_err(|e| Failure::io(format!("jp: {}: {}", url, e)))
+}
 
-    if let Some(filename) = args.get(0) {
-        let mut file = match File::open(filename) {
-            Ok(f) => f,
-            Err(_) => {
-                eprintln!("jp: {}: No such file or directory", filename);
-                std::process::exit(1);
+#[cfg(not(feature = "http"))]
+fn read_url(url: &str, _: &Options) -> Result<Option<String>, Failure> {
+    Err(Failure::usage(format!(
+        "jp: {}: reading URLs needs jp built with the http feature",
+        url
+    )))
+}
+
+fn stdin_lock() -> io::StdinLock<'static> {
+    // With no FILE and nothing piped in there's no input at all
+    if io::stdin().is_terminal() {
+        eprintln!("{}", USAGE);
+        std::process::exit(EXIT_USAGE);
+    }
+    io::stdin().lock()
+}
+
+fn read_stdin(options: &Options) -> String {
+    // Allow piped input via stdin
+    let buffer = read_to_limit(stdin_lock(), options).unwrap_or_else(|e| {
+        eprintln!("Error reading from stdin: {}", e);
+        std::process::exit(EXIT_IO);
+    });
+    buffer.unwrap_or_else(|| {
+        eprintln!("jp: {}", too_large(options));
+        std::process::exit(EXIT_IO);
+    })
+}
+
+// Write a parsed value in the format chosen with --to
+fn convert(value: &JsonValue, options: &Options) -> Result<String, Error> {
+    match options.to {
+        Output::Json if options.canonical => to_canonical(value),
+        Output::Json => Ok(serialize_pretty(value, &options.format)),
+        Output::Yaml => Ok(to_yaml(value)),
+        Output::Csv => to_csv(value),
+        Output::Html if options.css => Ok(format!(
+            "<style>{}{}{}</style>{}{}",
+            eol(options),
+            STYLESHEET,
+            eol(options),
+            eol(options),
+            to_html(value, &options.format)
+        )),
+        Output::Html => Ok(to_html(value, &options.format)),
+    }
+}
+
+// Whether output goes through the parsed value for a conversion or transform
+fn transforms_value(options: &Options) -> bool {
+    options.to != Output::Json || options.flatten || options.unflatten || options.canonical
+}
+
+// Whether the document is changed before anything is selected or printed
+fn edits_value(options: &Options) -> bool {
+    !options.edits.is_empty()
+}
+
+// An object of the values at each --pick pointer, under their names
+fn pick<'v>(value: &JsonValue<'v>, options: &Options) -> Result<JsonValue<'v>, Error> {
+    options
+        .picks
+        .iter()
+        .filter_map(|(pointer, name)| match resolve_pointer(value, pointer) {
+            Ok(found) => Some(Ok((name.clone(), found.clone()))),
+            Err(_) if options.lenient => None,
+            Err(e) => Some(Err(e)),
+        })
+        .collect()
+}
+
+fn format_input(input: &str, options: &Options) -> Result<String, Error> {
+    if transforms_value(options) || edits_value(options) || !options.picks.is_empty() {
+        let mut value = parse_value_with(input, &options.parse)?;
+        for edit in &options.edits {
+            match edit {
+                Edit::Set(pointer, new) => {
+                    set_pointer(&mut value, pointer, new.clone(), options.create)?
+                }
+                Edit::Delete(pointer) => {
+                    delete_pointer(&mut value, pointer)?;
+                }
+                Edit::SelectKeys(keys) => select_keys(&mut value, &as_strs(keys)),
+                Edit::OmitKeys(keys) => omit_keys(&mut value, &as_strs(keys)),
+                Edit::DropNulls => drop_nulls(&mut value),
             }
+        }
+        let picked;
+        let target = if options.picks.is_empty() {
+            resolve_pointer(&value, options.pointer.as_deref().unwrap_or(""))?
+        } else {
+            picked = pick(&value, options)?;
+            &picked
         };
-        file.read_to_string(&mut buffer).unwrap_or_else(|e| {
-            eprintln!("Error reading from file {}: {}", filename, e);
-            std::process::exit(1);
+        let transformed;
+        let target = if options.flatten {
+            transformed = flatten(target);
+            &transformed
+        } else if options.unflatten {
+            transformed = unflatten(target)?;
+            &transformed
+        } else {
+            target
+        };
+        return convert(target, options);
+    }
+    if let Some(value_type) = options.only {
+        return Ok(select_values(input, value_type, options)?.join(eol(options)));
+    }
+    if let Some(path) = &options.path {
+        return query_path(input, path, &options.parse, &options.format);
+    }
+    if options.ndjson {
+        return parse_ndjson(input, &options.parse, &options.format);
+    }
+    if options.multi {
+        return parse_multi(input, &options.parse, &options.format);
+    }
+    match &options.pointer {
+        Some(pointer) => query(input, pointer, &options.parse, &options.format),
+        None => parse(input, &options.parse, &options.format),
+    }
+}
+
+// Each value of one type, optionally labelled with its pointer from the document root
+fn select_values(
+    input: &str,
+    value_type: ValueType,
+    options: &Options,
+) -> Result<Vec<String>, Error> {
+    let value = parse_value_with(input, &options.parse)?;
+    let pointer = options.pointer.as_deref().unwrap_or("");
+    let target = resolve_pointer(&value, pointer)?;
+    let lines = select_type(target, value_type)
+        .into_iter()
+        .map(|(path, found)| {
+            let formatted = serialize_pretty(found, &options.format);
+            if !options.with_paths {
+                return formatted;
+            }
+            let path = format!("{}{}", pointer, path);
+            let path = if path.is_empty() { "(root)" } else { &path };
+            format!("{}: {}", path, formatted)
+        })
+        .collect();
+    Ok(lines)
+}
+
+// Line ending between lines of output, matching the formatter's
+fn eol(options: &Options) -> &'static str {
+    options.format.eol.as_str()
+}
+
+// What follows each document: a line ending unless --no-trailing-newline
+fn terminator(options: &Options) -> &'static str {
+    if !options.format.trailing_newline {
+        ""
+    } else {
+        eol(options)
+    }
+}
+
+// Write formatted input followed by a newline, streaming when there's no pointer or NDJSON
+fn write_input(out: &mut impl Write, input: &str, options: &Options) -> Result<(), Error> {
+    if options.path.is_some() || options.only.is_some() {
+        // A path that matches nothing prints nothing, not an empty line
+        let matches = format_input(input, options)?;
+        if !matches.is_empty() {
+            write!(out, "{}{}", matches, terminator(options))?;
+        }
+    } else if options.ndjson
+        || options.multi
+        || transforms_value(options)
+        || edits_value(options)
+        || !options.picks.is_empty()
+        || options.pointer.is_some()
+    {
+        write!(
+            out,
+            "{}{}",
+            format_input(input, options)?,
+            terminator(options)
+        )?;
+    } else {
+        parse_to(out, input, &options.parse, &options.format)?;
+        write!(out, "{}", terminator(options))?;
+    }
+    Ok(())
+}
+
+// Plain validation reads files as a stream, so their size doesn't matter.
+// NDJSON and --stats need each document in memory, and so does --max-size,
+// which is enforced while reading it.
+fn streams(options: &Options) -> bool {
+    !options.ndjson
+        && !options.multi
+        && !options.stats
+        && !options.warn_precision
+        && !options.pretty_errors
+        && options.max_size.is_none()
+}
+
+// Warn on stderr about each integer an `f64` would round, for --warn-precision.
+// Invalid input is left for the caller to report.
+fn warn_precision(input: &str, filename: Option<&str>, options: &Options) {
+    if !options.warn_precision {
+        return;
+    }
+    let Ok(tokens) = tokenize_with(input, &options.parse) else {
+        return;
+    };
+    let label = filename
+        .map(|name| format!("{}: ", name))
+        .unwrap_or_default();
+    for token in tokens {
+        if let Some(JsonValue::Number(number)) = &token.value {
+            if !number.is_exact() {
+                eprintln!(
+                    "jp: {}warning: integer {} is not exactly representable as a double, line {} column {}",
+                    label,
+                    number.raw(),
+                    token.line,
+                    token.column
+                );
+            }
+        }
+    }
+}
+
+fn validate_file_stream(filename: &str, options: &Options) -> Result<(), Failure> {
+    let file = File::open(filename)
+        .map_err(|_| Failure::io(format!("jp: {}: No such file or directory", filename)))?;
+    validate_reader(io::BufReader::new(file), &options.parse)
+        .map_err(|e| file_failure(filename, &e, options))
+}
+
+fn validate_input(input: &str, options: &Options) -> Result<(), Error> {
+    if options.ndjson {
+        validate_ndjson(input, &options.parse)
+    } else if options.multi {
+        validate_multi(input, &options.parse)
+    } else {
+        validate(input, &options.parse)
+    }
+}
+
+// Every error in the input instead of just the first
+fn all_errors(input: &str, options: &Options) -> Vec<Error> {
+    if options.ndjson {
+        validate_all_ndjson(input, &options.parse)
+    } else {
+        validate_all(input, &options.parse)
+    }
+}
+
+// Token and byte counts from the lexer alone; quiet drops the labels
+fn count_summary(input: &str, options: &Options) -> Result<String, Error> {
+    let tokens = tokenize_with(input, &options.parse)?.len();
+    Ok(if options.quiet {
+        format!("{} {}", tokens, input.len())
+    } else {
+        format!("{} tokens, {} bytes", tokens, input.len())
+    })
+}
+
+// The JSON type of the document, or of the value at --pointer
+fn type_summary(input: &str, options: &Options) -> Result<String, Error> {
+    let value = parse_value_with(input, &options.parse)?;
+    let target = resolve_pointer(&value, options.pointer.as_deref().unwrap_or(""))?;
+    let name = match target.value_type() {
+        ValueType::Null => "null",
+        ValueType::Bool => "boolean",
+        ValueType::Number => "number",
+        ValueType::String => "string",
+        ValueType::Array => "array",
+        ValueType::Object => "object",
+    };
+    Ok(name.to_string())
+}
+
+// Exit 0 when the pointer resolves in stdin or in every file, and 1 otherwise,
+// printing nothing but errors, or the values found with --verbose
+fn pointer_exists(pointer: &str, options: &Options) -> ! {
+    let inputs = if options.files.is_empty() {
+        vec![("-".to_string(), Ok(read_stdin(options)))]
+    } else {
+        options
+            .files
+            .iter()
+            .map(|filename| (filename.clone(), read_file(filename, options)))
+            .collect()
+    };
+    let mut status = 0;
+    for (filename, input) in inputs {
+        let result = input.and_then(|input| {
+            let value = parse_value_with(&input, &options.parse)
+                .map_err(|e| input_failure(&filename, &e, &input, options))?;
+            let Ok(found) = resolve_pointer(&value, pointer) else {
+                return Ok(false);
+            };
+            if options.verbose {
+                let output =
+                    convert(found, options).map_err(|e| file_failure(&filename, &e, options))?;
+                print!("{}{}", output, terminator(options));
+            }
+            Ok(true)
         });
-    } else if !io::stdin().is_terminal() {
-        // Allow piped input via stdin
-        io::stdin().read_to_string(&mut buffer).unwrap_or_else(|e| {
-            eprintln!("Error reading from stdin: {}", e);
-            std::process::exit(1);
+        match result {
+            Ok(true) => {}
+            // A missing value is the answer, not an error to report
+            Ok(false) => status = status.max(EXIT_INVALID),
+            Err(e) => {
+                eprintln!("{}", e);
+                status = status.max(e.code);
+            }
+        }
+    }
+    std::process::exit(status);
+}
+
+// Print a one-line summary of stdin or of each file, labelled by file name when
+// there are several, and exit with the worst status
+fn print_summaries(options: &Options, summary: fn(&str, &Options) -> Result<String, Error>) -> ! {
+    let mut status = 0;
+    if options.files.is_empty() {
+        let input = read_stdin(options);
+        match summary(&input, options) {
+            Ok(summary) => println!("{}", summary),
+            Err(e) => {
+                eprintln!(
+                    "{}",
+                    with_snippet(error_message(&e, options), &e, &input, options)
+                );
+                status = exit_code(&e);
+            }
+        }
+    }
+    let multiple = options.files.len() > 1;
+    for filename in &options.files {
+        let result = read_file(filename, options).and_then(|input| {
+            summary(&input, options).map_err(|e| input_failure(filename, &e, &input, options))
         });
+        match result {
+            Ok(summary) if multiple && !options.quiet => println!("{}: {}", filename, summary),
+            Ok(summary) => println!("{}", summary),
+            Err(e) => {
+                eprintln!("{}", e);
+                status = status.max(e.code);
+            }
+        }
+    }
+    std::process::exit(status);
+}
+
+// Print document metrics to stderr so stdout stays the formatted document
+fn print_stats(input: &str, options: &Options, filename: Option<&str>) {
+    let result = if options.ndjson {
+        stats_ndjson(input, &options.parse)
     } else {
-        print_usage();
+        stats(input, &options.parse)
+    };
+    if let Ok(stats) = result {
+        if let Some(filename) = filename {
+            eprintln!("==> {} <==", filename);
+        }
+        eprintln!("{}", stats);
+    }
+}
+
+fn describe(e: &Error) -> String {
+    match e {
+        Error::InvalidPointer(e) | Error::InvalidPath(e) | Error::Unsupported(e) => e.clone(),
+        e => format!("Invalid JSON: {}", e),
+    }
+}
+
+fn error_message(e: &Error, options: &Options) -> String {
+    match e {
+        _ if options.error_format == ErrorFormat::Json => error_json(e, None),
+        Error::InvalidPointer(_) | Error::InvalidPath(_) | Error::Unsupported(_) => {
+            format!("jp: {}", describe(e))
+        }
+        e => describe(e),
+    }
+}
+
+// An error as one compact JSON object on a line, for --error-format json. The
+// line and column are null when the error has no position.
+fn error_json(e: &Error, filename: Option<&str>) -> String {
+    let (line, column) = match e.position() {
+        Some((line, column)) => (line.into(), column.into()),
+        None => (JsonValue::Null, JsonValue::Null),
+    };
+    let mut fields = vec![
+        ("error", JsonValue::from(e.name().to_string())),
+        ("message", JsonValue::from(e.to_string())),
+        ("line", line),
+        ("column", column),
+    ];
+    if let Some(filename) = filename {
+        fields.push(("file", JsonValue::from(filename.to_string())));
+    }
+    let options = FormatOptions {
+        style: Style::Compact,
+        ..FormatOptions::default()
+    };
+    serialize_pretty(&fields.into_iter().collect(), &options)
+}
+
+// An error in one file, labelled with its name
+fn file_failure(filename: &str, e: &Error, options: &Options) -> Failure {
+    let message = match options.error_format {
+        ErrorFormat::Text => format!("jp: {}: {}", filename, describe(e)),
+        ErrorFormat::Json => error_json(e, Some(filename)),
+    };
+    Failure {
+        code: exit_code(e),
+        message,
+    }
+}
+
+// `file_failure`, followed by the error's snippet with --pretty-errors
+fn input_failure(filename: &str, e: &Error, input: &str, options: &Options) -> Failure {
+    let failure = file_failure(filename, e, options);
+    Failure {
+        message: with_snippet(failure.message, e, input, options),
+        ..failure
+    }
+}
+
+// An error message followed, with --pretty-errors, by the line of input the
+// error is on and a caret under its column. JSON errors stay on one line.
+fn with_snippet(message: String, e: &Error, input: &str, options: &Options) -> String {
+    let position = e
+        .position()
+        .filter(|_| options.pretty_errors && options.error_format == ErrorFormat::Text);
+    match position {
+        Some((line, column)) => format!("{}\n{}", message, snippet(input, line, column)),
+        None => message,
+    }
+}
+
+// Lines and columns count as the lexer does: `\r\n`, `\n` and `\r` each end a
+// line, and a column is one character. Long lines are cut down to the part
+// around the column.
+fn snippet(input: &str, line: usize, column: usize) -> String {
+    const CONTEXT: usize = 40;
+    let text = input
+        .split("\r\n")
+        .flat_map(|part| part.split(['\n', '\r']))
+        .nth(line - 1)
+        .unwrap_or("");
+    let chars = text.chars().collect::<Vec<_>>();
+    let start = column.saturating_sub(CONTEXT + 1);
+    let end = chars.len().min(column + CONTEXT);
+    let mut shown = String::new();
+    let mut caret = String::new();
+    if start > 0 {
+        shown.push('…');
+        caret.push(' ');
+    }
+    shown.extend(&chars[start.min(end)..end]);
+    for i in start..column.saturating_sub(1) {
+        // Keep tabs so the caret lines up however wide they display
+        caret.push(if chars.get(i) == Some(&'\t') {
+            '\t'
+        } else {
+            ' '
+        });
+    }
+    if end < chars.len() {
+        shown.push('…');
+    }
+    let gutter = " ".repeat(line.to_string().len());
+    format!(
+        "{} |\n{} | {}\n{} | {}^",
+        gutter, line, shown, gutter, caret
+    )
+}
+
+// Read a file and produce its canonical formatting, as written by --write
+fn format_file(filename: &str, options: &Options) -> Result<(String, String), Failure> {
+    let input = read_file(filename, options)?;
+    let json =
+        format_input(&input, options).map_err(|e| input_failure(filename, &e, &input, options))?;
+    Ok((input, format!("{}{}", json, terminator(options))))
+}
+
+// Format a file for --write: the new contents, or `None` when it is unchanged
+fn rewrite_file(filename: &str, options: &Options) -> Result<Option<String>, Failure> {
+    if filename == "-" || is_url(filename) {
+        return Err(Failure::usage(format!(
+            "jp: {}: --write only works on files",
+            filename
+        )));
+    }
+    let (input, formatted) = format_file(filename, options)?;
+
+    // Never replace a file with output that doesn't parse back
+    if let Err(e) = parse(&formatted, &options.parse, &FormatOptions::default()) {
+        return Err(Failure::invalid(format!(
+            "jp: {}: refusing to write invalid output: {}",
+            filename, e
+        )));
+    }
+    Ok((formatted != input).then_some(formatted))
+}
+
+// Format a file and overwrite it, leaving it untouched if invalid or unchanged
+fn write_in_place(filename: &str, options: &Options) -> Result<(), Failure> {
+    if let Some(formatted) = rewrite_file(filename, options)? {
+        fs::write(filename, formatted)
+            .map_err(|e| Failure::io(format!("Error writing to file {}: {}", filename, e)))?;
+    }
+    Ok(())
+}
+
+// Whether a file already matches its canonical formatting byte-for-byte
+fn check_file(filename: &str, options: &Options) -> Result<bool, Failure> {
+    let (input, formatted) = format_file(filename, options)?;
+    Ok(formatted == input)
+}
+
+fn read_or_exit(filename: &str, options: &Options) -> String {
+    read_file(filename, options).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(e.code);
+    })
+}
+
+fn parse_or_exit<'a>(filename: &str, input: &'a str, options: &Options) -> JsonValue<'a> {
+    parse_value_with(input, &options.parse).unwrap_or_else(|e| {
+        eprintln!("{}", file_failure(filename, &e, options));
+        std::process::exit(exit_code(&e));
+    })
+}
+
+// Print structural differences between two files, exiting 0 when they're
+// equal and 1 when they differ, like diff(1)
+fn diff_files(options: &Options) -> ! {
+    let [a, b] = options.files.as_slice() else {
+        eprintln!("jp: --diff requires exactly two FILEs");
+        std::process::exit(EXIT_USAGE);
+    };
+    let (input_a, input_b) = (read_or_exit(a, options), read_or_exit(b, options));
+    let value_a = parse_or_exit(a, &input_a, options);
+    let value_b = parse_or_exit(b, &input_b, options);
+
+    let changes = diff(&value_a, &value_b);
+    for change in &changes {
+        println!("{}", change);
+    }
+    std::process::exit(if changes.is_empty() { 0 } else { 1 });
+}
+
+// Deep-merge every file in order, later files winning, and format the result
+fn merge_files(options: &Options) -> Result<String, Failure> {
+    if options.files.is_empty() {
+        return Err(Failure::usage(
+            "jp: --merge requires at least one FILE".to_string(),
+        ));
+    }
+    let inputs = options
+        .files
+        .iter()
+        .map(|filename| read_file(filename, options))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut merged: Option<JsonValue> = None;
+    for (i, (filename, input)) in options.files.iter().zip(&inputs).enumerate() {
+        let value = parse_value_with(input, &options.parse)
+            .map_err(|e| input_failure(filename, &e, input, options))?;
+        // Merging is only defined for objects
+        if value.as_object().is_none() {
+            return Err(Failure::invalid(format!(
+                "jp: {}: top-level value must be an object to merge",
+                filename
+            )));
+        }
+        if let Some(pointer) = merged
+            .as_ref()
+            .filter(|_| options.fail_on_conflict)
+            .and_then(|into| merge_conflict(into, &value, options.merge_arrays))
+        {
+            return Err(merge_conflict_failure(&pointer, i, &inputs, options));
+        }
+        match merged.as_mut() {
+            Some(into) if options.merge_arrays => merge_concat(into, value),
+            Some(into) => merge(into, value),
+            None => merged = Some(value),
+        }
+    }
+
+    let merged = merged.unwrap_or(JsonValue::Null);
+    convert(&merged, options).map_err(|e| Failure::invalid(format!("jp: {}", describe(&e))))
+}
+
+// Name both places a conflicting key is written: in file `i`, and in the
+// latest file before it that has the key
+fn merge_conflict_failure(
+    pointer: &str,
+    i: usize,
+    inputs: &[String],
+    options: &Options,
+) -> Failure {
+    let at = |input: &str| match key_position(input, pointer, options) {
+        Some((line, column)) => format!(" at line {} column {}", line, column),
+        None => String::new(),
+    };
+    let earlier = options.files[..i]
+        .iter()
+        .zip(inputs)
+        .rfind(|(_, input)| key_position(input, pointer, options).is_some());
+    let first = match earlier {
+        Some((other, input)) => format!("{}{}", other, at(input)),
+        None => "an earlier file".to_string(),
+    };
+    Failure::invalid(format!(
+        "jp: {}: key {}{} conflicts with {}",
+        options.files[i],
+        pointer,
+        at(&inputs[i]),
+        first
+    ))
+}
+
+// Where the last key of a pointer through objects is written in the source
+fn key_position(input: &str, pointer: &str, options: &Options) -> Option<(usize, usize)> {
+    let tokens = tokenize_with(input, &options.parse).ok()?;
+    let segments = pointer
+        .split('/')
+        .skip(1)
+        .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+        .collect::<Vec<_>>();
+    if segments.is_empty() {
+        return None;
+    }
+    // Containers open around the current token, and keys matched so far
+    let (mut depth, mut matched) = (0, 0);
+    for (i, token) in tokens.iter().enumerate() {
+        match token.token_type {
+            TokenType::LeftBrace | TokenType::LeftBracket => depth += 1,
+            TokenType::RightBrace | TokenType::RightBracket => {
+                depth -= 1;
+                // Left the object the next key should be in
+                if depth <= matched {
+                    return None;
+                }
+            }
+            TokenType::String
+                if depth == matched + 1
+                    && tokens
+                        .get(i + 1)
+                        .is_some_and(|next| next.token_type == TokenType::Colon)
+                    && token.value.as_ref().and_then(JsonValue::as_str)
+                        == Some(&segments[matched]) =>
+            {
+                if matched + 1 == segments.len() {
+                    return Some((token.line, token.column));
+                }
+                // Only go deeper when this key's own value is an object;
+                // otherwise a later sibling may still hold the key
+                if tokens
+                    .get(i + 2)
+                    .is_some_and(|value| value.token_type == TokenType::LeftBrace)
+                {
+                    matched += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn main() {
+    let mut options = parse_args(std::env::args().skip(1));
+
+    if options.ndjson && options.pointer.is_some() {
+        eprintln!("jp: --pointer cannot be combined with --ndjson");
+        std::process::exit(EXIT_USAGE);
+    }
+    if !options.picks.is_empty()
+        && (options.pointer.is_some()
+            || options.path.is_some()
+            || options.only.is_some()
+            || options.ndjson
+            || options.write
+            || options.check)
+    {
+        eprintln!(
+            "jp: --pick cannot be combined with --pointer, --path, --only, --ndjson, --write or --check"
+        );
+        std::process::exit(EXIT_USAGE);
+    }
+    if options.multi
+        && (options.ndjson
+            || options.pointer.is_some()
+            || options.path.is_some()
+            || options.only.is_some()
+            || !options.picks.is_empty()
+            || edits_value(&options)
+            || transforms_value(&options)
+            || options.parse.preserve_comments
+            || options.all_errors
+            || options.stats)
+    {
+        eprintln!(
+            "jp: --multi cannot be combined with --ndjson, --all-errors, --stats, --preserve-comments, or options that pick or change a value, such as --pointer, --set or --to"
+        );
+        std::process::exit(EXIT_USAGE);
+    }
+    if options.print_type
+        && (options.count
+            || options.validate
+            || options.all_errors
+            || options.write
+            || options.check
+            || options.ndjson
+            || options.multi)
+    {
+        eprintln!(
+            "jp: --type cannot be combined with --count, --validate, --all-errors, --write, --check, --ndjson or --multi"
+        );
+        std::process::exit(EXIT_USAGE);
+    }
+    if options.pointer_exists.is_some()
+        && (options.pointer.is_some()
+            || options.path.is_some()
+            || options.count
+            || options.print_type
+            || options.validate
+            || options.all_errors
+            || options.write
+            || options.check
+            || options.diff
+            || options.merge
+            || options.ndjson
+            || options.multi)
+    {
+        eprintln!(
+            "jp: --pointer-exists cannot be combined with --pointer, --path, --count, --type, --validate, --all-errors, --write, --check, --diff, --merge, --ndjson or --multi"
+        );
+        std::process::exit(EXIT_USAGE);
+    }
+    if options.verbose && options.pointer_exists.is_none() {
+        eprintln!("jp: --verbose requires --pointer-exists");
+        std::process::exit(EXIT_USAGE);
+    }
+    if options.lenient && options.picks.is_empty() {
+        eprintln!("jp: --lenient requires --pick");
+        std::process::exit(EXIT_USAGE);
+    }
+    if options.fail_on_conflict && !options.merge {
+        eprintln!("jp: --fail-on-conflict requires --merge or --merge-arrays");
+        std::process::exit(EXIT_USAGE);
+    }
+    if options.preserve_order && (options.format.sort_keys != KeyOrder::Input || options.canonical)
+    {
+        eprintln!(
+            "jp: --preserve-order cannot be combined with --sort-keys, --sort-keys-by or --canonical"
+        );
+        std::process::exit(EXIT_USAGE);
+    }
+    if options.dry_run && !options.write {
+        eprintln!("jp: --dry-run requires --write");
+        std::process::exit(EXIT_USAGE);
+    }
+    if options.path.is_some()
+        && (options.pointer.is_some()
+            || options.ndjson
+            || options.write
+            || options.check
+            || transforms_value(&options))
+    {
+        eprintln!(
+            "jp: --path cannot be combined with --pointer, --ndjson, --write, --check, --to, --flatten or --unflatten"
+        );
+        std::process::exit(EXIT_USAGE);
+    }
+    if options.only.is_some()
+        && (options.path.is_some()
+            || options.ndjson
+            || options.write
+            || options.check
+            || transforms_value(&options))
+    {
+        eprintln!(
+            "jp: --only cannot be combined with --path, --ndjson, --write, --check, --to, --flatten or --unflatten"
+        );
+        std::process::exit(EXIT_USAGE);
+    }
+    if edits_value(&options) && (options.path.is_some() || options.only.is_some() || options.ndjson)
+    {
+        eprintln!(
+            "jp: --set, --delete, --select-keys, --omit-keys and --drop-nulls cannot be combined with --path, --only or --ndjson"
+        );
+        std::process::exit(EXIT_USAGE);
+    }
+    if options.create
+        && !options
+            .edits
+            .iter()
+            .any(|edit| matches!(edit, Edit::Set(..)))
+    {
+        eprintln!("jp: --create requires --set");
+        std::process::exit(EXIT_USAGE);
+    }
+    if options.parse.preserve_comments && !options.parse.allow_comments {
+        eprintln!("jp: --preserve-comments requires --jsonc");
+        std::process::exit(EXIT_USAGE);
+    }
+    if options.parse.preserve_comments
+        && (options.format.needs_value()
+            || transforms_value(&options)
+            || edits_value(&options)
+            || !options.picks.is_empty()
+            || options.pointer.is_some()
+            || options.path.is_some()
+            || options.only.is_some())
+    {
+        eprintln!(
+            "jp: --preserve-comments cannot be combined with options that rebuild the document, such as --sort-keys, --pointer, --set or --to"
+        );
+        std::process::exit(EXIT_USAGE);
+    }
+    if options.with_paths && options.only.is_none() {
+        eprintln!("jp: --with-paths requires --only");
+        std::process::exit(EXIT_USAGE);
+    }
+    if options.canonical
+        && (options.to != Output::Json
+            || options.ndjson
+            || options.write
+            || options.check
+            || options.format.depth.is_some())
+    {
+        eprintln!(
+            "jp: --canonical cannot be combined with --to, --ndjson, --write, --check or --depth"
+        );
+        std::process::exit(EXIT_USAGE);
+    }
+    if options.format.numbers_as_strings && (options.canonical || options.to != Output::Json) {
+        eprintln!("jp: --numbers-as-strings cannot be combined with --canonical or --to");
+        std::process::exit(EXIT_USAGE);
+    }
+    if options.to != Output::Json && (options.ndjson || options.write || options.check) {
+        eprintln!("jp: --to cannot be combined with --ndjson, --write or --check");
+        std::process::exit(EXIT_USAGE);
+    }
+    if options.flatten && (options.ndjson || options.write || options.check) {
+        eprintln!("jp: --flatten cannot be combined with --ndjson, --write or --check");
+        std::process::exit(EXIT_USAGE);
+    }
+    if options.unflatten && (options.flatten || options.ndjson || options.write || options.check) {
+        eprintln!(
+            "jp: --unflatten cannot be combined with --flatten, --ndjson, --write or --check"
+        );
+        std::process::exit(EXIT_USAGE);
+    }
+
+    // Collapsed containers aren't JSON, so they can't be written back or converted
+    if options.format.depth.is_some()
+        && (options.write || options.check || options.to != Output::Json)
+    {
+        eprintln!("jp: --depth cannot be combined with --write, --check or --to");
+        std::process::exit(EXIT_USAGE);
+    }
+    if options.format.head.is_some()
+        && (options.write || options.check || options.to != Output::Json || options.canonical)
+    {
+        eprintln!("jp: --head cannot be combined with --write, --check, --to or --canonical");
+        std::process::exit(EXIT_USAGE);
+    }
+
+    if let Some(list) = &options.files_from {
+        let listed = list_files(list).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(e.code);
+        });
+        // When stdin holds the list it can't also hold a document
+        if list == "-"
+            && (listed.iter().any(|file| file == "-")
+                || options.files.iter().any(|file| file == "-"))
+        {
+            eprintln!("jp: --files-from - reads the list from stdin, so - can't also be a FILE");
+            std::process::exit(EXIT_USAGE);
+        }
+        // An empty list means there is nothing to do, not that stdin is the input
+        if listed.is_empty() && options.files.is_empty() {
+            std::process::exit(0);
+        }
+        options.files.extend(listed);
+    }
+    if options.recursive {
+        options.files = expand_files(&options.files, &options.extensions).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(e.code);
+        });
+    }
+
+    if options.diff {
+        diff_files(&options);
+    }
+
+    if options.write || options.check {
+        let flag = if options.write { "--write" } else { "--check" };
+        if options.files.is_empty() {
+            eprintln!("jp: {} requires at least one FILE", flag);
+            std::process::exit(EXIT_USAGE);
+        }
+
+        let mut status = 0;
+        let mut changed = 0;
+        for filename in &options.files {
+            let result = if options.dry_run {
+                rewrite_file(filename, &options).map(|formatted| {
+                    if formatted.is_some() {
+                        println!("{}", filename);
+                        changed += 1;
+                    }
+                })
+            } else if options.write {
+                write_in_place(filename, &options)
+            } else {
+                check_file(filename, &options).map(|formatted| {
+                    if !formatted {
+                        // List files that would be reformatted, like `gofmt -l`
+                        println!("{}", filename);
+                        status = status.max(EXIT_INVALID);
+                    }
+                })
+            };
+            if let Err(e) = result {
+                eprintln!("{}", e);
+                status = status.max(e.code);
+            }
+        }
+        if options.dry_run && !options.quiet {
+            let files = options.files.len();
+            eprintln!(
+                "jp: {} of {} file{} would be reformatted",
+                changed,
+                files,
+                if files == 1 { "" } else { "s" }
+            );
+        }
+        std::process::exit(status);
+    }
+
+    if options.count {
+        print_summaries(&options, count_summary);
+    }
+    if options.print_type {
+        print_summaries(&options, type_summary);
+    }
+    if let Some(pointer) = &options.pointer_exists {
+        pointer_exists(pointer, &options);
+    }
+
+    // Like --validate, but with every error rather than the first
+    if options.all_errors {
+        let mut status = 0;
+        if options.files.is_empty() {
+            let input = read_stdin(&options);
+            for e in all_errors(&input, &options) {
+                eprintln!(
+                    "{}",
+                    with_snippet(error_message(&e, &options), &e, &input, &options)
+                );
+                status = status.max(exit_code(&e));
+            }
+        }
+        for filename in &options.files {
+            match read_file(filename, &options) {
+                Ok(input) => {
+                    for e in all_errors(&input, &options) {
+                        eprintln!("{}", input_failure(filename, &e, &input, &options));
+                        status = status.max(exit_code(&e));
+                    }
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    status = status.max(e.code);
+                }
+            }
+        }
+        std::process::exit(status);
     }
 
-    if let Err(e) = parse(&buffer) {
-        eprintln!("Invalid JSON: {}", e);
-        std::process::exit(1);
+    // Only report errors; print nothing for valid input
+    if options.validate {
+        let mut status = 0;
+        if options.files.is_empty() && streams(&options) {
+            if let Err(e) = validate_reader(stdin_lock(), &options.parse) {
+                eprintln!("{}", error_message(&e, &options));
+                status = exit_code(&e);
+            }
+        } else if options.files.is_empty() {
+            let input = read_stdin(&options);
+            warn_precision(&input, None, &options);
+            match validate_input(&input, &options) {
+                Ok(()) if options.stats => print_stats(&input, &options, None),
+                Ok(()) => {}
+                Err(e) => {
+                    eprintln!(
+                        "{}",
+                        with_snippet(error_message(&e, &options), &e, &input, &options)
+                    );
+                    status = exit_code(&e);
+                }
+            }
+        }
+        let multiple = options.files.len() > 1;
+        for filename in &options.files {
+            if streams(&options) && filename != "-" && !is_url(filename) {
+                if let Err(e) = validate_file_stream(filename, &options) {
+                    eprintln!("{}", e);
+                    status = status.max(e.code);
+                }
+                continue;
+            }
+            let result = read_file(filename, &options).and_then(|input| {
+                warn_precision(&input, Some(filename), &options);
+                validate_input(&input, &options)
+                    .map_err(|e| input_failure(filename, &e, &input, &options))?;
+                if options.stats {
+                    print_stats(&input, &options, multiple.then_some(filename.as_str()));
+                }
+                Ok(())
+            });
+            if let Err(e) = result {
+                eprintln!("{}", e);
+                status = status.max(e.code);
+            }
+        }
+        std::process::exit(status);
+    }
+
+    options.format.color = match options.color.as_deref().unwrap_or("auto") {
+        "auto" => options.output.is_none() && io::stdout().is_terminal(),
+        "always" => true,
+        "never" => false,
+        other => {
+            eprintln!("jp: invalid --color value: {}", other);
+            std::process::exit(EXIT_USAGE);
+        }
+    };
+
+    let out: Box<dyn Write> = match &options.output {
+        Some(output) => Box::new(File::create(output).unwrap_or_else(|e| {
+            eprintln!("jp: {}: {}", output, e);
+            std::process::exit(EXIT_IO);
+        })),
+        None => Box::new(io::stdout()),
+    };
+    let mut out = BufWriter::new(out);
+    let write_error = |e: io::Error| {
+        let target = options.output.as_deref().unwrap_or("stdout");
+        eprintln!("Error writing to {}: {}", target, e);
+        std::process::exit(EXIT_IO);
+    };
+
+    if options.merge {
+        let json = merge_files(&options).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(e.code);
+        });
+        write!(out, "{}{}", json, terminator(&options)).unwrap_or_else(write_error);
+        out.flush().unwrap_or_else(write_error);
+        return;
+    }
+
+    if options.files.is_empty() {
+        let buffer = read_stdin(&options);
+        warn_precision(&buffer, None, &options);
+        match write_input(&mut out, &buffer, &options) {
+            Ok(()) => {}
+            Err(Error::Io(e)) => write_error(e),
+            Err(e) => {
+                eprintln!(
+                    "{}",
+                    with_snippet(error_message(&e, &options), &e, &buffer, &options)
+                );
+                std::process::exit(exit_code(&e));
+            }
+        }
+        out.flush().unwrap_or_else(write_error);
+        if options.stats {
+            print_stats(&buffer, &options, None);
+        }
+        return;
+    }
+
+    // Format each file in turn, reporting failures and carrying on
+    let multiple = options.files.len() > 1;
+    let mut status = 0;
+    let mut printed = false;
+    for filename in &options.files {
+        let buffer = match read_file(filename, &options) {
+            Ok(buffer) => buffer,
+            Err(e) => {
+                eprintln!("{}", e);
+                status = status.max(e.code);
+                continue;
+            }
+        };
+        warn_precision(&buffer, Some(filename), &options);
+
+        if multiple {
+            // Format fully before writing, so a failing file leaves no dangling header
+            let json = match format_input(&buffer, &options) {
+                Ok(json) => json,
+                Err(e) => {
+                    eprintln!("{}", input_failure(filename, &e, &buffer, &options));
+                    status = status.max(exit_code(&e));
+                    continue;
+                }
+            };
+            if !options.quiet {
+                let eol = eol(&options);
+                // Skipped files leave no gap, so only separate from earlier output
+                let separator = if printed { eol } else { "" };
+                write!(out, "{}==> {} <=={}", separator, filename, eol).unwrap_or_else(write_error);
+            }
+            write!(out, "{}{}", json, terminator(&options)).unwrap_or_else(write_error);
+            printed = true;
+        } else {
+            match write_input(&mut out, &buffer, &options) {
+                Ok(()) => {}
+                Err(Error::Io(e)) => write_error(e),
+                Err(e) => {
+                    eprintln!(
+                        "{}",
+                        with_snippet(error_message(&e, &options), &e, &buffer, &options)
+                    );
+                    status = status.max(exit_code(&e));
+                    continue;
+                }
+            }
+        }
+        if options.stats {
+            // Keep the document ahead of the stats written to stderr
+            out.flush().unwrap_or_else(write_error);
+            print_stats(&buffer, &options, multiple.then_some(filename.as_str()));
+        }
+    }
+
+    out.flush().unwrap_or_else(write_error);
+    std::process::exit(status);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_position_follows_the_matched_key() {
+        let options = Options::default();
+        let input = "{\"b\": 1,\n \"x\": {\"c\": 3},\n \"b\": {\"c\": 4}}";
+        assert_eq!(key_position(input, "/b/c", &options), Some((3, 8)));
+        assert_eq!(key_position(input, "/x/c", &options), Some((2, 8)));
+        assert_eq!(
+            key_position(r#"{"b":1,"x":{"c":3}}"#, "/b/c", &options),
+            None
+        );
+        assert_eq!(key_position(r#"{"b":1}"#, "/b", &options), Some((1, 2)));
+    }
+    #[test]
+    fn trailing_newline_follows_eol() {
+        let mut options = Options::default();
+        assert_eq!(terminator(&options), "\n");
+        options.format.eol = Newline::Crlf;
+        assert_eq!(terminator(&options), "\r\n");
+        options.format.trailing_newline = false;
+        assert_eq!(terminator(&options), "");
+    }
+    #[test]
+    fn write_input_without_trailing_newline() {
+        let mut options = Options::default();
+        let mut out = Vec::new();
+        write_input(&mut out, "[1]", &options).unwrap();
+        assert_eq!(out, b"[\n    1\n]\n");
+
+        options.format.trailing_newline = false;
+        out.clear();
+        write_input(&mut out, "[1]", &options).unwrap();
+        assert_eq!(out, b"[\n    1\n]");
+    }
+    #[test]
+    fn recursive_expansion_filters_extensions() {
+        let root = std::env::temp_dir().join(format!("jp-recursive-{}", std::process::id()));
+        fs::create_dir_all(root.join("nested/deeper")).unwrap();
+        for name in [
+            "b.json",
+            "a.jsonc",
+            "notes.txt",
+            "nested/c.json",
+            "nested/deeper/d.JSON",
+        ] {
+            fs::write(root.join(name), "{}").unwrap();
+        }
+        let dir = root.to_string_lossy().into_owned();
+        let args = [dir.clone()];
+        let relative = |files: Vec<String>| {
+            files
+                .iter()
+                .map(|file| file.strip_prefix(&dir).unwrap().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let files = expand_files(&args, &["json".to_string()]).unwrap();
+        assert_eq!(relative(files), ["/b.json", "/nested/c.json"]);
+
+        let extensions = ["json".to_string(), "jsonc".to_string()];
+        let files = expand_files(&args, &extensions).unwrap();
+        assert_eq!(relative(files), ["/a.jsonc", "/b.json", "/nested/c.json"]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+    #[test]
+    fn args_after_double_dash_are_files() {
+        let args = ["--compact", "-", "a.json", "--", "--sort-keys", "-"];
+        let options = parse_args(args.map(String::from));
+        assert_eq!(options.format.style, Style::Compact);
+        assert_eq!(options.format.sort_keys, KeyOrder::Input);
+        assert_eq!(options.files, ["-", "a.json", "--sort-keys", "-"]);
+    }
+    #[test]
+    fn count_tokens_and_bytes() {
+        let mut options = Options::default();
+        let input = r#"{"a": [1, true]}"#;
+        assert_eq!(
+            count_summary(input, &options).unwrap(),
+            "9 tokens, 16 bytes"
+        );
+        options.quiet = true;
+        assert_eq!(count_summary(input, &options).unwrap(), "9 16");
+        assert!(count_summary("[1, ~]", &options).is_err());
+    }
+    #[test]
+    fn snippet_marks_the_column() {
+        let input = "{\r\n\t\"é\": 1 2\r\n}";
+        assert_eq!(
+            snippet(input, 2, 9),
+            "  |\n2 | \t\"é\": 1 2\n  | \t       ^"
+        );
+        // A long line is cut down to the part around the column
+        let input = format!("[{}x]", "1,".repeat(50));
+        let expected = format!("  |\n1 | …{}x]\n  |  {}^", &input[61..101], " ".repeat(40));
+        assert_eq!(snippet(&input, 1, 102), expected);
+        let input = format!("{}x{}", "1".repeat(50), "2".repeat(50));
+        assert!(snippet(&input, 1, 51).ends_with(&format!(
+            "{}…\n  |  {}^",
+            "2".repeat(40),
+            " ".repeat(40)
+        )));
     }
 }
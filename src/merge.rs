@@ -0,0 +1,183 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::pointer::push_segment;
+use crate::types::{last_wins, JsonValue};
+
+// Deep-merge `from` into `into`: objects merge key by key, and anything else
+// in `from` replaces what was there, so later documents win on conflicts
+pub fn merge<'a>(into: &mut JsonValue<'a>, from: JsonValue<'a>) {
+    merge_with(into, from, false);
+}
+
+// As `merge`, but arrays found at the same path are concatenated rather than replaced
+pub fn merge_concat<'a>(into: &mut JsonValue<'a>, from: JsonValue<'a>) {
+    merge_with(into, from, true);
+}
+
+fn merge_with<'a>(into: &mut JsonValue<'a>, from: JsonValue<'a>, concat_arrays: bool) {
+    match (into, from) {
+        (JsonValue::Object(target), JsonValue::Object(source)) => {
+            // Where each key is in `target`, so merging is linear in both sizes.
+            // A repeated key maps to its last entry, on either side.
+            let mut indices = last_indices(target);
+            let last = last_indices(&source);
+            for (i, (key, value)) in source.into_iter().enumerate() {
+                if last[key.as_ref()] != i {
+                    continue;
+                }
+                match indices.get(key.as_ref()) {
+                    Some(&i) => merge_with(&mut target[i].1, value, concat_arrays),
+                    None => {
+                        indices.insert(key.clone(), target.len());
+                        target.push((key, value));
+                    }
+                }
+            }
+        }
+        (JsonValue::Array(target), JsonValue::Array(source)) if concat_arrays => {
+            target.extend(source);
+        }
+        (target, value) => *target = value,
+    }
+}
+
+// Where each key's last entry is in an object
+fn last_indices<'a>(object: &[(Cow<'a, str>, JsonValue<'a>)]) -> HashMap<Cow<'a, str>, usize> {
+    object
+        .iter()
+        .enumerate()
+        .map(|(i, (key, _))| (key.clone(), i))
+        .collect()
+}
+
+// The JSON Pointer of the first value that merging `from` into `into` would
+// replace with a different one, if any. Objects at the same path merge rather
+// than conflict, and so do arrays when they are concatenated.
+pub fn merge_conflict(into: &JsonValue, from: &JsonValue, concat_arrays: bool) -> Option<String> {
+    let mut path = String::new();
+    find_conflict(into, from, concat_arrays, &mut path).then_some(path)
+}
+
+fn find_conflict(
+    into: &JsonValue,
+    from: &JsonValue,
+    concat_arrays: bool,
+    path: &mut String,
+) -> bool {
+    match (into, from) {
+        (JsonValue::Object(target), JsonValue::Object(source)) => {
+            let (existing, incoming) = (last_wins(target), last_wins(source));
+            for (key, value) in source {
+                // Only the last of a repeated key is merged
+                if !std::ptr::eq(incoming[key.as_ref()], value) {
+                    continue;
+                }
+                if let Some(existing) = existing.get(key.as_ref()) {
+                    let len = push_segment(path, key);
+                    if find_conflict(existing, value, concat_arrays, path) {
+                        return true;
+                    }
+                    path.truncate(len);
+                }
+            }
+            false
+        }
+        (JsonValue::Array(_), JsonValue::Array(_)) if concat_arrays => false,
+        (target, value) => target != value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::options::{FormatOptions, Style};
+    use crate::parse_value;
+    use crate::serializer::serialize_pretty;
+
+    fn merged(base: &str, other: &str, concat_arrays: bool) -> String {
+        let mut value = parse_value(base).unwrap();
+        let other = parse_value(other).unwrap();
+        if concat_arrays {
+            merge_concat(&mut value, other);
+        } else {
+            merge(&mut value, other);
+        }
+        let options = FormatOptions {
+            style: Style::Spaced,
+            ..FormatOptions::default()
+        };
+        serialize_pretty(&value, &options)
+    }
+
+    #[test]
+    fn merge_nested_overrides() {
+        let base = r#"{"name": "app", "db": {"host": "localhost", "port": 5432}, "tags": [1, 2]}"#;
+        let other = r#"{"db": {"port": 6543, "ssl": true}, "tags": [3], "name": {"full": "App"}}"#;
+        assert_eq!(
+            merged(base, other, false),
+            r#"{"name": {"full": "App"}, "db": {"host": "localhost", "port": 6543, "ssl": true}, "tags": [3]}"#
+        );
+    }
+    #[test]
+    fn find_the_first_merge_conflict() {
+        let conflict = |base: &str, other: &str, concat_arrays: bool| {
+            let (base, other) = (parse_value(base).unwrap(), parse_value(other).unwrap());
+            merge_conflict(&base, &other, concat_arrays)
+        };
+        let base = r#"{"db": {"host": "localhost", "a/b": 1}, "tags": [1], "name": "app"}"#;
+        assert_eq!(
+            conflict(base, r#"{"db": {"port": 1}, "name": "app"}"#, false),
+            None
+        );
+        assert_eq!(
+            conflict(base, r#"{"db": {"a/b": 2}, "name": "other"}"#, false).as_deref(),
+            Some("/db/a~1b")
+        );
+        assert_eq!(
+            conflict(base, r#"{"tags": [2]}"#, false).as_deref(),
+            Some("/tags")
+        );
+        assert_eq!(conflict(base, r#"{"tags": [2]}"#, true), None);
+        assert_eq!(
+            conflict(base, r#"{"db": null}"#, true).as_deref(),
+            Some("/db")
+        );
+    }
+    #[test]
+    fn merge_repeated_keys_by_their_last_entry() {
+        // The parser keeps one entry per key, so build the repeats by hand
+        let repeated = |first: &'static str, last: &'static str| {
+            JsonValue::Object(vec![
+                ("a".into(), parse_value(first).unwrap()),
+                ("a".into(), parse_value(last).unwrap()),
+            ])
+        };
+        let mut value = repeated(r#"{"x": 1}"#, r#"{"y": 2}"#);
+        merge(&mut value, parse_value(r#"{"a": {"z": 3}}"#).unwrap());
+        assert_eq!(value, repeated(r#"{"x": 1}"#, r#"{"y": 2, "z": 3}"#));
+        let entries = value.as_object().unwrap();
+        assert_eq!(entries[0].1, parse_value(r#"{"x": 1}"#).unwrap());
+
+        let mut value = parse_value("{}").unwrap();
+        merge(&mut value, repeated(r#"{"x": 1}"#, r#"{"y": 2}"#));
+        assert_eq!(value, parse_value(r#"{"a": {"y": 2}}"#).unwrap());
+
+        let single = parse_value(r#"{"a": 2}"#).unwrap();
+        assert_eq!(merge_conflict(&repeated("1", "2"), &single, false), None);
+        assert_eq!(merge_conflict(&single, &repeated("1", "2"), false), None);
+        assert_eq!(
+            merge_conflict(&repeated("2", "1"), &single, false).as_deref(),
+            Some("/a")
+        );
+    }
+    #[test]
+    fn merge_concatenates_arrays() {
+        let base = r#"{"tags": [1, 2], "nested": {"list": ["a"]}}"#;
+        let other = r#"{"tags": [3], "nested": {"list": ["b"]}}"#;
+        assert_eq!(
+            merged(base, other, true),
+            r#"{"tags": [1, 2, 3], "nested": {"list": ["a", "b"]}}"#
+        );
+    }
+}
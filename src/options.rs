@@ -0,0 +1,244 @@
+use std::cmp::Reverse;
+
+// Lenient extensions to strict JSON, all off by default
+#[derive(Clone, Debug, Default)]
+pub struct ParseOptions {
+    // Skip `//` line and `/* */` block comments (JSONC)
+    pub allow_comments: bool,
+    // With `allow_comments`, keep comments as tokens so the token formatter
+    // can write them back out; parsing to a value still drops them
+    pub preserve_comments: bool,
+    // Accept a comma after the last array element or object entry
+    pub allow_trailing_commas: bool,
+    // Reject integers that don't fit `i64` instead of keeping their exact text
+    pub strict_integers: bool,
+    // Reject integers that an `f64` can't represent exactly
+    pub strict_precision: bool,
+}
+
+// Layout of formatted output
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Style {
+    // One value per line, indented by nesting level
+    #[default]
+    Pretty,
+    // Everything on one line with a space after each comma and colon
+    Spaced,
+    // Everything on one line with no optional whitespace at all
+    Compact,
+}
+
+// How one object or array is laid out, as chosen by a `format_value_with`
+// callback
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Layout {
+    // Whatever the format options would choose
+    #[default]
+    Auto,
+    // On one line in spaced style
+    Inline,
+    // One item per line, even when it would fit the line width
+    Expanded,
+}
+
+// Order of object keys in output. Keys compare by Unicode code point, so the
+// order never depends on the locale.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum KeyOrder {
+    // As they appear in the input
+    #[default]
+    Input,
+    Asc,
+    Desc,
+    // Shortest first, by number of characters, then ascending
+    Length,
+}
+
+impl KeyOrder {
+    pub(crate) fn sort<T>(self, entries: &mut [(&str, T)]) {
+        match self {
+            Self::Input => {}
+            Self::Asc => entries.sort_by_key(|(key, _)| *key),
+            Self::Desc => entries.sort_by_key(|(key, _)| Reverse(*key)),
+            Self::Length => entries
+                .sort_by(|(a, _), (b, _)| a.chars().count().cmp(&b.chars().count()).then(a.cmp(b))),
+        }
+    }
+}
+
+// Palette for colored output
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Theme {
+    #[default]
+    Default,
+    // Bright colors for dark terminal backgrounds
+    Dark,
+    // Darker colors for light terminal backgrounds
+    Light,
+    // No color even when `color` is set
+    None,
+}
+
+// Line ending written between lines of output
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Newline {
+    #[default]
+    Lf,
+    Crlf,
+}
+
+impl Newline {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::Crlf => "\r\n",
+        }
+    }
+}
+
+// How parsed JSON is written back out
+#[derive(Clone, Debug)]
+pub struct FormatOptions {
+    pub style: Style,
+    pub eol: Newline,
+    // End the output of `format_value` with `eol`
+    pub trailing_newline: bool,
+    // Spaces per nesting level
+    pub indent: usize,
+    // Indent with one tab per nesting level instead of spaces
+    pub tabs: bool,
+    // Columns a tab takes up when measuring lines against `line_width`
+    pub tab_size: usize,
+    // The exact text of one level of indentation, overriding `indent` and
+    // `tabs`. Only JSON whitespace (space, tab, CR, LF) keeps the output valid
+    pub indent_string: Option<String>,
+    // Wrap keys and values in ANSI color codes
+    pub color: bool,
+    // Which codes `color` uses
+    pub theme: Theme,
+    // Wrap tokens in `<span class="json-…">` instead of ANSI codes; needs the
+    // value serializer
+    pub html: bool,
+    // Escape every non-ASCII character as `\uXXXX`
+    pub ascii: bool,
+    // Escape every `/` as `\/`, so `</script>` can't end an HTML script block
+    pub escape_slashes: bool,
+    // Write object keys in this order; anything but `Input` needs the value
+    // serializer
+    pub sort_keys: KeyOrder,
+    // Collapse containers nested this deep into a summary like `{…3 keys}`;
+    // needs the value serializer
+    pub depth: Option<usize>,
+    // Write only this many elements of a top-level array, then a summary like
+    // `…3 more items`; needs the value serializer
+    pub head: Option<usize>,
+    // Keep a container on one line when it fits within this many columns;
+    // pretty style only, and needs the value serializer
+    pub line_width: Option<usize>,
+    // Keep arrays of only scalars on one line while objects and arrays of
+    // containers still expand; pretty style only, and needs the value serializer
+    pub inline_scalar_arrays: bool,
+    // Write a string value as its unescaped contents, without quotes, when it is
+    // the whole output rather than part of a container; needs the value serializer
+    pub raw_strings: bool,
+    // Use the compact style for documents whose compact output is shorter than
+    // this many bytes, and `style` for the rest
+    pub compact_threshold: Option<usize>,
+    // Write a comma after the last element of every array and object with more
+    // than one element spread over several lines. The output is no longer valid
+    // JSON; pretty style only, and needs the value serializer
+    pub trailing_commas: bool,
+    // Write every number as a string holding its text from the input, for
+    // consumers that would round large integers; needs the value serializer
+    pub numbers_as_strings: bool,
+}
+
+impl FormatOptions {
+    // Whether output needs the parsed value rather than the raw token stream
+    pub fn needs_value(&self) -> bool {
+        self.sort_keys != KeyOrder::Input
+            || self.html
+            || self.depth.is_some()
+            || self.head.is_some()
+            || self.line_width.is_some()
+            || self.inline_scalar_arrays
+            || self.raw_strings
+            || self.trailing_commas
+            || self.numbers_as_strings
+    }
+}
+
+impl FormatOptions {
+    // The text repeated to indent, and how many of it make one level
+    pub(crate) fn indent_unit(&self) -> (&str, usize) {
+        if let Some(unit) = &self.indent_string {
+            (unit, 1)
+        } else if self.tabs {
+            ("\t", 1)
+        } else {
+            (" ", self.indent)
+        }
+    }
+    // Compact output without color or markup, for measuring against
+    // `compact_threshold`
+    pub(crate) fn measuring(&self) -> FormatOptions {
+        FormatOptions {
+            style: Style::Compact,
+            color: false,
+            html: false,
+            compact_threshold: None,
+            ..self.clone()
+        }
+    }
+    // The options to format with, given the measured compact length
+    pub(crate) fn settle_threshold(&self, compact_len: usize) -> FormatOptions {
+        let compact = self
+            .compact_threshold
+            .is_some_and(|threshold| compact_len < threshold);
+        FormatOptions {
+            style: if compact { Style::Compact } else { self.style },
+            compact_threshold: None,
+            ..self.clone()
+        }
+    }
+    // On-screen width of one level of indentation
+    pub(crate) fn indent_columns(&self) -> usize {
+        if let Some(unit) = &self.indent_string {
+            unit.chars()
+                .map(|c| if c == '\t' { self.tab_size } else { 1 })
+                .sum()
+        } else if self.tabs {
+            self.tab_size
+        } else {
+            self.indent
+        }
+    }
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            style: Style::default(),
+            eol: Newline::default(),
+            trailing_newline: true,
+            indent: 4,
+            tabs: false,
+            tab_size: 4,
+            indent_string: None,
+            color: false,
+            theme: Theme::Default,
+            html: false,
+            ascii: false,
+            escape_slashes: false,
+            sort_keys: KeyOrder::Input,
+            depth: None,
+            head: None,
+            line_width: None,
+            inline_scalar_arrays: false,
+            raw_strings: false,
+            compact_threshold: None,
+            trailing_commas: false,
+            numbers_as_strings: false,
+        }
+    }
+}
@@ -1,194 +1,551 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
 
 use crate::types::{Error, JsonValue, Token, TokenType};
 
+// Default limit on nested objects/arrays, guarding against stack overflow
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
 #[derive(Debug)]
-pub struct Parser<'a> {
-    tokens: &'a [Token],
+pub struct Parser<'t, 'a> {
+    tokens: &'t [Token<'a>],
     index: usize,
+    depth: usize,
+    max_depth: usize,
+    allow_trailing_commas: bool,
 }
 
-impl<'a> Parser<'a> {
-    pub fn new(tokens: &'a [Token]) -> Parser {
-        Parser { tokens, index: 0 }
+impl<'t, 'a> Parser<'t, 'a> {
+    pub fn new(tokens: &'t [Token<'a>]) -> Parser<'t, 'a> {
+        Parser::with_max_depth(tokens, DEFAULT_MAX_DEPTH)
+    }
+    pub fn with_max_depth(tokens: &'t [Token<'a>], max_depth: usize) -> Parser<'t, 'a> {
+        // A closing `Eof` token only marks where the tokens already end
+        let tokens = match tokens.split_last() {
+            Some((last, rest)) if last.token_type == TokenType::Eof => rest,
+            _ => tokens,
+        };
+        Parser {
+            tokens,
+            index: 0,
+            depth: 0,
+            max_depth,
+            allow_trailing_commas: false,
+        }
+    }
+    // Accept `[1,]` and `{"a":1,}` instead of rejecting them
+    pub fn allow_trailing_commas(mut self, allow: bool) -> Self {
+        self.allow_trailing_commas = allow;
+        self
+    }
+    // Index of the next token to parse, which starts at 0 and moves past each
+    // value as it is parsed
+    pub fn index(&self) -> usize {
+        self.index
+    }
+    pub fn parse(&mut self) -> Result<JsonValue<'a>, Error> {
+        // Any JSON value is a valid document (RFC 8259)
+        let value = self.parse_one()?;
+
+        // The top-level value must consume every token
+        if let Some(token) = self.tokens.get(self.index) {
+            return Err(expected("end of input", token));
+        }
+
+        Ok(value)
     }
-    pub fn parse(&mut self) -> Result<JsonValue, Error> {
+    // Parse top-level values one after another until the tokens run out, for
+    // documents written back to back like `{"a":1}{"b":2}[3]`. No tokens at all
+    // is no documents rather than an error.
+    pub fn parse_many(&mut self) -> Result<Vec<JsonValue<'a>>, Error> {
+        let mut values = Vec::new();
+        while self.index < self.tokens.len() {
+            values.push(self.parse_one()?);
+        }
+        Ok(values)
+    }
+    // Parse the value starting at `index` and stop after it, leaving any
+    // tokens that follow for the next call
+    pub fn parse_one(&mut self) -> Result<JsonValue<'a>, Error> {
+        if self.index >= self.tokens.len() {
+            return Err(Error::EmptyDocument);
+        }
+        self.parse_value()
+    }
+    // Check the whole token stream, reporting every error instead of stopping
+    // at the first. This walks the tokens with an explicit stack rather than
+    // building a value, and after each error carries on as if the offending
+    // token had been valid where possible, so one mistake isn't reported twice.
+    pub fn collect_errors(&mut self) -> Vec<Error> {
         if self.tokens.is_empty() {
-            return Err(Error::UnexpectedEndOfInput);
+            return vec![Error::EmptyDocument];
         }
 
-        let token = &self.tokens[self.index];
-        match token.token_type {
-            TokenType::LeftBrace => self.parse_object(),
-            TokenType::LeftBracket => self.parse_array(),
-            _ => Err(Error::UnexpectedToken(format!(
-                "Expected JSON object or array, got {}, line {}, col {}",
-                token.token_type, token.line, token.column
-            ))),
+        let mut errors = Vec::new();
+        let mut stack: Vec<&Token> = Vec::new(); // Open brackets and braces
+        let mut expect = Expect::Value;
+        let mut complete = false;
+        for token in &self.tokens[self.index..] {
+            if complete {
+                errors.push(expected("end of input", token));
+                break;
+            }
+            let in_object = stack
+                .last()
+                .is_some_and(|open| open.token_type == TokenType::LeftBrace);
+            let closes_top = stack
+                .last()
+                .is_some_and(|open| closes(open.token_type, token.token_type));
+            let scalar = matches!(
+                token.token_type,
+                TokenType::String | TokenType::Number | TokenType::Bool | TokenType::Null
+            );
+
+            let valid = match (expect, token.token_type) {
+                (
+                    Expect::Value | Expect::FirstValue,
+                    TokenType::LeftBrace | TokenType::LeftBracket,
+                ) => true,
+                (Expect::Value | Expect::FirstValue, _) if scalar => true,
+                (Expect::FirstValue, _) => closes_top && !in_object,
+                (Expect::Value, _) => closes_top && !in_object && self.allow_trailing_commas,
+                (Expect::FirstKey, TokenType::RightBrace) => true,
+                (Expect::Key, TokenType::RightBrace) => self.allow_trailing_commas,
+                (Expect::FirstKey | Expect::Key, TokenType::String) => true,
+                (Expect::Colon, TokenType::Colon) => true,
+                (Expect::CommaOrClose, TokenType::Comma) => true,
+                (Expect::CommaOrClose, _) => closes_top,
+                _ => false,
+            };
+            let closing = matches!(
+                token.token_type,
+                TokenType::RightBrace | TokenType::RightBracket
+            );
+            let may_close = matches!(
+                expect,
+                Expect::FirstValue | Expect::FirstKey | Expect::CommaOrClose
+            );
+            if let Some(open) = stack.last().filter(|_| !valid && closing && may_close) {
+                errors.push(mismatched(open, token));
+            } else if !valid {
+                let wanted = match expect {
+                    Expect::Value | Expect::FirstValue => VALUE,
+                    Expect::FirstKey | Expect::Key => KEY,
+                    Expect::Colon => COLON,
+                    Expect::CommaOrClose if in_object => "',' or '}'",
+                    Expect::CommaOrClose => "',' or ']'",
+                };
+                errors.push(expected(wanted, token));
+            }
+
+            // Move on as if the token were allowed here
+            match token.token_type {
+                TokenType::LeftBrace | TokenType::LeftBracket => {
+                    stack.push(token);
+                    if stack.len() > self.max_depth {
+                        errors.push(Error::MaxDepthExceeded(self.max_depth));
+                        return errors;
+                    }
+                    expect = if token.token_type == TokenType::LeftBrace {
+                        Expect::FirstKey
+                    } else {
+                        Expect::FirstValue
+                    };
+                }
+                TokenType::RightBrace | TokenType::RightBracket => {
+                    // Close the nearest matching container, or ignore a stray close
+                    if let Some(i) = stack
+                        .iter()
+                        .rposition(|open| closes(open.token_type, token.token_type))
+                    {
+                        stack.truncate(i);
+                        complete = stack.is_empty();
+                        expect = Expect::CommaOrClose;
+                    }
+                }
+                TokenType::Comma if !stack.is_empty() => {
+                    expect = if in_object {
+                        Expect::Key
+                    } else {
+                        Expect::Value
+                    };
+                }
+                TokenType::Colon => expect = Expect::Value,
+                TokenType::Comma => {}
+                _ if in_object && matches!(expect, Expect::FirstKey | Expect::Key) => {
+                    expect = Expect::Colon;
+                }
+                _ if in_object
+                    && expect == Expect::CommaOrClose
+                    && token.token_type == TokenType::String =>
+                {
+                    // Most likely a key after a missing comma
+                    expect = Expect::Colon;
+                }
+                _ => {
+                    complete = stack.is_empty();
+                    expect = Expect::CommaOrClose;
+                }
+            }
+        }
+
+        match stack.last().map(|open| open.token_type) {
+            Some(TokenType::LeftBrace) => errors.push(Error::UnexpectedEndOfObject),
+            Some(_) => errors.push(Error::UnexpectedEndOfArray),
+            None if !complete && errors.is_empty() => errors.push(Error::UnexpectedEndOfInput),
+            None => {}
+        }
+        errors
+    }
+    // Token at the current index, or `eof` if the stream ended early
+    fn token(&self, eof: Error) -> Result<&'t Token<'a>, Error> {
+        self.tokens.get(self.index).ok_or(eof)
+    }
+    // Track nesting depth around parsing a container
+    fn nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<JsonValue<'a>, Error>,
+    ) -> Result<JsonValue<'a>, Error> {
+        self.depth += 1;
+        if self.depth > self.max_depth {
+            return Err(Error::MaxDepthExceeded(self.max_depth));
         }
+        let value = parse(self);
+        self.depth -= 1;
+        value
     }
-    fn parse_array(&mut self) -> Result<JsonValue, Error> {
-        let mut array: Vec<JsonValue> = Vec::new();
+    fn parse_array(&mut self) -> Result<JsonValue<'a>, Error> {
+        let mut array: Vec<JsonValue<'a>> = Vec::new();
+        let open = &self.tokens[self.index];
         self.index += 1; // Move past TokenType::LeftBracket
 
         loop {
-            let token = &self.tokens[self.index];
+            let token = self.token(Error::UnexpectedEndOfArray)?;
             if token.token_type == TokenType::RightBracket {
                 self.index += 1;
                 return Ok(JsonValue::Array(array));
+            } else if array.is_empty() && token.token_type == TokenType::RightBrace {
+                return Err(mismatched(open, token));
             }
 
             // Parse array value (and increment self.index)
             let value = self.parse_value()?;
             array.push(value);
 
-            let token = &self.tokens[self.index];
+            let token = self.token(Error::UnexpectedEndOfArray)?;
             if token.token_type == TokenType::Comma {
                 self.index += 1; // Move past TokenType::Comma
 
                 // JSON doesn't allow trailing commas
-                if self.tokens[self.index].token_type == TokenType::RightBrace {
-                    return Err(Error::UnexpectedToken(format!(
-                        "Unexpected comma at line {}, column {}",
-                        token.line, token.column
-                    )));
+                let next = self.token(Error::UnexpectedEndOfArray)?;
+                if !self.allow_trailing_commas && next.token_type == TokenType::RightBracket {
+                    return Err(expected(VALUE, next));
                 }
             } else if token.token_type == TokenType::RightBracket {
                 self.index += 1; // Move past TokenType::RightBracket
                 return Ok(JsonValue::Array(array));
+            } else if token.token_type == TokenType::RightBrace {
+                return Err(mismatched(open, token));
             } else {
-                return Err(Error::UnexpectedToken(format!(
-                    "Unexpected token in array: {}, line {}, col {}",
-                    token.token_type, token.line, token.column
-                )));
+                return Err(expected("',' or ']'", token));
             }
         }
     }
-    fn parse_object(&mut self) -> Result<JsonValue, Error> {
-        let mut object: HashMap<String, JsonValue> = HashMap::new();
+    fn parse_object(&mut self) -> Result<JsonValue<'a>, Error> {
+        let mut object: Vec<(Cow<'a, str>, JsonValue<'a>)> = Vec::new();
+        // Where each key is in `object`, so a duplicate is found without a scan
+        let mut indices: HashMap<Cow<'a, str>, usize> = HashMap::new();
+        let open = &self.tokens[self.index];
         self.index += 1; // Move past TokenType::LeftBrace
 
         loop {
-            if self.tokens[self.index].token_type == TokenType::RightBrace {
+            let token = self.token(Error::UnexpectedEndOfObject)?;
+            if token.token_type == TokenType::RightBrace {
                 self.index += 1; // Move past TokenType::RightBrace
                 return Ok(JsonValue::Object(object));
+            } else if object.is_empty() && token.token_type == TokenType::RightBracket {
+                return Err(mismatched(open, token));
             }
 
             // Parse key
-            let key_token = &self.tokens[self.index];
-            let maybe_key = match key_token.token_type {
-                TokenType::String => key_token.value.clone(),
-                _ => {
-                    return Err(Error::UnexpectedToken(
-                        "Expected string as object key".to_string(),
-                    ))
-                }
-            };
-            let key = match maybe_key {
-                Some(JsonValue::String(str)) => str,
-                _ => {
-                    return Err(Error::UnexpectedToken(
-                        "Expected string as object key".to_string(),
-                    ))
-                }
+            // Keys are string tokens, decoded by the lexer exactly like values
+            let key_token = self.token(Error::UnexpectedEndOfObject)?;
+            let key = match &key_token.value {
+                Some(JsonValue::String(key)) => key.clone(),
+                _ => return Err(expected(KEY, key_token)),
             };
             self.index += 1; // Move past key
 
             // Check next token is a colon
-            if self.tokens[self.index].token_type != TokenType::Colon {
-                return Err(Error::UnexpectedToken(
-                    "Expected colon after object key".to_string(),
-                ));
+            let colon_token = self.token(Error::UnexpectedEndOfObject)?;
+            if colon_token.token_type != TokenType::Colon {
+                return Err(expected(COLON, colon_token));
             }
             self.index += 1; // Move past TokenType::Colon
 
             // Parse object value (and increment self.index)
+            self.token(Error::UnexpectedEndOfObject)?;
             let value = self.parse_value()?;
-            object.insert(key, value);
+            // Duplicate keys keep their first position but take the last value
+            match indices.get(&key) {
+                Some(&i) => object[i].1 = value,
+                None => {
+                    indices.insert(key.clone(), object.len());
+                    object.push((key, value));
+                }
+            }
 
-            let token = &self.tokens[self.index];
+            let token = self.token(Error::UnexpectedEndOfObject)?;
             if token.token_type == TokenType::Comma {
                 self.index += 1; // Move past TokenType::Comma
 
                 // JSON doesn't allow trailing commas
-                if self.tokens[self.index].token_type == TokenType::RightBrace {
-                    return Err(Error::UnexpectedToken(format!(
-                        "Unexpected comma at line {}, column {}",
-                        token.line, token.column
-                    )));
+                let next = self.token(Error::UnexpectedEndOfObject)?;
+                if !self.allow_trailing_commas && next.token_type == TokenType::RightBrace {
+                    return Err(expected(KEY, next));
                 }
             } else if token.token_type == TokenType::RightBrace {
                 self.index += 1; // Move past TokenType::RightBrace
                 return Ok(JsonValue::Object(object));
+            } else if token.token_type == TokenType::RightBracket {
+                return Err(mismatched(open, token));
             } else {
-                return Err(Error::UnexpectedToken(format!(
-                    "Unexpected token in object: {}, line {}, col {}",
-                    token.token_type, token.line, token.column
-                )));
+                return Err(expected("',' or '}'", token));
             }
         }
     }
-    fn parse_value(&mut self) -> Result<JsonValue, Error> {
-        let token = &self.tokens[self.index];
+    fn parse_value(&mut self) -> Result<JsonValue<'a>, Error> {
+        let token = self.token(Error::UnexpectedEndOfInput)?;
         match token.token_type {
-            TokenType::LeftBrace => self.parse_object(),
-            TokenType::LeftBracket => self.parse_array(),
+            TokenType::LeftBrace => self.nested(Self::parse_object),
+            TokenType::LeftBracket => self.nested(Self::parse_array),
             TokenType::String | TokenType::Number | TokenType::Bool | TokenType::Null => {
                 self.index += 1;
-                let value = token.value.clone().ok_or_else(|| {
-                    Error::UnexpectedToken(format!(
-                        "Unexpected {} at line {}, col {}",
-                        token.token_type, token.line, token.column
-                    ))
-                })?;
+                let value = token.value.clone().ok_or_else(|| expected(VALUE, token))?;
                 Ok(value)
             }
-            _ => Err(Error::UnexpectedToken(format!(
-                "Unexpected token {} at line {}, col {}",
-                token.token_type, token.line, token.column
-            ))),
+            _ => Err(expected(VALUE, token)),
         }
     }
 }
 
+// Check a stream of tokens without building a value, failing at the first
+// error. Memory is bounded by the nesting depth, so together with
+// `ReaderLexer` this validates documents of any size. Errors match `Parser`'s,
+// except that a syntax error is reported before a lexical error further on.
+pub fn validate_stream<'a>(
+    tokens: impl IntoIterator<Item = Result<Token<'a>, Error>>,
+    allow_trailing_commas: bool,
+    max_depth: usize,
+) -> Result<(), Error> {
+    let mut stack: Vec<Token> = Vec::new(); // Open brackets and braces
+    let mut expect = Expect::Value;
+    let mut complete = false;
+    for token in tokens {
+        let token = token?;
+        if token.token_type == TokenType::Eof {
+            break;
+        }
+        if complete {
+            return Err(expected("end of input", &token));
+        }
+        let in_object = stack
+            .last()
+            .is_some_and(|open| open.token_type == TokenType::LeftBrace);
+        let closes_top = stack
+            .last()
+            .is_some_and(|open| closes(open.token_type, token.token_type));
+        let closing = matches!(
+            token.token_type,
+            TokenType::RightBrace | TokenType::RightBracket
+        );
+
+        let after_value = match (expect, token.token_type) {
+            (Expect::Value | Expect::FirstValue, TokenType::LeftBrace) => {
+                stack.push(token);
+                expect = Expect::FirstKey;
+                false
+            }
+            (Expect::Value | Expect::FirstValue, TokenType::LeftBracket) => {
+                stack.push(token);
+                expect = Expect::FirstValue;
+                false
+            }
+            (
+                Expect::Value | Expect::FirstValue,
+                TokenType::String | TokenType::Number | TokenType::Bool | TokenType::Null,
+            ) => true,
+            (Expect::FirstValue, TokenType::RightBracket) => {
+                stack.pop();
+                true
+            }
+            (Expect::Value, TokenType::RightBracket)
+                if allow_trailing_commas && closes_top && !in_object =>
+            {
+                stack.pop();
+                true
+            }
+            (Expect::FirstValue, TokenType::RightBrace) => {
+                return Err(mismatched(&stack[stack.len() - 1], &token))
+            }
+            (Expect::Value | Expect::FirstValue, _) => return Err(expected(VALUE, &token)),
+            (Expect::FirstKey, TokenType::RightBrace) => {
+                stack.pop();
+                true
+            }
+            (Expect::Key, TokenType::RightBrace) if allow_trailing_commas => {
+                stack.pop();
+                true
+            }
+            (Expect::FirstKey | Expect::Key, TokenType::String) => {
+                expect = Expect::Colon;
+                false
+            }
+            (Expect::FirstKey, TokenType::RightBracket) => {
+                return Err(mismatched(&stack[stack.len() - 1], &token))
+            }
+            (Expect::FirstKey | Expect::Key, _) => return Err(expected(KEY, &token)),
+            (Expect::Colon, TokenType::Colon) => {
+                expect = Expect::Value;
+                false
+            }
+            (Expect::Colon, _) => return Err(expected(COLON, &token)),
+            (Expect::CommaOrClose, TokenType::Comma) => {
+                expect = if in_object {
+                    Expect::Key
+                } else {
+                    Expect::Value
+                };
+                false
+            }
+            (Expect::CommaOrClose, _) if closes_top => {
+                stack.pop();
+                true
+            }
+            (Expect::CommaOrClose, _) if closing && !stack.is_empty() => {
+                return Err(mismatched(&stack[stack.len() - 1], &token))
+            }
+            (Expect::CommaOrClose, _) if in_object => return Err(expected("',' or '}'", &token)),
+            (Expect::CommaOrClose, _) => return Err(expected("',' or ']'", &token)),
+        };
+        if stack.len() > max_depth {
+            return Err(Error::MaxDepthExceeded(max_depth));
+        }
+        if after_value {
+            complete = stack.is_empty();
+            expect = Expect::CommaOrClose;
+        }
+    }
+
+    match stack.last().map(|open| open.token_type) {
+        Some(TokenType::LeftBrace) => Err(Error::UnexpectedEndOfObject),
+        Some(_) => Err(Error::UnexpectedEndOfArray),
+        // Any token either completes the document or leaves a container open
+        None if !complete => Err(Error::EmptyDocument),
+        None => Ok(()),
+    }
+}
+
+// What `collect_errors` and `validate_stream` will accept next
+#[derive(Clone, Copy, PartialEq)]
+enum Expect {
+    Value,
+    // Just after `[`, where `]` may close an empty array
+    FirstValue,
+    Key,
+    // Just after `{`, where `}` may close an empty object
+    FirstKey,
+    Colon,
+    CommaOrClose,
+}
+
+fn closes(open: TokenType, close: TokenType) -> bool {
+    matches!(
+        (open, close),
+        (TokenType::LeftBrace, TokenType::RightBrace)
+            | (TokenType::LeftBracket, TokenType::RightBracket)
+    )
+}
+
+// A closing bracket or brace that doesn't match the innermost open container
+fn mismatched(open: &Token, found: &Token) -> Error {
+    let (close, container) = match open.token_type {
+        TokenType::LeftBrace => (TokenType::RightBrace, "object"),
+        _ => (TokenType::RightBracket, "array"),
+    };
+    Error::UnexpectedToken(
+        format!(
+            "Expected '{}' to close {} opened at line {} column {}, found '{}' at line {} column {}",
+            close, container, open.line, open.column, found.token_type, found.line, found.column
+        ),
+        (found.line, found.column),
+        found.start,
+    )
+}
+
+// What the grammar allows in each position, as named in diagnostics
+const VALUE: &str = "a value (object, array, string, number, boolean, or null)";
+const KEY: &str = "an object key (a string)";
+const COLON: &str = "':' after the object key";
+
+// Uniform diagnostic for a token that doesn't fit the grammar at this point
+fn expected(expected: &str, found: &Token) -> Error {
+    let description = match found.token_type {
+        TokenType::Null | TokenType::Bool | TokenType::Number | TokenType::String => {
+            found.token_type.to_string()
+        }
+        punctuation => format!("'{}'", punctuation),
+    };
+    Error::UnexpectedToken(
+        format!(
+            "Expected {}, found {} at line {} column {}",
+            expected, description, found.line, found.column
+        ),
+        (found.line, found.column),
+        found.start,
+    )
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::parse;
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::{parse, serialize_pretty, FormatOptions, ParseOptions, Style};
 
     #[test]
     fn empty_string_is_invalid() {
         let input = "";
-        let result = parse(input);
+        let result = parse(input, &ParseOptions::default(), &FormatOptions::default());
         assert!(result.is_err());
     }
     #[test]
     fn empty_object_is_valid() {
         let input = "{}";
-        let result = parse(input);
+        let result = parse(input, &ParseOptions::default(), &FormatOptions::default());
         assert!(result.is_ok());
     }
     #[test]
     fn empty_array_is_valid() {
         let input = "[]";
-        let result = parse(input);
+        let result = parse(input, &ParseOptions::default(), &FormatOptions::default());
         assert!(result.is_ok());
     }
     #[test]
     fn invalid_key() {
         let input = r#"{key: "value"}"#;
-        let result = parse(input);
+        let result = parse(input, &ParseOptions::default(), &FormatOptions::default());
         assert!(result.is_err());
     }
     #[test]
     fn invalid_boolean() {
         let input = r#"{"key": True}"#;
-        let result = parse(input);
+        let result = parse(input, &ParseOptions::default(), &FormatOptions::default());
         assert!(result.is_err());
     }
     #[test]
     fn invalid_null() {
         let input = r#"{"key": Null}"#;
-        let result = parse(input);
+        let result = parse(input, &ParseOptions::default(), &FormatOptions::default());
         assert!(result.is_err());
     }
     #[test]
@@ -207,7 +564,7 @@ mod tests {
                 "int": 42
             },
         }"#;
-        let result = parse(input);
+        let result = parse(input, &ParseOptions::default(), &FormatOptions::default());
         assert!(result.is_err());
     }
     #[test]
@@ -226,7 +583,359 @@ mod tests {
                 "int": 42
             }
         }"#;
-        let result = parse(input);
+        let result = parse(input, &ParseOptions::default(), &FormatOptions::default());
         assert!(result.is_ok());
     }
+    #[test]
+    fn object_preserves_key_order() {
+        let tokens = Lexer::from(r#"{"b": 1, "a": 2, "c": 3}"#).lex().unwrap();
+        let value = Parser::new(&tokens).parse().unwrap();
+        let keys = match value {
+            JsonValue::Object(object) => object.into_iter().map(|(k, _)| k).collect::<Vec<_>>(),
+            _ => panic!("expected object"),
+        };
+        assert_eq!(keys, vec!["b", "a", "c"]);
+    }
+    #[test]
+    fn escaped_keys_are_decoded_like_values() {
+        let input = r#"{"a\nb": "a\nb", "\u00e9\/": 1}"#;
+        let tokens = Lexer::from(input).lex().unwrap();
+        let value = Parser::new(&tokens).parse().unwrap();
+        let JsonValue::Object(object) = &value else {
+            panic!("expected an object");
+        };
+        assert_eq!(object[0].0, "a\nb");
+        assert_eq!(object[0].1, JsonValue::String("a\nb".into()));
+        assert_eq!(object[1].0, "\u{e9}/");
+
+        let options = FormatOptions {
+            style: Style::Compact,
+            ..FormatOptions::default()
+        };
+        let output = serialize_pretty(&value, &options);
+        assert_eq!(output, "{\"a\\nb\":\"a\\nb\",\"\u{e9}/\":1}");
+        let tokens = Lexer::from(output.as_str()).lex().unwrap();
+        assert_eq!(Parser::new(&tokens).parse().unwrap(), value);
+
+        let error = Lexer::from(r#"{"a\qb": 1}"#).lex().unwrap_err();
+        assert!(matches!(error, Error::InvalidEscape(..)), "{:?}", error);
+    }
+    #[test]
+    fn duplicate_key_keeps_position_and_last_value() {
+        let tokens = Lexer::from(r#"{"a": 1, "b": 2, "a": 3}"#).lex().unwrap();
+        let value = Parser::new(&tokens).parse().unwrap();
+        let expected = JsonValue::Object(vec![
+            ("a".into(), JsonValue::from(3.0)),
+            ("b".into(), JsonValue::from(2.0)),
+        ]);
+        assert_eq!(value, expected);
+        // Object equality ignores order, so check positions separately
+        let keys = value.as_object().unwrap().iter().map(|(k, _)| k.as_ref());
+        assert_eq!(keys.collect::<Vec<_>>(), ["a", "b"]);
+    }
+    #[test]
+    fn deeply_nested_input_is_invalid() {
+        let input = format!("{}{}", "[".repeat(10_000), "]".repeat(10_000));
+        let result = parse(&input, &ParseOptions::default(), &FormatOptions::default());
+        assert!(matches!(
+            result,
+            Err(Error::MaxDepthExceeded(DEFAULT_MAX_DEPTH))
+        ));
+    }
+    #[test]
+    fn max_depth_is_configurable() {
+        let tokens = Lexer::from("[[[]]]").lex().unwrap();
+        assert!(Parser::with_max_depth(&tokens, 3).parse().is_ok());
+        let result = Parser::with_max_depth(&tokens, 2).parse();
+        assert!(matches!(result, Err(Error::MaxDepthExceeded(2))));
+    }
+    #[test]
+    fn trailing_content_after_object_is_invalid() {
+        let tokens = Lexer::from(r#"{"a": 1} "garbage""#).lex().unwrap();
+        let result = Parser::new(&tokens).parse();
+        match result {
+            Err(Error::UnexpectedToken(msg, _, offset)) => {
+                assert_eq!(
+                    msg,
+                    "Expected end of input, found string at line 1 column 10"
+                );
+                assert_eq!(offset, 9);
+            }
+            other => panic!("expected trailing token error, got {:?}", other),
+        }
+    }
+    #[test]
+    fn trailing_content_after_array_is_invalid() {
+        let tokens = Lexer::from("[1,2][3,4]").lex().unwrap();
+        let result = Parser::new(&tokens).parse();
+        match result {
+            Err(Error::UnexpectedToken(msg, _, offset)) => {
+                assert_eq!(msg, "Expected end of input, found '[' at line 1 column 6");
+                assert_eq!(offset, 5);
+            }
+            other => panic!("expected trailing token error, got {:?}", other),
+        }
+    }
+    #[test]
+    fn unterminated_containers_are_invalid() {
+        let cases = [
+            ("{", "Unexpected end-of-object brace"),
+            ("[", "Unexpected end-of-array bracket"),
+            (r#"{"a":"#, "Unexpected end-of-object brace"),
+            (r#"{"a""#, "Unexpected end-of-object brace"),
+            (r#"{"a":1"#, "Unexpected end-of-object brace"),
+            ("[1,", "Unexpected end-of-array bracket"),
+            ("[[1]", "Unexpected end-of-array bracket"),
+        ];
+        for (input, expected) in cases {
+            let result = parse(input, &ParseOptions::default(), &FormatOptions::default());
+            assert_eq!(result.unwrap_err().to_string(), expected, "{}", input);
+        }
+    }
+    #[test]
+    fn lone_opener_is_an_error() {
+        // The first read after the opener runs past the end of the tokens
+        let cases = [
+            ("[", "Unexpected end-of-array bracket"),
+            ("{", "Unexpected end-of-object brace"),
+        ];
+        for (input, expected) in cases {
+            let tokens = Lexer::from(input).lex().unwrap();
+            assert_eq!(tokens.len(), 1);
+            let error = Parser::new(&tokens).parse().unwrap_err();
+            assert_eq!(error.to_string(), expected);
+            let errors = Parser::new(&tokens).collect_errors();
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].to_string(), expected);
+            let error = validate_stream(tokens.into_iter().map(Ok), false, 1).unwrap_err();
+            assert_eq!(error.to_string(), expected);
+        }
+    }
+    #[test]
+    fn top_level_scalars_are_valid() {
+        let cases = [
+            ("42", JsonValue::from(42.0)),
+            (r#""hello""#, JsonValue::String("hello".into())),
+            ("true", JsonValue::Bool(true)),
+            ("false", JsonValue::Bool(false)),
+            ("null", JsonValue::Null),
+        ];
+        for (input, expected) in cases {
+            let tokens = Lexer::from(input).lex().unwrap();
+            assert_eq!(Parser::new(&tokens).parse().unwrap(), expected);
+        }
+    }
+    #[test]
+    fn top_level_scalar_with_trailing_token_is_invalid() {
+        let tokens = Lexer::from("42 true").lex().unwrap();
+        assert!(Parser::new(&tokens).parse().is_err());
+    }
+    #[test]
+    fn whitespace_only_is_an_empty_document() {
+        let tokens = Lexer::from("   \n\t ").lex().unwrap();
+        let error = Parser::new(&tokens).parse().unwrap_err();
+        assert!(matches!(error, Error::EmptyDocument));
+        assert_eq!(error.to_string(), "Empty document: expected a JSON value");
+        assert!(matches!(
+            Parser::new(&[]).collect_errors()[..],
+            [Error::EmptyDocument]
+        ));
+    }
+    #[test]
+    fn trailing_commas_allowed_when_enabled() {
+        let cases = [
+            r#"{"a": 1,}"#,
+            r#"{"a": {"b": 2,},}"#,
+            "[1, 2,]",
+            "[[1,], {},]",
+        ];
+        for input in cases {
+            let tokens = Lexer::from(input).lex().unwrap();
+            let result = Parser::new(&tokens).allow_trailing_commas(true).parse();
+            assert!(result.is_ok(), "{}", input);
+        }
+    }
+    #[test]
+    fn trailing_commas_rejected_by_default() {
+        let cases = [
+            r#"{"a": 1,}"#,
+            r#"{"a": {"b": 2,},}"#,
+            "[1, 2,]",
+            "[[1,], {},]",
+        ];
+        for input in cases {
+            let tokens = Lexer::from(input).lex().unwrap();
+            assert!(Parser::new(&tokens).parse().is_err(), "{}", input);
+        }
+    }
+    #[test]
+    fn lone_comma_is_invalid_with_trailing_commas() {
+        for input in ["[,]", "{,}"] {
+            let tokens = Lexer::from(input).lex().unwrap();
+            let result = Parser::new(&tokens).allow_trailing_commas(true).parse();
+            assert!(result.is_err(), "{}", input);
+        }
+    }
+    #[test]
+    fn trailing_comma_diagnostics() {
+        let cases = [
+            (
+                "[1,]",
+                "Expected a value (object, array, string, number, boolean, or null), found ']' at line 1 column 4 (byte 3)",
+            ),
+            (
+                "[[1, 2,]]",
+                "Expected a value (object, array, string, number, boolean, or null), found ']' at line 1 column 8 (byte 7)",
+            ),
+            (
+                r#"{"a":1,}"#,
+                "Expected an object key (a string), found '}' at line 1 column 8 (byte 7)",
+            ),
+            (
+                r#"{"a":{"b":2,}}"#,
+                "Expected an object key (a string), found '}' at line 1 column 13 (byte 12)",
+            ),
+        ];
+        for (input, expected) in cases {
+            let tokens = Lexer::from(input).lex().unwrap();
+            let error = Parser::new(&tokens).parse().unwrap_err();
+            assert_eq!(error.to_string(), expected, "{}", input);
+        }
+    }
+    #[test]
+    fn mismatched_close_after_comma_is_invalid() {
+        for input in ["[1,}", r#"{"a":1,]"#] {
+            let tokens = Lexer::from(input).lex().unwrap();
+            assert!(Parser::new(&tokens).parse().is_err(), "{}", input);
+            let result = Parser::new(&tokens).allow_trailing_commas(true).parse();
+            assert!(result.is_err(), "{}", input);
+        }
+    }
+    #[test]
+    fn mismatched_delimiters_name_the_open_container() {
+        let cases = [
+            (
+                r#"{"a":1]"#,
+                "Expected '}' to close object opened at line 1 column 1, found ']' at line 1 column 7 (byte 6)",
+            ),
+            (
+                "[\n  1,\n  2}",
+                "Expected ']' to close array opened at line 1 column 1, found '}' at line 3 column 4 (byte 10)",
+            ),
+            (
+                r#"{"a": [}"#,
+                "Expected ']' to close array opened at line 1 column 7, found '}' at line 1 column 8 (byte 7)",
+            ),
+            (
+                "[{]",
+                "Expected '}' to close object opened at line 1 column 2, found ']' at line 1 column 3 (byte 2)",
+            ),
+        ];
+        for (input, expected) in cases {
+            let tokens = Lexer::from(input).lex().unwrap();
+            let error = Parser::new(&tokens).parse().unwrap_err();
+            assert_eq!(error.to_string(), expected, "{}", input);
+            let errors = Parser::new(&tokens).collect_errors();
+            assert_eq!(errors[0].to_string(), expected, "{}", input);
+            let error =
+                validate_stream(tokens.into_iter().map(Ok), false, DEFAULT_MAX_DEPTH).unwrap_err();
+            assert_eq!(error.to_string(), expected, "{}", input);
+        }
+    }
+    #[test]
+    fn collect_errors_keeps_going() {
+        let cases: [(&str, &[&str]); 5] = [
+            (
+                r#"{"a" 1, "b": [1 2,]}"#,
+                &[
+                    "Expected ':' after the object key, found number at line 1 column 6 (byte 5)",
+                    "Expected ',' or ']', found number at line 1 column 17 (byte 16)",
+                    "Expected a value (object, array, string, number, boolean, or null), found ']' at line 1 column 19 (byte 18)",
+                ],
+            ),
+            (
+                r#"[{"a": }, {1: 2}] 3"#,
+                &[
+                    "Expected a value (object, array, string, number, boolean, or null), found '}' at line 1 column 8 (byte 7)",
+                    "Expected an object key (a string), found number at line 1 column 12 (byte 11)",
+                    "Expected end of input, found number at line 1 column 19 (byte 18)",
+                ],
+            ),
+            ("[1, {", &["Unexpected end-of-object brace"]),
+            (
+                "[1}",
+                &[
+                    "Expected ']' to close array opened at line 1 column 1, found '}' at line 1 column 3 (byte 2)",
+                    "Unexpected end-of-array bracket",
+                ],
+            ),
+            (r#"{"a": [true, {"b": null}]}"#, &[]),
+        ];
+        for (input, expected) in cases {
+            let tokens = Lexer::from(input).lex().unwrap();
+            let errors = Parser::new(&tokens).collect_errors();
+            let errors = errors.iter().map(|e| e.to_string()).collect::<Vec<_>>();
+            assert_eq!(errors, expected, "{}", input);
+        }
+    }
+    #[test]
+    fn parse_one_stops_after_a_value() {
+        let tokens = Lexer::from(r#"{"a": [1]} "b""#).lex().unwrap();
+        let mut parser = Parser::new(&tokens);
+        assert_eq!(parser.index(), 0);
+        assert_eq!(parser.parse_one().unwrap().to_string(), "[Object]");
+        assert_eq!(parser.index(), 7);
+        assert_eq!(tokens[parser.index()].token_type, TokenType::String);
+        assert_eq!(parser.parse_one().unwrap().as_str(), Some("b"));
+        assert_eq!(parser.index(), tokens.len());
+        assert!(matches!(parser.parse_one(), Err(Error::EmptyDocument)));
+    }
+    #[test]
+    fn unexpected_token_messages() {
+        let cases = [
+            (
+                "{1: 2}",
+                "Expected an object key (a string), found number at line 1 column 2 (byte 1)",
+            ),
+            (
+                r#"{"a" 1}"#,
+                "Expected ':' after the object key, found number at line 1 column 6 (byte 5)",
+            ),
+            (
+                r#"{"a": 1 "b": 2}"#,
+                "Expected ',' or '}', found string at line 1 column 9 (byte 8)",
+            ),
+            (
+                "[1 2]",
+                "Expected ',' or ']', found number at line 1 column 4 (byte 3)",
+            ),
+            (
+                "]",
+                "Expected a value (object, array, string, number, boolean, or null), found ']' at line 1 column 1 (byte 0)",
+            ),
+            (
+                r#"{"a": }"#,
+                "Expected a value (object, array, string, number, boolean, or null), found '}' at line 1 column 7 (byte 6)",
+            ),
+            (
+                "[1, :]",
+                "Expected a value (object, array, string, number, boolean, or null), found ':' at line 1 column 5 (byte 4)",
+            ),
+            (
+                "{\n  \"a\":\n}",
+                "Expected a value (object, array, string, number, boolean, or null), found '}' at line 3 column 1 (byte 9)",
+            ),
+            (
+                "null null",
+                "Expected end of input, found null at line 1 column 6 (byte 5)",
+            ),
+        ];
+        for (input, expected) in cases {
+            let tokens = Lexer::from(input).lex().unwrap();
+            let error = Parser::new(&tokens).parse().unwrap_err();
+            assert_eq!(error.to_string(), expected, "{}", input);
+            let error = validate_stream(Lexer::from(input), false, DEFAULT_MAX_DEPTH).unwrap_err();
+            assert_eq!(error.to_string(), expected, "{}", input);
+        }
+    }
 }
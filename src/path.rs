@@ -0,0 +1,285 @@
+use crate::pointer::push_segment;
+use crate::types::{Error, JsonValue, ValueType};
+
+// A practical subset of JSONPath: `$`, `.name`, `['name']`, `[0]`, `[*]`, `.*`
+// and `..` for recursive descent. Filters, slices and unions are rejected
+// rather than matching nothing.
+#[derive(Debug, PartialEq)]
+enum Selector {
+    Name(String),
+    Index(usize),
+    Wildcard,
+}
+
+#[derive(Debug, PartialEq)]
+enum Step {
+    Child(Selector),
+    // The selector applied to the value and everything below it
+    Descendant(Selector),
+}
+
+// Every value matched by `path`, in document order
+pub fn select_path<'a, 'v>(
+    value: &'a JsonValue<'v>,
+    path: &str,
+) -> Result<Vec<&'a JsonValue<'v>>, Error> {
+    let mut matches = vec![value];
+    for step in parse_path(path)? {
+        let mut next = Vec::new();
+        for value in matches {
+            match &step {
+                Step::Child(selector) => apply(selector, value, &mut next),
+                Step::Descendant(selector) => descend(selector, value, &mut next),
+            }
+        }
+        matches = next;
+    }
+    Ok(matches)
+}
+
+fn apply<'a, 'v>(selector: &Selector, value: &'a JsonValue<'v>, out: &mut Vec<&'a JsonValue<'v>>) {
+    match (selector, value) {
+        (Selector::Name(name), JsonValue::Object(object)) => out.extend(
+            object
+                .iter()
+                .filter(|(key, _)| key == name)
+                .map(|(_, value)| value),
+        ),
+        (Selector::Index(index), JsonValue::Array(array)) => out.extend(array.get(*index)),
+        (Selector::Wildcard, JsonValue::Object(object)) => {
+            out.extend(object.iter().map(|(_, value)| value))
+        }
+        (Selector::Wildcard, JsonValue::Array(array)) => out.extend(array),
+        _ => {}
+    }
+}
+
+fn descend<'a, 'v>(
+    selector: &Selector,
+    value: &'a JsonValue<'v>,
+    out: &mut Vec<&'a JsonValue<'v>>,
+) {
+    apply(selector, value, out);
+    match value {
+        JsonValue::Object(object) => {
+            for (_, value) in object {
+                descend(selector, value, out);
+            }
+        }
+        JsonValue::Array(array) => {
+            for value in array {
+                descend(selector, value, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+// Every value of the given type at any depth, with its JSON Pointer, in
+// document order. Matches inside a matching object or array are included.
+pub fn select_type<'a, 'v>(
+    value: &'a JsonValue<'v>,
+    value_type: ValueType,
+) -> Vec<(String, &'a JsonValue<'v>)> {
+    let mut matches = Vec::new();
+    walk_type(value, value_type, &mut String::new(), &mut matches);
+    matches
+}
+
+fn walk_type<'a, 'v>(
+    value: &'a JsonValue<'v>,
+    value_type: ValueType,
+    path: &mut String,
+    out: &mut Vec<(String, &'a JsonValue<'v>)>,
+) {
+    if value.value_type() == value_type {
+        out.push((path.clone(), value));
+    }
+    match value {
+        JsonValue::Object(object) => {
+            for (key, value) in object {
+                let len = push_segment(path, key);
+                walk_type(value, value_type, path, out);
+                path.truncate(len);
+            }
+        }
+        JsonValue::Array(array) => {
+            for (i, value) in array.iter().enumerate() {
+                let len = push_segment(path, &i.to_string());
+                walk_type(value, value_type, path, out);
+                path.truncate(len);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn parse_path(path: &str) -> Result<Vec<Step>, Error> {
+    let Some(mut rest) = path.strip_prefix('$') else {
+        return Err(Error::InvalidPath(format!(
+            "JSONPath must start with '$': {}",
+            path
+        )));
+    };
+    let unsupported = |at: &str| {
+        Error::InvalidPath(format!(
+            "Unsupported JSONPath syntax at '{}' in path {}",
+            at, path
+        ))
+    };
+
+    let mut steps = Vec::new();
+    while !rest.is_empty() {
+        let descendant = rest.starts_with("..");
+        let selector;
+        if let Some(after) = rest.strip_prefix("..").or_else(|| rest.strip_prefix('.')) {
+            if after.starts_with('[') && descendant {
+                (selector, rest) = parse_bracket(after).ok_or_else(|| unsupported(after))?;
+            } else if let Some(after) = after.strip_prefix('*') {
+                selector = Selector::Wildcard;
+                rest = after;
+            } else {
+                let end = after.find(['.', '[']).unwrap_or(after.len());
+                let name = &after[..end];
+                if name.is_empty()
+                    || !name
+                        .chars()
+                        .all(|c| c.is_alphanumeric() || "_-$".contains(c))
+                {
+                    return Err(unsupported(after));
+                }
+                selector = Selector::Name(name.to_string());
+                rest = &after[end..];
+            }
+        } else if rest.starts_with('[') {
+            (selector, rest) = parse_bracket(rest).ok_or_else(|| unsupported(rest))?;
+        } else {
+            return Err(unsupported(rest));
+        }
+        steps.push(if descendant {
+            Step::Descendant(selector)
+        } else {
+            Step::Child(selector)
+        });
+    }
+    Ok(steps)
+}
+
+// `[*]`, `[0]`, `['name']` or `["name"]`, and what follows the closing bracket
+fn parse_bracket(input: &str) -> Option<(Selector, &str)> {
+    let inner = input.strip_prefix('[')?;
+    if let Some(rest) = inner.strip_prefix("*]") {
+        return Some((Selector::Wildcard, rest));
+    }
+    if let Some(quote) = inner.chars().next().filter(|c| *c == '\'' || *c == '"') {
+        let mut name = String::new();
+        let mut chars = inner[1..].char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => name.push(chars.next()?.1),
+                c if c == quote => {
+                    let rest = inner[1 + i + 1..].strip_prefix(']')?;
+                    return Some((Selector::Name(name), rest));
+                }
+                c => name.push(c),
+            }
+        }
+        return None;
+    }
+    let end = inner.find(']')?;
+    let digits = &inner[..end];
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    Some((Selector::Index(digits.parse().ok()?), &inner[end + 1..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_value;
+
+    const DOCUMENT: &str = r#"{
+        "store": {
+            "book": [
+                {"title": "Sayings", "author": "Rees", "id": 1},
+                {"title": "Sword", "author": "Waugh", "id": 2}
+            ],
+            "bicycle": {"author": "Nobody", "color": "red"}
+        },
+        "items": [{"id": "a"}, {"id": "b"}, {"name": "c"}],
+        "odd key": true
+    }"#;
+
+    fn select(path: &str) -> Result<Vec<String>, Error> {
+        let value = parse_value(DOCUMENT).unwrap();
+        select_path(&value, path).map(|values| values.iter().map(|v| v.to_string()).collect())
+    }
+
+    #[test]
+    fn select_by_name_and_index() {
+        assert_eq!(select("$.store.book[0].title").unwrap(), [r#""Sayings""#]);
+        assert_eq!(select("$['odd key']").unwrap(), ["true"]);
+        assert_eq!(select(r#"$.store["book"][1].id"#).unwrap(), ["2"]);
+        assert_eq!(select("$").unwrap(), ["[Object]"]);
+        assert!(select("$.store.book[5]").unwrap().is_empty());
+    }
+    #[test]
+    fn select_with_wildcards() {
+        assert_eq!(select("$.items[*].id").unwrap(), [r#""a""#, r#""b""#]);
+        assert_eq!(
+            select("$.store.bicycle.*").unwrap(),
+            [r#""Nobody""#, r#""red""#]
+        );
+    }
+    #[test]
+    fn select_recursive_descent() {
+        assert_eq!(
+            select("$..author").unwrap(),
+            [r#""Rees""#, r#""Waugh""#, r#""Nobody""#]
+        );
+        assert_eq!(select("$..book[1].title").unwrap(), [r#""Sword""#]);
+        assert_eq!(select("$..[0].id").unwrap(), ["1", r#""a""#]);
+    }
+    #[test]
+    fn select_values_by_type() {
+        let value =
+            parse_value(r#"{"a": "x", "b": [1, {"c": "y", "d/e": "z"}], "f": null}"#).unwrap();
+        let strings = select_type(&value, ValueType::String)
+            .into_iter()
+            .map(|(path, value)| format!("{} {}", path, value))
+            .collect::<Vec<_>>();
+        assert_eq!(strings, [r#"/a "x""#, r#"/b/1/c "y""#, r#"/b/1/d~1e "z""#]);
+
+        let objects = select_type(&value, ValueType::Object);
+        let paths = objects
+            .iter()
+            .map(|(path, _)| path.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(paths, ["", "/b/1"]);
+        assert!(select_type(&value, ValueType::Bool).is_empty());
+        assert!(select_type(&JsonValue::Array(vec![]), ValueType::Null).is_empty());
+    }
+    #[test]
+    fn unsupported_path_syntax_is_an_error() {
+        let cases = [
+            ("store", "JSONPath must start with '$': store"),
+            (
+                "$.items[?(@.id)]",
+                "Unsupported JSONPath syntax at '[?(@.id)]' in path $.items[?(@.id)]",
+            ),
+            (
+                "$.items[0:2]",
+                "Unsupported JSONPath syntax at '[0:2]' in path $.items[0:2]",
+            ),
+            ("$.", "Unsupported JSONPath syntax at '' in path $."),
+            (
+                "$store",
+                "Unsupported JSONPath syntax at 'store' in path $store",
+            ),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(select(path).unwrap_err().to_string(), expected);
+        }
+    }
+}
@@ -0,0 +1,284 @@
+use std::borrow::Cow;
+
+use crate::types::{Error, JsonValue};
+
+// Resolve an RFC 6901 JSON Pointer such as `/foo/0/bar` against a value. Of
+// a repeated key the last entry is the one a pointer reaches.
+pub fn resolve_pointer<'a, 'v>(
+    value: &'a JsonValue<'v>,
+    pointer: &str,
+) -> Result<&'a JsonValue<'v>, Error> {
+    let mut target = value;
+    for segment in segments(pointer)? {
+        target = match target {
+            JsonValue::Object(object) => object
+                .iter()
+                .rfind(|(key, _)| *key == segment)
+                .map(|(_, value)| value)
+                .ok_or_else(|| no_such_key(&segment, pointer))?,
+            JsonValue::Array(array) => parse_index(&segment)
+                .and_then(|index| array.get(index))
+                .ok_or_else(|| invalid_index(&segment, pointer))?,
+            _ => return Err(scalar(&segment, pointer)),
+        };
+    }
+    Ok(target)
+}
+
+// Replace the value at a JSON Pointer. With `create`, keys missing along the
+// way are added to their objects, holding empty objects until the last one;
+// without it the pointer must already resolve.
+pub fn set_pointer<'v>(
+    value: &mut JsonValue<'v>,
+    pointer: &str,
+    new: JsonValue<'v>,
+    create: bool,
+) -> Result<(), Error> {
+    *walk_mut(value, segments(pointer)?, pointer, create)? = new;
+    Ok(())
+}
+
+// Remove the value at a JSON Pointer from its object or array and return it.
+// Later array elements shift down. The whole document can't be deleted.
+pub fn delete_pointer<'v>(
+    value: &mut JsonValue<'v>,
+    pointer: &str,
+) -> Result<JsonValue<'v>, Error> {
+    let mut segments = segments(pointer)?;
+    let Some(segment) = segments.pop() else {
+        return Err(Error::InvalidPointer(
+            "Cannot delete the whole document".to_string(),
+        ));
+    };
+    match walk_mut(value, segments, pointer, false)? {
+        object @ JsonValue::Object(_) => object
+            .remove(&segment)
+            .ok_or_else(|| no_such_key(&segment, pointer)),
+        array @ JsonValue::Array(_) => parse_index(&segment)
+            .and_then(|index| array.remove_index(index))
+            .ok_or_else(|| invalid_index(&segment, pointer)),
+        _ => Err(scalar(&segment, pointer)),
+    }
+}
+
+// Mutable counterpart of `resolve_pointer`, for editing a value in place.
+// Returns `None` for a malformed pointer or one that doesn't resolve.
+pub(crate) fn resolve_pointer_mut<'a, 'v>(
+    value: &'a mut JsonValue<'v>,
+    pointer: &str,
+) -> Option<&'a mut JsonValue<'v>> {
+    walk_mut(value, segments(pointer).ok()?, pointer, false).ok()
+}
+
+// Follow `segments` of `pointer`, which is only used in error messages
+fn walk_mut<'a, 'v>(
+    value: &'a mut JsonValue<'v>,
+    segments: Vec<String>,
+    pointer: &str,
+    create: bool,
+) -> Result<&'a mut JsonValue<'v>, Error> {
+    let mut target = value;
+    for segment in segments {
+        target = match target {
+            JsonValue::Object(object) => {
+                let index = match object.iter().rposition(|(key, _)| *key == segment) {
+                    Some(index) => index,
+                    None if create => {
+                        object.push((Cow::Owned(segment), JsonValue::Object(Vec::new())));
+                        object.len() - 1
+                    }
+                    None => return Err(no_such_key(&segment, pointer)),
+                };
+                &mut object[index].1
+            }
+            JsonValue::Array(array) => parse_index(&segment)
+                .and_then(|index| array.get_mut(index))
+                .ok_or_else(|| invalid_index(&segment, pointer))?,
+            _ => return Err(scalar(&segment, pointer)),
+        };
+    }
+    Ok(target)
+}
+
+// The unescaped reference tokens of a pointer; none for the empty pointer
+fn segments(pointer: &str) -> Result<Vec<String>, Error> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(path) = pointer.strip_prefix('/') else {
+        return Err(Error::InvalidPointer(format!(
+            "JSON Pointer must start with '/': {}",
+            pointer
+        )));
+    };
+    Ok(path.split('/').map(unescape).collect())
+}
+
+fn no_such_key(segment: &str, pointer: &str) -> Error {
+    Error::InvalidPointer(format!("No such key '{}' in pointer {}", segment, pointer))
+}
+
+fn invalid_index(segment: &str, pointer: &str) -> Error {
+    Error::InvalidPointer(format!(
+        "Invalid array index '{}' in pointer {}",
+        segment, pointer
+    ))
+}
+
+fn scalar(segment: &str, pointer: &str) -> Error {
+    Error::InvalidPointer(format!(
+        "Cannot index into scalar with '{}' in pointer {}",
+        segment, pointer
+    ))
+}
+
+// Append an escaped pointer segment, returning the length to truncate back to
+pub(crate) fn push_segment(path: &mut String, segment: &str) -> usize {
+    let len = path.len();
+    path.push('/');
+    path.push_str(&segment.replace('~', "~0").replace('/', "~1"));
+    len
+}
+
+// Decode `~1` to `/` and `~0` to `~`, in that order
+fn unescape(segment: &str) -> String {
+    segment.replace("~1", "/").replace("~0", "~")
+}
+
+// Array indices are decimal with no leading zeros
+pub(crate) fn parse_index(segment: &str) -> Option<usize> {
+    if segment.is_empty()
+        || !segment.bytes().all(|b| b.is_ascii_digit())
+        || (segment.len() > 1 && segment.starts_with('0'))
+    {
+        return None;
+    }
+    segment.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    const DOCUMENT: &str = r#"{
+        "foo": ["bar", {"baz": true}],
+        "a/b": 1,
+        "m~n": 2,
+        "": 3
+    }"#;
+
+    fn resolve(pointer: &str) -> Result<String, Error> {
+        let tokens = Lexer::from(DOCUMENT).lex().unwrap();
+        let value = Parser::new(&tokens).parse().unwrap();
+        resolve_pointer(&value, pointer).map(|value| value.to_string())
+    }
+
+    #[test]
+    fn resolve_nested_pointer() {
+        assert_eq!(resolve("").unwrap(), "[Object]");
+        assert_eq!(resolve("/foo").unwrap(), "[Array]");
+        assert_eq!(resolve("/foo/0").unwrap(), r#""bar""#);
+        assert_eq!(resolve("/foo/1/baz").unwrap(), "true");
+    }
+    #[test]
+    fn resolve_escaped_pointer() {
+        assert_eq!(resolve("/a~1b").unwrap(), "1");
+        assert_eq!(resolve("/m~0n").unwrap(), "2");
+        assert_eq!(resolve("/").unwrap(), "3");
+    }
+    #[test]
+    fn set_replaces_or_creates() {
+        let tokens = Lexer::from(DOCUMENT).lex().unwrap();
+        let mut value = Parser::new(&tokens).parse().unwrap();
+        set_pointer(&mut value, "/foo/1/baz", JsonValue::Null, false).unwrap();
+        assert_eq!(
+            resolve_pointer(&value, "/foo/1/baz").unwrap(),
+            &JsonValue::Null
+        );
+
+        let error = set_pointer(&mut value, "/x/y", JsonValue::Null, false).unwrap_err();
+        assert_eq!(error.to_string(), "No such key 'x' in pointer /x/y");
+        set_pointer(&mut value, "/x/y", JsonValue::Bool(true), true).unwrap();
+        assert_eq!(
+            resolve_pointer(&value, "/x/y").unwrap(),
+            &JsonValue::Bool(true)
+        );
+
+        // Only object keys are created; arrays and scalars still need the path
+        assert!(set_pointer(&mut value, "/foo/5", JsonValue::Null, true).is_err());
+        assert!(set_pointer(&mut value, "/a~1b/c", JsonValue::Null, true).is_err());
+    }
+    #[test]
+    fn delete_keys_and_elements() {
+        let tokens = Lexer::from(DOCUMENT).lex().unwrap();
+        let mut value = Parser::new(&tokens).parse().unwrap();
+        assert_eq!(
+            delete_pointer(&mut value, "/a~1b").unwrap().to_string(),
+            "1"
+        );
+        assert_eq!(
+            delete_pointer(&mut value, "/foo/0").unwrap().to_string(),
+            r#""bar""#
+        );
+        assert_eq!(
+            resolve_pointer(&value, "/foo/0/baz").unwrap(),
+            &JsonValue::Bool(true)
+        );
+        assert_eq!(value.as_object().unwrap().len(), 3);
+
+        let cases = [
+            ("/a~1b", "No such key 'a/b' in pointer /a~1b"),
+            ("/foo/1", "Invalid array index '1' in pointer /foo/1"),
+            (
+                "/m~0n/x",
+                "Cannot index into scalar with 'x' in pointer /m~0n/x",
+            ),
+            ("/x/y", "No such key 'x' in pointer /x/y"),
+            ("", "Cannot delete the whole document"),
+        ];
+        for (pointer, expected) in cases {
+            let error = delete_pointer(&mut value, pointer).unwrap_err();
+            assert_eq!(error.to_string(), expected);
+        }
+    }
+    #[test]
+    fn repeated_keys_resolve_to_the_last() {
+        // The parser keeps one entry per key, so build the repeats by hand
+        let object = |c: f64| JsonValue::Object(vec![("c".into(), JsonValue::from(c))]);
+        let mut value = JsonValue::Object(vec![
+            ("a".into(), JsonValue::from(1.0)),
+            ("b".into(), object(2.0)),
+            ("a".into(), JsonValue::from(3.0)),
+            ("b".into(), object(4.0)),
+        ]);
+        assert_eq!(resolve_pointer(&value, "/a").unwrap().to_string(), "3");
+        assert_eq!(resolve_pointer(&value, "/b/c").unwrap().to_string(), "4");
+        set_pointer(&mut value, "/b/c", JsonValue::Null, false).unwrap();
+        let entries = value.as_object().unwrap();
+        assert_eq!(entries[1].1, object(2.0));
+        assert_eq!(entries[3].1.get("c"), Some(&JsonValue::Null));
+        // Deleting a repeated key removes every entry, so no earlier one shows through
+        assert_eq!(delete_pointer(&mut value, "/a").unwrap().to_string(), "3");
+        assert!(resolve_pointer(&value, "/a").is_err());
+        assert_eq!(value.as_object().unwrap().len(), 2);
+    }
+    #[test]
+    fn unresolved_pointer_names_segment() {
+        let cases = [
+            ("/missing", "No such key 'missing' in pointer /missing"),
+            ("/foo/2", "Invalid array index '2' in pointer /foo/2"),
+            ("/foo/01", "Invalid array index '01' in pointer /foo/01"),
+            ("/foo/-", "Invalid array index '-' in pointer /foo/-"),
+            (
+                "/foo/0/x",
+                "Cannot index into scalar with 'x' in pointer /foo/0/x",
+            ),
+            ("foo", "JSON Pointer must start with '/': foo"),
+        ];
+        for (pointer, expected) in cases {
+            assert_eq!(resolve(pointer).unwrap_err().to_string(), expected);
+        }
+    }
+}
@@ -5,3 +5,11 @@ pub const JSON_LEFTBRACKET: char = '[';
 pub const JSON_RIGHTBRACKET: char = ']';
 pub const JSON_LEFTBRACE: char = '{';
 pub const JSON_RIGHTBRACE: char = '}';
+pub const JSON_SLASH: char = '/';
+pub const JSON_BOM: char = '\u{FEFF}';
+
+// The only whitespace JSON allows between tokens; form feed and vertical tab
+// are not among them
+pub fn is_json_whitespace(char: char) -> bool {
+    matches!(char, ' ' | '\t' | '\n' | '\r')
+}
@@ -0,0 +1,133 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+
+use crate::types::JsonValue;
+
+// Values map onto serde's data model as serde_json's own `Value` does. Numbers
+// written as integers that fit `i64` or `u64` stay integers, and the rest go
+// through their `f64`, so integers beyond 64 bits lose their exact digits.
+impl Serialize for JsonValue<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            JsonValue::Null => serializer.serialize_unit(),
+            JsonValue::Bool(bool) => serializer.serialize_bool(*bool),
+            JsonValue::Number(number) => {
+                if let Some(integer) = number.as_i64() {
+                    serializer.serialize_i64(integer)
+                } else if let Ok(integer) = number.raw().parse::<u64>() {
+                    serializer.serialize_u64(integer)
+                } else {
+                    serializer.serialize_f64(number.as_f64())
+                }
+            }
+            JsonValue::String(string) => serializer.serialize_str(string),
+            JsonValue::Array(array) => {
+                let mut seq = serializer.serialize_seq(Some(array.len()))?;
+                for item in array {
+                    seq.serialize_element(item)?;
+                }
+                seq.end()
+            }
+            JsonValue::Object(object) => {
+                let mut map = serializer.serialize_map(Some(object.len()))?;
+                for (key, value) in object {
+                    map.serialize_entry(key.as_ref(), value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+// Deserialized values own their strings, so any lifetime can be produced,
+// including `JsonValue<'static>` from a reader
+impl<'de> Deserialize<'de> for JsonValue<'_> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = JsonValue<'static>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("any JSON value")
+    }
+    fn visit_unit<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(JsonValue::Null)
+    }
+    fn visit_none<E: de::Error>(self) -> Result<Self::Value, E> {
+        Ok(JsonValue::Null)
+    }
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(self)
+    }
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<Self::Value, E> {
+        Ok(JsonValue::Bool(value))
+    }
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        Ok(value.into())
+    }
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        Ok(value.into())
+    }
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<Self::Value, E> {
+        Ok(value.into())
+    }
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(JsonValue::String(Cow::Owned(value.to_string())))
+    }
+    fn visit_string<E: de::Error>(self, value: String) -> Result<Self::Value, E> {
+        Ok(JsonValue::String(Cow::Owned(value)))
+    }
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut array = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(item) = seq.next_element()? {
+            array.push(item);
+        }
+        Ok(JsonValue::Array(array))
+    }
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut object = Vec::with_capacity(map.size_hint().unwrap_or(0));
+        while let Some((key, value)) = map.next_entry::<String, JsonValue>()? {
+            object.push((Cow::Owned(key), value));
+        }
+        Ok(JsonValue::Object(object))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_value;
+    use crate::types::JsonValue;
+
+    const DOCUMENT: &str = r#"{"name": "jp", "tags": ["a", "b"], "size": -3, "big": 18446744073709551615, "ratio": 0.25, "none": null, "ok": true, "nested": {"é": [{}]}}"#;
+
+    #[test]
+    fn round_trip_through_serde_json() {
+        let value = parse_value(DOCUMENT).unwrap();
+        let text = serde_json::to_string(&value).unwrap();
+        let back: JsonValue = serde_json::from_str(&text).unwrap();
+        assert_eq!(back, value);
+        // Keys keep their order, and integers stay integers
+        assert_eq!(
+            text,
+            r#"{"name":"jp","tags":["a","b"],"size":-3,"big":18446744073709551615,"ratio":0.25,"none":null,"ok":true,"nested":{"é":[{}]}}"#
+        );
+    }
+    #[test]
+    fn convert_to_and_from_serde_json_values() {
+        let value = parse_value(DOCUMENT).unwrap();
+        let converted = serde_json::to_value(&value).unwrap();
+        assert_eq!(converted["tags"][1], "b");
+        assert_eq!(converted["size"].as_i64(), Some(-3));
+        assert_eq!(converted["big"].as_u64(), Some(u64::MAX));
+        let back: JsonValue<'static> = serde_json::from_value(converted).unwrap();
+        assert_eq!(back, value);
+    }
+}
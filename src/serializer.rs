@@ -0,0 +1,746 @@
+use crate::color::{self, Highlight};
+use crate::escape;
+use crate::options::{FormatOptions, Layout, Style};
+use crate::pointer::push_segment;
+use crate::types::JsonValue;
+
+// Serialize a parsed value using the same layout as the token formatter, plus
+// transforms like key sorting and depth limits that the token formatter can't do
+pub fn serialize_pretty(value: &JsonValue, options: &FormatOptions) -> String {
+    let mut out = String::new();
+    format_into(&mut out, value, options);
+    out
+}
+
+/// Format a value as the `jp` command does, ending with `eol` unless
+/// `trailing_newline` is off.
+///
+/// ```
+/// use jp::{format_value, json, FormatOptions, Style};
+///
+/// let value = json!({"name": "jp", "tags": ["json", "cli"]});
+/// let pretty = FormatOptions::default();
+/// assert_eq!(
+///     format_value(&value, &pretty),
+///     "{\n    \"name\": \"jp\",\n    \"tags\": [\n        \"json\",\n        \"cli\"\n    ]\n}\n"
+/// );
+///
+/// let compact = FormatOptions {
+///     style: Style::Compact,
+///     trailing_newline: false,
+///     ..FormatOptions::default()
+/// };
+/// assert_eq!(format_value(&value, &compact), r#"{"name":"jp","tags":["json","cli"]}"#);
+/// ```
+pub fn format_value(value: &JsonValue, options: &FormatOptions) -> String {
+    let mut out = serialize_pretty(value, options);
+    if options.trailing_newline {
+        out.push_str(options.eol.as_str());
+    }
+    out
+}
+
+/// Like [`format_value`], but `layout` picks how each object and array is laid
+/// out, given its JSON Pointer and value. `Layout::Auto` leaves the choice to
+/// the options. Only pretty output has a layout to choose.
+///
+/// ```
+/// use jp::{format_value_with, json, FormatOptions, JsonValue, Layout};
+///
+/// let value = json!({"name": "origin", "point": [0, 0]});
+/// let layout = |pointer: &str, _: &JsonValue| match pointer {
+///     "/point" => Layout::Inline,
+///     _ => Layout::Auto,
+/// };
+/// assert_eq!(
+///     format_value_with(&value, &FormatOptions::default(), layout),
+///     "{\n    \"name\": \"origin\",\n    \"point\": [0, 0]\n}\n"
+/// );
+/// ```
+pub fn format_value_with<F>(value: &JsonValue, options: &FormatOptions, layout: F) -> String
+where
+    F: Fn(&str, &JsonValue) -> Layout,
+{
+    let mut out = String::new();
+    let mut hook = Hook {
+        layout: &layout,
+        path: String::new(),
+    };
+    format_hooked(&mut out, value, options, Some(&mut hook));
+    if options.trailing_newline {
+        out.push_str(options.eol.as_str());
+    }
+    out
+}
+
+// A `format_value_with` callback and the pointer to the value being written
+struct Hook<'f> {
+    layout: &'f dyn Fn(&str, &JsonValue) -> Layout,
+    path: String,
+}
+
+// Append a serialized value to `buf`, so one buffer can be cleared and reused
+// across many values
+pub fn format_into(buf: &mut String, value: &JsonValue, options: &FormatOptions) {
+    format_hooked(buf, value, options, None);
+}
+
+fn format_hooked(
+    buf: &mut String,
+    value: &JsonValue,
+    options: &FormatOptions,
+    hook: Option<&mut Hook>,
+) {
+    if options.compact_threshold.is_some() {
+        let mut compact = String::new();
+        format_into(&mut compact, value, &options.measuring());
+        let options = options.settle_threshold(compact.len());
+        return format_hooked(buf, value, &options, hook);
+    }
+    match value {
+        JsonValue::String(string) if options.raw_strings => buf.push_str(string),
+        _ => write_value(buf, value, options, 0, 0, hook),
+    }
+}
+
+// `column` is the width of whatever precedes the value on its line
+fn write_value(
+    out: &mut String,
+    value: &JsonValue,
+    options: &FormatOptions,
+    offset: usize,
+    column: usize,
+    mut hook: Option<&mut Hook>,
+) {
+    let layout = match &hook {
+        Some(hook) if matches!(value, JsonValue::Array(_) | JsonValue::Object(_)) => {
+            (hook.layout)(&hook.path, value)
+        }
+        _ => Layout::Auto,
+    };
+    let pretty = options.style == Style::Pretty;
+    if layout == Layout::Inline && pretty {
+        out.push_str(&inline(value, options, offset, column));
+        return;
+    }
+    if layout == Layout::Auto {
+        if let Some(inline) = fits_inline(value, options, offset, column) {
+            out.push_str(&inline);
+            return;
+        }
+        if options.inline_scalar_arrays && pretty && is_scalar_array(value) {
+            out.push_str(&inline(value, options, offset, column));
+            return;
+        }
+    }
+    match value {
+        JsonValue::Array(array) if array.is_empty() => punct(out, "[]", options),
+        JsonValue::Object(object) if object.is_empty() => punct(out, "{}", options),
+        // Past the depth limit only the size of a container is shown
+        JsonValue::Array(array) if options.depth.is_some_and(|depth| offset >= depth) => {
+            punct(out, &format!("[…{}]", count(array.len(), "item")), options);
+        }
+        JsonValue::Object(object) if options.depth.is_some_and(|depth| offset >= depth) => {
+            punct(
+                out,
+                &format!("{{…{}}}", count(object.len(), "key")),
+                options,
+            );
+        }
+        JsonValue::Array(array) => {
+            let shown = match options.head {
+                Some(head) if offset == 0 => head.min(array.len()),
+                _ => array.len(),
+            };
+            punct(out, "[", options);
+            for (i, item) in array.iter().take(shown).enumerate() {
+                if i > 0 {
+                    comma(out, options);
+                }
+                newline(out, options, offset + 1);
+                let len = hook
+                    .as_mut()
+                    .map(|hook| push_segment(&mut hook.path, &i.to_string()));
+                write_value(
+                    out,
+                    item,
+                    options,
+                    offset + 1,
+                    options.indent_columns() * (offset + 1),
+                    hook.as_deref_mut(),
+                );
+                leave(&mut hook, len);
+            }
+            if shown < array.len() {
+                if shown > 0 {
+                    comma(out, options);
+                }
+                newline(out, options, offset + 1);
+                let more = count(array.len() - shown, "more item");
+                punct(out, &format!("…{}", more), options);
+            }
+            trailing_comma(out, array.len(), options);
+            newline(out, options, offset);
+            punct(out, "]", options);
+        }
+        JsonValue::Object(object) => {
+            punct(out, "{", options);
+            let mut entries = object
+                .iter()
+                .map(|(key, item)| (key.as_ref(), item))
+                .collect::<Vec<_>>();
+            options.sort_keys.sort(&mut entries);
+            let len = entries.len();
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    comma(out, options);
+                }
+                newline(out, options, offset + 1);
+                let key_text = escape::quote_for(key, options);
+                out.push_str(&paint(Highlight::Key, &key_text, options));
+                punct(out, ":", options);
+                if options.style != Style::Compact {
+                    out.push(' ');
+                }
+                let column = options.indent_columns() * (offset + 1) + key_text.chars().count() + 2;
+                let len = hook.as_mut().map(|hook| push_segment(&mut hook.path, key));
+                write_value(out, item, options, offset + 1, column, hook.as_deref_mut());
+                leave(&mut hook, len);
+            }
+            trailing_comma(out, len, options);
+            newline(out, options, offset);
+            punct(out, "}", options);
+        }
+        scalar => {
+            let (kind, text) = match scalar {
+                JsonValue::String(string) => {
+                    (Highlight::String, escape::quote_for(string, options))
+                }
+                JsonValue::Number(number) if options.numbers_as_strings => {
+                    (Highlight::String, escape::quote_for(number.raw(), options))
+                }
+                JsonValue::Number(_) => (Highlight::Number, scalar.to_string()),
+                JsonValue::Bool(_) => (Highlight::Bool, scalar.to_string()),
+                _ => (Highlight::Null, scalar.to_string()),
+            };
+            out.push_str(&paint(kind, &text, options));
+        }
+    }
+}
+
+// A pretty-printed container rendered on one line, if that fits the line width
+fn fits_inline(
+    value: &JsonValue,
+    options: &FormatOptions,
+    offset: usize,
+    column: usize,
+) -> Option<String> {
+    let width = options
+        .line_width
+        .filter(|_| options.style == Style::Pretty)?;
+    if !matches!(value, JsonValue::Array(_) | JsonValue::Object(_)) {
+        return None;
+    }
+
+    // Measure without color codes or markup, which take no space on screen
+    let plain_options = FormatOptions {
+        color: false,
+        html: false,
+        ..options.clone()
+    };
+    if column
+        + inline(value, &plain_options, offset, column)
+            .chars()
+            .count()
+        > width
+    {
+        return None;
+    }
+    Some(inline(value, options, offset, column))
+}
+
+// A container written on one line in spaced style
+fn inline(value: &JsonValue, options: &FormatOptions, offset: usize, column: usize) -> String {
+    let inline_options = FormatOptions {
+        style: Style::Spaced,
+        line_width: None,
+        ..options.clone()
+    };
+    let mut inline = String::new();
+    write_value(&mut inline, value, &inline_options, offset, column, None);
+    inline
+}
+
+// Truncate the hook's path back to before the segment the caller pushed
+fn leave(hook: &mut Option<&mut Hook>, len: Option<usize>) {
+    if let (Some(hook), Some(len)) = (hook, len) {
+        hook.path.truncate(len);
+    }
+}
+
+// A non-empty array holding no arrays or objects
+fn is_scalar_array(value: &JsonValue) -> bool {
+    match value {
+        JsonValue::Array(array) => {
+            !array.is_empty()
+                && array
+                    .iter()
+                    .all(|item| !matches!(item, JsonValue::Array(_) | JsonValue::Object(_)))
+        }
+        _ => false,
+    }
+}
+
+fn count(n: usize, noun: &str) -> String {
+    if n == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", n, noun)
+    }
+}
+
+fn newline(out: &mut String, options: &FormatOptions, offset: usize) {
+    if options.style == Style::Pretty {
+        out.push_str(options.eol.as_str());
+        let (fill, per_level) = options.indent_unit();
+        for _ in 0..per_level * offset {
+            out.push_str(fill);
+        }
+    }
+}
+
+fn comma(out: &mut String, options: &FormatOptions) {
+    punct(out, ",", options);
+    if options.style == Style::Spaced {
+        out.push(' ');
+    }
+}
+
+// After the last of `len` elements; a single element gets none
+fn trailing_comma(out: &mut String, len: usize, options: &FormatOptions) {
+    if options.trailing_commas && options.style == Style::Pretty && len > 1 {
+        punct(out, ",", options);
+    }
+}
+
+fn punct(out: &mut String, text: &str, options: &FormatOptions) {
+    out.push_str(&paint(Highlight::Punct, text, options));
+}
+
+fn paint(kind: Highlight, text: &str, options: &FormatOptions) -> String {
+    if options.html {
+        format!(
+            "<span class=\"{}\">{}</span>",
+            kind.class(),
+            escape::html(text)
+        )
+    } else if let Some(code) = kind.code(options.theme).filter(|_| options.color) {
+        color::paint(code, text)
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::options::{KeyOrder, Theme};
+    use crate::parser::Parser;
+
+    fn serialize_str(input: &str, color: bool) -> String {
+        let tokens = Lexer::from(input).lex().unwrap();
+        let value = Parser::new(&tokens).parse().unwrap();
+        let options = FormatOptions {
+            color,
+            ..FormatOptions::default()
+        };
+        serialize_pretty(&value, &options)
+    }
+
+    #[test]
+    fn serialize_nested_value() {
+        let output = serialize_str(r#"{"a":[1,true,null],"b":{},"c":[],"d":{"e":"f"}}"#, false);
+        let expected = r#"{
+    "a": [
+        1,
+        true,
+        null
+    ],
+    "b": {},
+    "c": [],
+    "d": {
+        "e": "f"
+    }
+}"#;
+        assert_eq!(output, expected);
+    }
+    #[test]
+    fn serialize_sorted_keys() {
+        let tokens = Lexer::from(r#"{"b":1,"a":{"z":true,"y":[{"d":0,"c":1}]}}"#)
+            .lex()
+            .unwrap();
+        let value = Parser::new(&tokens).parse().unwrap();
+        let options = FormatOptions {
+            style: Style::Compact,
+            sort_keys: KeyOrder::Asc,
+            ..FormatOptions::default()
+        };
+        let expected = r#"{"a":{"y":[{"c":1,"d":0}],"z":true},"b":1}"#;
+        assert_eq!(serialize_pretty(&value, &options), expected);
+    }
+    #[test]
+    fn serialize_keys_in_each_order() {
+        let tokens = Lexer::from(r#"{"ccc": 1, "a": 2, "bb": 3, "é": 4, "B": 5}"#)
+            .lex()
+            .unwrap();
+        let value = Parser::new(&tokens).parse().unwrap();
+        let sorted = |sort_keys| {
+            let options = FormatOptions {
+                style: Style::Compact,
+                sort_keys,
+                ..FormatOptions::default()
+            };
+            serialize_pretty(&value, &options)
+        };
+        assert_eq!(
+            sorted(KeyOrder::Input),
+            r#"{"ccc":1,"a":2,"bb":3,"é":4,"B":5}"#
+        );
+        assert_eq!(
+            sorted(KeyOrder::Asc),
+            r#"{"B":5,"a":2,"bb":3,"ccc":1,"é":4}"#
+        );
+        assert_eq!(
+            sorted(KeyOrder::Desc),
+            r#"{"é":4,"ccc":1,"bb":3,"a":2,"B":5}"#
+        );
+        assert_eq!(
+            sorted(KeyOrder::Length),
+            r#"{"B":5,"a":2,"é":4,"bb":3,"ccc":1}"#
+        );
+    }
+    #[test]
+    fn serialize_with_color() {
+        let output = serialize_str(r#"{"key":"value"}"#, true);
+        let expected = format!(
+            "{{\n    {}: {}\n}}",
+            color::paint(color::KEY, r#""key""#),
+            color::paint(color::STRING, r#""value""#)
+        );
+        assert_eq!(output, expected);
+    }
+    #[test]
+    fn themes_pick_different_codes() {
+        let value = Parser::new(&Lexer::from("[1, null]").lex().unwrap())
+            .parse()
+            .unwrap();
+        let themed = |theme| {
+            let options = FormatOptions {
+                color: true,
+                theme,
+                style: Style::Compact,
+                ..FormatOptions::default()
+            };
+            (
+                serialize_pretty(&value, &options),
+                crate::parse("[1, null]", &Default::default(), &options).unwrap(),
+            )
+        };
+        let (default, _) = themed(Theme::Default);
+        let (dark, formatted) = themed(Theme::Dark);
+        assert_eq!(
+            default,
+            format!(
+                "[{},{}]",
+                color::paint(color::NUMBER, "1"),
+                color::paint(color::NULL, "null")
+            )
+        );
+        assert_eq!(dark, "[\x1b[93m1\x1b[0m,\x1b[37mnull\x1b[0m]");
+        // The token formatter uses the same palette
+        assert_eq!(formatted, dark);
+        assert_ne!(
+            Highlight::Number.code(Theme::Light),
+            Highlight::Number.code(Theme::Default)
+        );
+        assert_eq!(themed(Theme::None).0, "[1,null]");
+    }
+    fn serialize_depth(input: &str, depth: usize) -> String {
+        let tokens = Lexer::from(input).lex().unwrap();
+        let value = Parser::new(&tokens).parse().unwrap();
+        let options = FormatOptions {
+            style: Style::Spaced,
+            depth: Some(depth),
+            ..FormatOptions::default()
+        };
+        serialize_pretty(&value, &options)
+    }
+
+    #[test]
+    fn serialize_depth_limits() {
+        let input = r#"{"a": {"b": {"c": 1}, "d": [1, 2]}, "e": [{"f": null}], "g": "h", "i": {}}"#;
+        assert_eq!(serialize_depth(input, 0), "{…4 keys}");
+        assert_eq!(serialize_depth("[[1]]", 0), "[…1 item]");
+        assert_eq!(
+            serialize_depth(input, 1),
+            r#"{"a": {…2 keys}, "e": […1 item], "g": "h", "i": {}}"#
+        );
+        assert_eq!(
+            serialize_depth(input, 10),
+            r#"{"a": {"b": {"c": 1}, "d": [1, 2]}, "e": [{"f": null}], "g": "h", "i": {}}"#
+        );
+        assert_eq!(serialize_depth("42", 0), "42");
+    }
+    #[test]
+    fn serialize_head_of_top_level_array() {
+        let value = Parser::new(&Lexer::from("[1, [2, 3, 4], 5]").lex().unwrap())
+            .parse()
+            .unwrap();
+        let head = |head: usize, style: Style| {
+            let options = FormatOptions {
+                head: Some(head),
+                style,
+                ..FormatOptions::default()
+            };
+            serialize_pretty(&value, &options)
+        };
+        // Nested arrays are shown whole
+        assert_eq!(
+            head(2, Style::Pretty),
+            "[\n    1,\n    [\n        2,\n        3,\n        4\n    ],\n    …1 more item\n]"
+        );
+        assert_eq!(head(1, Style::Compact), "[1,…2 more items]");
+        assert_eq!(head(0, Style::Spaced), "[…3 more items]");
+        assert_eq!(head(3, Style::Compact), "[1,[2,3,4],5]");
+        assert_eq!(head(10, Style::Compact), "[1,[2,3,4],5]");
+        let object = Parser::new(&Lexer::from(r#"{"a": [1, 2]}"#).lex().unwrap())
+            .parse()
+            .unwrap();
+        let options = FormatOptions {
+            head: Some(1),
+            style: Style::Compact,
+            ..FormatOptions::default()
+        };
+        assert_eq!(serialize_pretty(&object, &options), r#"{"a":[1,2]}"#);
+    }
+    #[test]
+    fn format_into_reuses_buffer() {
+        let options = FormatOptions {
+            style: Style::Compact,
+            ..FormatOptions::default()
+        };
+        let values = [r#"{"a": [1, 2]}"#, "null", r#""x""#];
+        let mut buf = String::new();
+        for input in values {
+            let tokens = Lexer::from(input).lex().unwrap();
+            format_into(&mut buf, &Parser::new(&tokens).parse().unwrap(), &options);
+            buf.push('\n');
+        }
+        assert_eq!(buf, "{\"a\":[1,2]}\nnull\n\"x\"\n");
+
+        let capacity = buf.capacity();
+        buf.clear();
+        format_into(&mut buf, &JsonValue::Bool(true), &options);
+        assert_eq!(buf, "true");
+        assert_eq!(buf.capacity(), capacity);
+    }
+    #[test]
+    fn serialize_inlines_within_line_width() {
+        let tokens = Lexer::from(
+            r#"{"short": [1, 2, 3], "point": {"x": 1, "y": 2}, "long": ["alpha", "beta", "gamma", "delta"]}"#,
+        )
+        .lex()
+        .unwrap();
+        let value = Parser::new(&tokens).parse().unwrap();
+        let options = FormatOptions {
+            line_width: Some(30),
+            ..FormatOptions::default()
+        };
+        let expected = r#"{
+    "short": [1, 2, 3],
+    "point": {"x": 1, "y": 2},
+    "long": [
+        "alpha",
+        "beta",
+        "gamma",
+        "delta"
+    ]
+}"#;
+        assert_eq!(serialize_pretty(&value, &options), expected);
+
+        let options = FormatOptions {
+            line_width: Some(100),
+            ..FormatOptions::default()
+        };
+        let expected = r#"{"short": [1, 2, 3], "point": {"x": 1, "y": 2}, "long": ["alpha", "beta", "gamma", "delta"]}"#;
+        assert_eq!(serialize_pretty(&value, &options), expected);
+    }
+    #[test]
+    fn serialize_tab_width_counts_toward_line_width() {
+        let tokens = Lexer::from(r#"{"outer": {"list": [1, 2, 3]}}"#)
+            .lex()
+            .unwrap();
+        let value = Parser::new(&tokens).parse().unwrap();
+        // `"list": [1, 2, 3]` is 17 characters after two levels of indentation
+        let options = |tab_size| FormatOptions {
+            tabs: true,
+            tab_size,
+            line_width: Some(25),
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            serialize_pretty(&value, &options(4)),
+            "{\n\t\"outer\": {\n\t\t\"list\": [1, 2, 3]\n\t}\n}"
+        );
+        assert_eq!(
+            serialize_pretty(&value, &options(8)),
+            "{\n\t\"outer\": {\n\t\t\"list\": [\n\t\t\t1,\n\t\t\t2,\n\t\t\t3\n\t\t]\n\t}\n}"
+        );
+    }
+    #[test]
+    fn serialize_inline_scalar_arrays() {
+        let tokens = Lexer::from(
+            r#"{"numbers": [1, 2, 3], "mixed": ["a", null, true], "points": [{"x": 1}, [2]], "empty": []}"#,
+        )
+        .lex()
+        .unwrap();
+        let value = Parser::new(&tokens).parse().unwrap();
+        let options = FormatOptions {
+            inline_scalar_arrays: true,
+            ..FormatOptions::default()
+        };
+        let expected = r#"{
+    "numbers": [1, 2, 3],
+    "mixed": ["a", null, true],
+    "points": [
+        {
+            "x": 1
+        },
+        [2]
+    ],
+    "empty": []
+}"#;
+        assert_eq!(serialize_pretty(&value, &options), expected);
+    }
+    #[test]
+    fn serialize_trailing_commas() {
+        let tokens =
+            Lexer::from(r#"{"list": [1, [2]], "object": {"a": {}, "b": 0}, "one": [true]}"#)
+                .lex()
+                .unwrap();
+        let value = Parser::new(&tokens).parse().unwrap();
+        let options = FormatOptions {
+            indent: 2,
+            trailing_commas: true,
+            ..FormatOptions::default()
+        };
+        let expected = r#"{
+  "list": [
+    1,
+    [
+      2
+    ],
+  ],
+  "object": {
+    "a": {},
+    "b": 0,
+  },
+  "one": [
+    true
+  ],
+}"#;
+        assert_eq!(serialize_pretty(&value, &options), expected);
+
+        let compact = FormatOptions {
+            style: Style::Compact,
+            ..options.clone()
+        };
+        assert_eq!(
+            serialize_pretty(&value, &compact),
+            r#"{"list":[1,[2]],"object":{"a":{},"b":0},"one":[true]}"#
+        );
+        let inline = FormatOptions {
+            inline_scalar_arrays: true,
+            ..options
+        };
+        assert!(serialize_pretty(&value, &inline).contains("\"one\": [true],\n}"));
+    }
+    #[test]
+    fn format_value_ends_with_the_newline() {
+        let value = crate::parse_value(r#"{"a": [1]}"#).unwrap();
+        let options = FormatOptions {
+            style: Style::Compact,
+            eol: crate::Newline::Crlf,
+            ..FormatOptions::default()
+        };
+        assert_eq!(format_value(&value, &options), "{\"a\":[1]}\r\n");
+        let bare = FormatOptions {
+            trailing_newline: false,
+            ..options
+        };
+        assert_eq!(format_value(&value, &bare), serialize_pretty(&value, &bare));
+    }
+    #[test]
+    fn layout_auto_defers_to_the_options() {
+        let value = crate::json!({"name": "origin", "point": [0, 0]});
+        let layout = |path: &str, _: &JsonValue| match path {
+            "/point" => Layout::Inline,
+            _ => Layout::Auto,
+        };
+        assert_eq!(
+            format_value_with(&value, &FormatOptions::default(), layout),
+            "{\n    \"name\": \"origin\",\n    \"point\": [0, 0]\n}\n"
+        );
+        // Compact output has no layout to choose
+        let compact = FormatOptions {
+            style: Style::Compact,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            format_value_with(&value, &compact, |_, _| Layout::Expanded),
+            format_value(&value, &compact)
+        );
+    }
+    #[test]
+    fn format_with_layout_callback() {
+        let input = r#"{"shape": {"points": [[0, 0], [2, 1]]}, "tags": ["a"], "ends": [[9]]}"#;
+        let value = Parser::new(&Lexer::from(input).lex().unwrap())
+            .parse()
+            .unwrap();
+        // Coordinate pairs stay on one line, everything else is expanded even
+        // though the line width would fit it
+        let options = FormatOptions {
+            indent: 2,
+            line_width: Some(80),
+            trailing_newline: false,
+            ..FormatOptions::default()
+        };
+        let layout = |path: &str, _: &JsonValue| {
+            if path.starts_with("/shape/points/") {
+                Layout::Inline
+            } else {
+                Layout::Expanded
+            }
+        };
+        let expected = r#"{
+  "shape": {
+    "points": [
+      [0, 0],
+      [2, 1]
+    ]
+  },
+  "tags": [
+    "a"
+  ],
+  "ends": [
+    [
+      9
+    ]
+  ]
+}"#;
+        assert_eq!(format_value_with(&value, &options, layout), expected);
+        let auto = format_value_with(&value, &options, |_, _| Layout::Auto);
+        assert_eq!(auto, format_value(&value, &options));
+    }
+}
@@ -0,0 +1,101 @@
+use std::fmt;
+
+use crate::types::JsonValue;
+
+// Counts gathered from a single walk over a parsed document
+#[derive(Debug, Default, PartialEq)]
+pub struct Stats {
+    pub objects: usize,
+    pub arrays: usize,
+    pub strings: usize,
+    pub numbers: usize,
+    pub booleans: usize,
+    pub nulls: usize,
+    pub keys: usize,
+    // Deepest container nesting; a bare scalar has depth 0
+    pub max_depth: usize,
+}
+
+impl Stats {
+    pub fn from_value(value: &JsonValue) -> Self {
+        let mut stats = Stats::default();
+        stats.visit(value, 0);
+        stats
+    }
+    // Merge counts from another document, as for NDJSON input
+    pub fn add(&mut self, other: &Stats) {
+        self.objects += other.objects;
+        self.arrays += other.arrays;
+        self.strings += other.strings;
+        self.numbers += other.numbers;
+        self.booleans += other.booleans;
+        self.nulls += other.nulls;
+        self.keys += other.keys;
+        self.max_depth = self.max_depth.max(other.max_depth);
+    }
+    fn visit(&mut self, value: &JsonValue, depth: usize) {
+        match value {
+            JsonValue::Object(object) => {
+                self.objects += 1;
+                self.keys += object.len();
+                self.max_depth = self.max_depth.max(depth + 1);
+                for (_, item) in object {
+                    self.visit(item, depth + 1);
+                }
+            }
+            JsonValue::Array(array) => {
+                self.arrays += 1;
+                self.max_depth = self.max_depth.max(depth + 1);
+                for item in array {
+                    self.visit(item, depth + 1);
+                }
+            }
+            JsonValue::String(_) => self.strings += 1,
+            JsonValue::Number(_) => self.numbers += 1,
+            JsonValue::Bool(_) => self.booleans += 1,
+            JsonValue::Null => self.nulls += 1,
+        }
+    }
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "objects:   {}", self.objects)?;
+        writeln!(f, "arrays:    {}", self.arrays)?;
+        writeln!(f, "strings:   {}", self.strings)?;
+        writeln!(f, "numbers:   {}", self.numbers)?;
+        writeln!(f, "booleans:  {}", self.booleans)?;
+        writeln!(f, "nulls:     {}", self.nulls)?;
+        writeln!(f, "keys:      {}", self.keys)?;
+        write!(f, "max depth: {}", self.max_depth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_value;
+
+    #[test]
+    fn count_nested_document() {
+        let value = parse_value(r#"{"a": [1, "x", {"b": null}], "c": true, "d": []}"#).unwrap();
+        let expected = Stats {
+            objects: 2,
+            arrays: 2,
+            strings: 1,
+            numbers: 1,
+            booleans: 1,
+            nulls: 1,
+            keys: 4,
+            max_depth: 3,
+        };
+        assert_eq!(Stats::from_value(&value), expected);
+    }
+    #[test]
+    fn scalar_has_no_depth() {
+        let value = parse_value("42").unwrap();
+        let stats = Stats::from_value(&value);
+        assert_eq!(stats.numbers, 1);
+        assert_eq!(stats.max_depth, 0);
+    }
+}
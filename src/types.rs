@@ -1,30 +1,488 @@
+use std::borrow::Cow;
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
 use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
 
-#[derive(Clone, Debug, PartialEq)]
-pub enum JsonValue {
+use crate::escape;
+
+#[derive(Clone, Debug)]
+pub enum JsonValue<'a> {
     Null,
     Bool(bool),
-    Number(f64),
-    String(String),
-    Array(Vec<JsonValue>),
-    Object(HashMap<String, JsonValue>),
+    Number(Number<'a>),
+    String(Cow<'a, str>),
+    Array(Vec<JsonValue<'a>>),
+    Object(Vec<(Cow<'a, str>, JsonValue<'a>)>),
+}
+
+// A number along with the exact text it was written as, so formatting never
+// loses precision and `-0` keeps its sign. Numbers compare by their `f64`
+// value however they were written: `1` equals `1.0` and `1e0`, `-0` equals `0`,
+// and NaN equals itself so that equality is reflexive. Integers beyond 2^53
+// compare by the nearest `f64`, as they would in JavaScript.
+#[derive(Clone, Debug)]
+pub struct Number<'a> {
+    value: f64,
+    raw: Cow<'a, str>,
+}
+
+impl<'a> Number<'a> {
+    pub fn new(value: f64, raw: Cow<'a, str>) -> Self {
+        Self { value, raw }
+    }
+    pub fn as_f64(&self) -> f64 {
+        self.value
+    }
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+    // Whether the number was written without a fraction or exponent
+    pub fn is_integer(&self) -> bool {
+        !self.raw.contains(['.', 'e', 'E'])
+    }
+    // Whether the `f64` value is exactly the integer that was written. Integers
+    // beyond 2^53 may be rounded to a neighbour; numbers with a fraction or
+    // exponent always count as exact, since decimal fractions are rounded anyway.
+    pub fn is_exact(&self) -> bool {
+        let digits = self.raw.trim_start_matches('-').trim_start_matches('0');
+        // Every integer of up to 15 digits is below 2^53
+        if !self.is_integer() || digits.len() <= 15 {
+            return true;
+        }
+        self.value.is_finite() && format!("{:.0}", self.value.abs()) == digits
+    }
+    // The exact integer value, if it was written as an integer that fits `i64`
+    pub fn as_i64(&self) -> Option<i64> {
+        self.raw.parse().ok()
+    }
+    pub fn into_owned(self) -> Number<'static> {
+        Number::new(self.value, Cow::Owned(self.raw.into_owned()))
+    }
+}
+
+// JSON has no NaN or infinity, so only finite values convert
+impl TryFrom<f64> for Number<'_> {
+    type Error = Error;
+
+    fn try_from(value: f64) -> Result<Self, Error> {
+        if !value.is_finite() {
+            return Err(Error::Unsupported(format!(
+                "{} can't be written as a JSON number",
+                value
+            )));
+        }
+        Ok(Self::new(value, Cow::Owned(value.to_string())))
+    }
+}
+
+impl PartialEq for Number<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value || (self.value.is_nan() && other.value.is_nan())
+    }
+}
+
+impl Eq for Number<'_> {}
+
+impl Hash for Number<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        // Values that compare equal must hash equally, so fold -0 into 0 and
+        // every NaN into one bit pattern
+        let value = if self.value == 0.0 {
+            0.0
+        } else if self.value.is_nan() {
+            f64::NAN
+        } else {
+            self.value
+        };
+        value.to_bits().hash(state);
+    }
+}
+
+// Objects are equal when they hold the same keys with equal values, in any
+// order. A repeated key counts once, with its last value, as the parser reads it.
+impl PartialEq for JsonValue<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Null, Self::Null) => true,
+            (Self::Bool(a), Self::Bool(b)) => a == b,
+            (Self::Number(a), Self::Number(b)) => a == b,
+            (Self::String(a), Self::String(b)) => a == b,
+            (Self::Array(a), Self::Array(b)) => a == b,
+            (Self::Object(a), Self::Object(b)) => last_wins(a) == last_wins(b),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for JsonValue<'_> {}
+
+// An object's entries by key, keeping the last value of a repeated key
+pub(crate) fn last_wins<'v, 'a>(
+    object: &'v [(Cow<'a, str>, JsonValue<'a>)],
+) -> HashMap<&'v str, &'v JsonValue<'a>> {
+    object
+        .iter()
+        .map(|(key, value)| (key.as_ref(), value))
+        .collect()
+}
+
+impl Hash for JsonValue<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Self::Null => {}
+            Self::Bool(bool) => bool.hash(state),
+            Self::Number(number) => number.hash(state),
+            Self::String(string) => string.hash(state),
+            Self::Array(array) => array.hash(state),
+            Self::Object(object) => {
+                // Combine entry hashes with a commutative sum so key order doesn't matter
+                let entries = last_wins(object);
+                let combined = entries.iter().fold(0u64, |sum, entry| {
+                    let mut hasher = DefaultHasher::new();
+                    entry.hash(&mut hasher);
+                    sum.wrapping_add(hasher.finish())
+                });
+                entries.len().hash(state);
+                combined.hash(state);
+            }
+        }
+    }
+}
+
+impl fmt::Display for Number<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+impl<'a> JsonValue<'a> {
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(string) => Some(string),
+            _ => None,
+        }
+    }
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Number(number) => Some(number.as_f64()),
+            _ => None,
+        }
+    }
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Bool(bool) => Some(*bool),
+            _ => None,
+        }
+    }
+    pub fn as_array(&self) -> Option<&Vec<JsonValue<'a>>> {
+        match self {
+            Self::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+    pub fn as_object(&self) -> Option<&Vec<(Cow<'a, str>, JsonValue<'a>)>> {
+        match self {
+            Self::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+    // The string, moved out; it still borrows from the input unless it had to
+    // be unescaped
+    pub fn into_string(self) -> Option<Cow<'a, str>> {
+        match self {
+            Self::String(string) => Some(string),
+            _ => None,
+        }
+    }
+    pub fn into_array(self) -> Option<Vec<JsonValue<'a>>> {
+        match self {
+            Self::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+    pub fn into_object(self) -> Option<Vec<(Cow<'a, str>, JsonValue<'a>)>> {
+        match self {
+            Self::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+    // Move the value out, leaving `null` in its place
+    pub fn take(&mut self) -> JsonValue<'a> {
+        std::mem::replace(self, Self::Null)
+    }
+    /// Whether the value is `null`.
+    ///
+    /// ```
+    /// use jp::parse_value;
+    ///
+    /// assert!(parse_value("null").unwrap().is_null());
+    /// assert!(!parse_value("0").unwrap().is_null());
+    /// ```
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+    /// Whether the value is `true` or `false`.
+    ///
+    /// ```
+    /// use jp::parse_value;
+    ///
+    /// assert!(parse_value("false").unwrap().is_bool());
+    /// assert!(!parse_value("null").unwrap().is_bool());
+    /// ```
+    pub fn is_bool(&self) -> bool {
+        matches!(self, Self::Bool(_))
+    }
+    /// Whether the value is a number.
+    ///
+    /// ```
+    /// use jp::parse_value;
+    ///
+    /// assert!(parse_value("-1.5e3").unwrap().is_number());
+    /// assert!(!parse_value(r#""1""#).unwrap().is_number());
+    /// ```
+    pub fn is_number(&self) -> bool {
+        matches!(self, Self::Number(_))
+    }
+    /// Whether the value is a string.
+    ///
+    /// ```
+    /// use jp::parse_value;
+    ///
+    /// assert!(parse_value(r#""text""#).unwrap().is_string());
+    /// assert!(!parse_value("[]").unwrap().is_string());
+    /// ```
+    pub fn is_string(&self) -> bool {
+        matches!(self, Self::String(_))
+    }
+    /// Whether the value is an array.
+    ///
+    /// ```
+    /// use jp::parse_value;
+    ///
+    /// assert!(parse_value("[1, 2]").unwrap().is_array());
+    /// assert!(!parse_value("{}").unwrap().is_array());
+    /// ```
+    pub fn is_array(&self) -> bool {
+        matches!(self, Self::Array(_))
+    }
+    /// Whether the value is an object.
+    ///
+    /// ```
+    /// use jp::parse_value;
+    ///
+    /// assert!(parse_value(r#"{"a": 1}"#).unwrap().is_object());
+    /// assert!(!parse_value("[]").unwrap().is_object());
+    /// ```
+    pub fn is_object(&self) -> bool {
+        matches!(self, Self::Object(_))
+    }
+    // Look up an object entry by key; of repeated keys the last one wins, as
+    // for pointers, merging, diffing and equality
+    pub fn get(&self, key: &str) -> Option<&JsonValue<'a>> {
+        self.as_object()?
+            .iter()
+            .rfind(|(k, _)| k == key)
+            .map(|(_, value)| value)
+    }
+    // Look up an array element by index
+    pub fn get_index(&self, index: usize) -> Option<&JsonValue<'a>> {
+        self.as_array()?.get(index)
+    }
+    // An object's entries in order, or nothing for any other value
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &JsonValue<'a>)> {
+        self.as_object()
+            .into_iter()
+            .flatten()
+            .map(|(key, value)| (key.as_ref(), value))
+    }
+    // An array's elements in order, or nothing for any other value
+    pub fn elements(&self) -> impl Iterator<Item = &JsonValue<'a>> {
+        self.as_array().into_iter().flatten()
+    }
+    // Remove an object entry by key, keeping the order of the others. Every
+    // entry of a repeated key goes, and the last one's value is returned.
+    pub fn remove(&mut self, key: &str) -> Option<JsonValue<'a>> {
+        let Self::Object(object) = self else {
+            return None;
+        };
+        let mut removed = None;
+        let mut i = 0;
+        while i < object.len() {
+            if object[i].0 == key {
+                removed = Some(object.remove(i).1);
+            } else {
+                i += 1;
+            }
+        }
+        removed
+    }
+    // Remove an array element, shifting later elements down
+    pub fn remove_index(&mut self, index: usize) -> Option<JsonValue<'a>> {
+        match self {
+            Self::Array(array) if index < array.len() => Some(array.remove(index)),
+            _ => None,
+        }
+    }
+    // The value at an RFC 6901 JSON Pointer, mutably, or `None` if there is none
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut JsonValue<'a>> {
+        crate::pointer::resolve_pointer_mut(self, pointer)
+    }
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            Self::Null => ValueType::Null,
+            Self::Bool(_) => ValueType::Bool,
+            Self::Number(_) => ValueType::Number,
+            Self::String(_) => ValueType::String,
+            Self::Array(_) => ValueType::Array,
+            Self::Object(_) => ValueType::Object,
+        }
+    }
+    // Copy any borrowed strings so the value no longer depends on the input
+    pub fn into_owned(self) -> JsonValue<'static> {
+        match self {
+            Self::Null => JsonValue::Null,
+            Self::Bool(bool) => JsonValue::Bool(bool),
+            Self::Number(number) => JsonValue::Number(number.into_owned()),
+            Self::String(string) => JsonValue::String(Cow::Owned(string.into_owned())),
+            Self::Array(array) => {
+                JsonValue::Array(array.into_iter().map(JsonValue::into_owned).collect())
+            }
+            Self::Object(object) => JsonValue::Object(
+                object
+                    .into_iter()
+                    .map(|(key, value)| (Cow::Owned(key.into_owned()), value.into_owned()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+// The variant of a value without its contents
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ValueType {
+    Null,
+    Bool,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+/// Parse a document into a value that owns its strings.
+///
+/// ```
+/// use jp::JsonValue;
+///
+/// let value = r#"{"name": "jp", "tags": ["json"]}"#.parse::<JsonValue>().unwrap();
+/// assert_eq!(value.get("name").and_then(JsonValue::as_str), Some("jp"));
+/// assert_eq!(value.get("tags").and_then(|tags| tags.get_index(0)).and_then(JsonValue::as_str), Some("json"));
+/// ```
+impl FromStr for JsonValue<'static> {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        crate::parse_value(input).map(JsonValue::into_owned)
+    }
+}
+
+impl<'a> From<&'a str> for JsonValue<'a> {
+    fn from(string: &'a str) -> Self {
+        Self::String(Cow::Borrowed(string))
+    }
+}
+
+impl From<String> for JsonValue<'_> {
+    fn from(string: String) -> Self {
+        Self::String(Cow::Owned(string))
+    }
+}
+
+// NaN and infinity become `null`, as JavaScript's JSON.stringify writes them
+impl From<f64> for JsonValue<'_> {
+    fn from(value: f64) -> Self {
+        Number::try_from(value).map_or(Self::Null, Self::Number)
+    }
+}
+
+// Integers are kept exact in the raw text, even beyond the 2^53 an `f64` can hold
+macro_rules! from_integer {
+    ($($integer:ty),*) => {
+        $(
+            impl From<$integer> for JsonValue<'_> {
+                fn from(value: $integer) -> Self {
+                    Self::Number(Number::new(value as f64, Cow::Owned(value.to_string())))
+                }
+            }
+        )*
+    };
+}
+
+from_integer!(i32, i64, u32, u64, usize);
+
+impl From<bool> for JsonValue<'_> {
+    fn from(bool: bool) -> Self {
+        Self::Bool(bool)
+    }
+}
+
+impl<'a> From<Vec<JsonValue<'a>>> for JsonValue<'a> {
+    fn from(array: Vec<JsonValue<'a>>) -> Self {
+        Self::Array(array)
+    }
+}
+
+/// Build an object from key-value pairs, keeping their order.
+///
+/// ```
+/// use jp::{serialize_pretty, FormatOptions, JsonValue, Style};
+///
+/// let value: JsonValue = [
+///     ("name", JsonValue::from("jp")),
+///     ("version", JsonValue::from(2_i64)),
+///     ("ratio", JsonValue::from(0.5)),
+///     ("stable", JsonValue::from(true)),
+///     ("tags", JsonValue::from(vec!["json".into(), String::from("cli").into()])),
+/// ]
+/// .into_iter()
+/// .collect();
+///
+/// let options = FormatOptions {
+///     style: Style::Compact,
+///     ..FormatOptions::default()
+/// };
+/// assert_eq!(
+///     serialize_pretty(&value, &options),
+///     r#"{"name":"jp","version":2,"ratio":0.5,"stable":true,"tags":["json","cli"]}"#
+/// );
+/// ```
+impl<'a, K: Into<Cow<'a, str>>> FromIterator<(K, JsonValue<'a>)> for JsonValue<'a> {
+    fn from_iter<I: IntoIterator<Item = (K, JsonValue<'a>)>>(entries: I) -> Self {
+        Self::Object(
+            entries
+                .into_iter()
+                .map(|(key, value)| (key.into(), value))
+                .collect(),
+        )
+    }
 }
 
-impl fmt::Display for JsonValue {
+impl fmt::Display for JsonValue<'_> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::Null => f.write_str("null"),
             Self::Bool(bool) => write!(f, "{}", bool),
             Self::Number(number) => write!(f, "{}", number),
-            Self::String(string) => write!(f, r#""{}""#, string),
+            Self::String(string) => f.write_str(&escape::quote(string, false)),
             Self::Array(_) => f.write_str("[Array]"),
             Self::Object(_) => f.write_str("[Object]"),
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum TokenType {
     Null,
     Bool,
@@ -36,31 +494,47 @@ pub enum TokenType {
     RightBrace,
     LeftBracket,
     RightBracket,
+    // A `//` or `/* */` comment, only emitted when comments are preserved
+    Comment,
+    // The end of the input, only emitted when asked for
+    Eof,
 }
 
-impl fmt::Display for TokenType {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl TokenType {
+    pub fn as_str(&self) -> &'static str {
         match self {
-            Self::Null => f.write_str("null"),
-            Self::Bool => f.write_str("boolean"),
-            Self::Number => f.write_str("number"),
-            Self::String => f.write_str("string"),
-            Self::Comma => f.write_str(","),
-            Self::Colon => f.write_str(":"),
-            Self::LeftBrace => f.write_str("{"),
-            Self::RightBrace => f.write_str("}"),
-            Self::LeftBracket => f.write_str("["),
-            Self::RightBracket => f.write_str("]"),
+            Self::Null => "null",
+            Self::Bool => "boolean",
+            Self::Number => "number",
+            Self::String => "string",
+            Self::Comma => ",",
+            Self::Colon => ":",
+            Self::LeftBrace => "{",
+            Self::RightBrace => "}",
+            Self::LeftBracket => "[",
+            Self::RightBracket => "]",
+            Self::Comment => "comment",
+            Self::Eof => "end of input",
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
-pub struct Token {
+impl fmt::Display for TokenType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token<'a> {
     pub token_type: TokenType,
-    pub value: Option<JsonValue>,
+    pub value: Option<JsonValue<'a>>,
+    // 1-based position of the first character
     pub line: usize,
     pub column: usize,
+    // Byte span of the token in the source
+    pub start: usize,
+    pub end: usize,
 }
 
 #[derive(Debug)]
@@ -69,9 +543,23 @@ pub enum Error {
     UnexpectedEndOfArray,
     UnexpectedEndOfObject,
     UnexpectedEndOfInput,
-    UnexpectedCharacter(char, (usize, usize)),
-    UnexpectedToken(String),
-    ParseNumber(String),
+    // Nothing but whitespace or comments
+    EmptyDocument,
+    UnexpectedEndOfComment((usize, usize), usize),
+    // A `\` in a string with nothing after it
+    DanglingEscape((usize, usize), usize),
+    UnexpectedCharacter(char, (usize, usize), usize),
+    InvalidEscape(String, (usize, usize), usize),
+    NumberOutOfRange(String, (usize, usize), usize),
+    ImpreciseNumber(String, (usize, usize), usize),
+    UnexpectedToken(String, (usize, usize), usize),
+    ParseNumber(String, (usize, usize), usize),
+    MaxDepthExceeded(usize),
+    InvalidPointer(String),
+    InvalidPath(String),
+    InvalidLine(usize, Box<Error>),
+    Io(std::io::Error),
+    Unsupported(String),
 }
 
 impl fmt::Display for Error {
@@ -81,27 +569,516 @@ impl fmt::Display for Error {
             Self::UnexpectedEndOfArray => f.write_str("Unexpected end-of-array bracket"),
             Self::UnexpectedEndOfObject => f.write_str("Unexpected end-of-object brace"),
             Self::UnexpectedEndOfInput => f.write_str("Unexpected end of input"),
-            Self::UnexpectedCharacter(char, (line, col)) => write!(
+            Self::EmptyDocument => f.write_str("Empty document: expected a JSON value"),
+            Self::UnexpectedEndOfComment((line, col), offset) => write!(
+                f,
+                "Unterminated block comment starting at line {} column {} (byte {})",
+                line, col, offset
+            ),
+            Self::DanglingEscape((line, col), offset) => write!(
+                f,
+                "Unterminated string: input ends after the backslash at line {} column {} (byte {})",
+                line, col, offset
+            ),
+            Self::UnexpectedCharacter(char, (line, col), offset) => {
+                // Control characters such as a form feed would be invisible
+                let shown = if char.is_control() {
+                    format!("U+{:04X}", *char as u32)
+                } else {
+                    char.to_string()
+                };
+                write!(
+                    f,
+                    "Unexpected character: {}, line {} column {} (byte {})",
+                    shown, line, col, offset
+                )
+            }
+            Self::InvalidEscape(sequence, (line, col), offset) => write!(
+                f,
+                "Invalid escape sequence: {}, line {} column {} (byte {})",
+                sequence, line, col, offset
+            ),
+            Self::NumberOutOfRange(number, (line, col), offset) => write!(
+                f,
+                "Integer out of range for i64: {}, line {} column {} (byte {})",
+                number, line, col, offset
+            ),
+            Self::ImpreciseNumber(number, (line, col), offset) => write!(
                 f,
-                "Unexpected character: {}, line {} column {}",
-                char, line, col
+                "Integer not exactly representable as a double: {}, line {} column {} (byte {})",
+                number, line, col, offset
             ),
-            Self::UnexpectedToken(err) => write!(f, "{}", err),
-            Self::ParseNumber(err) => write!(f, "{}", err),
+            Self::UnexpectedToken(err, _, offset) => write!(f, "{} (byte {})", err, offset),
+            Self::ParseNumber(number, (line, col), offset) => write!(
+                f,
+                "Invalid number: {}, line {} column {} (byte {})",
+                number, line, col, offset
+            ),
+            Self::InvalidPointer(err) => write!(f, "{}", err),
+            Self::InvalidPath(err) => write!(f, "{}", err),
+            Self::InvalidLine(line, err) => write!(f, "Line {}: {}", line, err),
+            Self::Io(err) => write!(f, "{}", err),
+            Self::Unsupported(err) => write!(f, "{}", err),
+            Self::MaxDepthExceeded(depth) => {
+                write!(f, "Maximum nesting depth of {} exceeded", depth)
+            }
         }
     }
 }
 
 impl std::error::Error for Error {}
 
-impl From<std::num::ParseIntError> for Error {
-    fn from(err: std::num::ParseIntError) -> Self {
-        Error::ParseNumber(format!("Failed to parse integer: {}", err))
+// Every variant but `Io` holds plain data. An `io::Error` can't be cloned, so
+// its copy keeps the kind and message but loses the underlying OS error.
+impl Clone for Error {
+    fn clone(&self) -> Self {
+        match self {
+            Self::UnexpectedEndOfString => Self::UnexpectedEndOfString,
+            Self::UnexpectedEndOfArray => Self::UnexpectedEndOfArray,
+            Self::UnexpectedEndOfObject => Self::UnexpectedEndOfObject,
+            Self::UnexpectedEndOfInput => Self::UnexpectedEndOfInput,
+            Self::EmptyDocument => Self::EmptyDocument,
+            Self::UnexpectedEndOfComment(position, offset) => {
+                Self::UnexpectedEndOfComment(*position, *offset)
+            }
+            Self::DanglingEscape(position, offset) => Self::DanglingEscape(*position, *offset),
+            Self::UnexpectedCharacter(char, position, offset) => {
+                Self::UnexpectedCharacter(*char, *position, *offset)
+            }
+            Self::InvalidEscape(sequence, position, offset) => {
+                Self::InvalidEscape(sequence.clone(), *position, *offset)
+            }
+            Self::NumberOutOfRange(number, position, offset) => {
+                Self::NumberOutOfRange(number.clone(), *position, *offset)
+            }
+            Self::ImpreciseNumber(number, position, offset) => {
+                Self::ImpreciseNumber(number.clone(), *position, *offset)
+            }
+            Self::UnexpectedToken(err, position, offset) => {
+                Self::UnexpectedToken(err.clone(), *position, *offset)
+            }
+            Self::ParseNumber(number, position, offset) => {
+                Self::ParseNumber(number.clone(), *position, *offset)
+            }
+            Self::MaxDepthExceeded(depth) => Self::MaxDepthExceeded(*depth),
+            Self::InvalidPointer(err) => Self::InvalidPointer(err.clone()),
+            Self::InvalidPath(err) => Self::InvalidPath(err.clone()),
+            Self::InvalidLine(line, err) => Self::InvalidLine(*line, err.clone()),
+            Self::Io(err) => Self::Io(std::io::Error::new(err.kind(), err.to_string())),
+            Self::Unsupported(err) => Self::Unsupported(err.clone()),
+        }
+    }
+}
+
+// Broad grouping of errors so callers can react without matching the message
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ErrorKind {
+    // The input could not be split into tokens
+    Lexical,
+    // The tokens do not form a valid document
+    Syntactic,
+    Pointer,
+    Io,
+    Unsupported,
+}
+
+impl Error {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Self::UnexpectedEndOfString
+            | Self::UnexpectedEndOfComment(..)
+            | Self::DanglingEscape(..)
+            | Self::UnexpectedCharacter(..)
+            | Self::InvalidEscape(..)
+            | Self::NumberOutOfRange(..)
+            | Self::ImpreciseNumber(..)
+            | Self::ParseNumber(..) => ErrorKind::Lexical,
+            Self::UnexpectedEndOfArray
+            | Self::UnexpectedEndOfObject
+            | Self::UnexpectedEndOfInput
+            | Self::EmptyDocument
+            | Self::UnexpectedToken(..)
+            | Self::MaxDepthExceeded(_) => ErrorKind::Syntactic,
+            Self::InvalidPointer(_) | Self::InvalidPath(_) => ErrorKind::Pointer,
+            Self::InvalidLine(_, err) => err.kind(),
+            Self::Io(_) => ErrorKind::Io,
+            Self::Unsupported(_) => ErrorKind::Unsupported,
+        }
+    }
+    // The variant's name, such as `UnexpectedCharacter`, for tools that match on
+    // errors; an NDJSON line's error is named by its cause
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::UnexpectedEndOfString => "UnexpectedEndOfString",
+            Self::UnexpectedEndOfArray => "UnexpectedEndOfArray",
+            Self::UnexpectedEndOfObject => "UnexpectedEndOfObject",
+            Self::UnexpectedEndOfInput => "UnexpectedEndOfInput",
+            Self::EmptyDocument => "EmptyDocument",
+            Self::UnexpectedEndOfComment(..) => "UnexpectedEndOfComment",
+            Self::DanglingEscape(..) => "DanglingEscape",
+            Self::UnexpectedCharacter(..) => "UnexpectedCharacter",
+            Self::InvalidEscape(..) => "InvalidEscape",
+            Self::NumberOutOfRange(..) => "NumberOutOfRange",
+            Self::ImpreciseNumber(..) => "ImpreciseNumber",
+            Self::UnexpectedToken(..) => "UnexpectedToken",
+            Self::ParseNumber(..) => "ParseNumber",
+            Self::MaxDepthExceeded(_) => "MaxDepthExceeded",
+            Self::InvalidPointer(_) => "InvalidPointer",
+            Self::InvalidPath(_) => "InvalidPath",
+            Self::InvalidLine(_, err) => err.name(),
+            Self::Io(_) => "Io",
+            Self::Unsupported(_) => "Unsupported",
+        }
+    }
+    // Wrap an error from one NDJSON line, lexed on its own, as an error on line
+    // `line` of the whole input, which starts at byte `start`. A line holds no
+    // line breaks, so every position inside it is on its first line.
+    pub(crate) fn on_line(self, line: usize, start: usize) -> Self {
+        let relocate = |(_, column): (usize, usize)| (line, column);
+        let err = match self {
+            Self::UnexpectedEndOfComment(position, offset) => {
+                Self::UnexpectedEndOfComment(relocate(position), start + offset)
+            }
+            Self::DanglingEscape(position, offset) => {
+                Self::DanglingEscape(relocate(position), start + offset)
+            }
+            Self::UnexpectedCharacter(char, position, offset) => {
+                Self::UnexpectedCharacter(char, relocate(position), start + offset)
+            }
+            Self::InvalidEscape(sequence, position, offset) => {
+                Self::InvalidEscape(sequence, relocate(position), start + offset)
+            }
+            Self::NumberOutOfRange(number, position, offset) => {
+                Self::NumberOutOfRange(number, relocate(position), start + offset)
+            }
+            Self::ImpreciseNumber(number, position, offset) => {
+                Self::ImpreciseNumber(number, relocate(position), start + offset)
+            }
+            Self::ParseNumber(number, position, offset) => {
+                Self::ParseNumber(number, relocate(position), start + offset)
+            }
+            // The parser writes positions into the message itself
+            Self::UnexpectedToken(message, position, offset) => Self::UnexpectedToken(
+                message.replace(" line 1 column ", &format!(" line {} column ", line)),
+                relocate(position),
+                start + offset,
+            ),
+            err => err,
+        };
+        Self::InvalidLine(line, Box::new(err))
+    }
+    // 1-based line and column of the error in the input, when known
+    pub fn position(&self) -> Option<(usize, usize)> {
+        match self {
+            Self::UnexpectedEndOfComment(position, _)
+            | Self::DanglingEscape(position, _)
+            | Self::UnexpectedCharacter(_, position, _)
+            | Self::InvalidEscape(_, position, _)
+            | Self::NumberOutOfRange(_, position, _)
+            | Self::ImpreciseNumber(_, position, _)
+            | Self::ParseNumber(_, position, _)
+            | Self::UnexpectedToken(_, position, _) => Some(*position),
+            Self::InvalidLine(_, err) => err.position(),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
     }
 }
 
-impl From<std::num::ParseFloatError> for Error {
-    fn from(err: std::num::ParseFloatError) -> Self {
-        Error::ParseNumber(format!("Failed to parse float: {}", err))
+#[cfg(test)]
+mod tests {
+    use super::{Error, ErrorKind, JsonValue, Number};
+    use crate::parse_value;
+    use crate::{parse_ndjson, FormatOptions, ParseOptions};
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::HashSet;
+    use std::hash::{Hash, Hasher};
+
+    #[test]
+    fn typed_accessors() {
+        let value =
+            parse_value(r#"{"s": "str", "n": 1.5, "b": true, "a": [null], "o": {}}"#).unwrap();
+        assert_eq!(value.get("s").and_then(|v| v.as_str()), Some("str"));
+        assert_eq!(value.get("n").and_then(|v| v.as_f64()), Some(1.5));
+        assert_eq!(value.get("b").and_then(|v| v.as_bool()), Some(true));
+        assert_eq!(
+            value.get("a").and_then(|v| v.as_array()).map(|a| a.len()),
+            Some(1)
+        );
+        assert_eq!(
+            value.get("o").and_then(|v| v.as_object()).map(|o| o.len()),
+            Some(0)
+        );
+        assert!(value.get("a").and_then(|v| v.get_index(0)).is_some());
+
+        // The parser keeps one entry per key, so build the repeat by hand
+        let mut value = JsonValue::Object(vec![
+            ("a".into(), JsonValue::from(1.0)),
+            ("b".into(), JsonValue::from(2.0)),
+            ("a".into(), JsonValue::from(3.0)),
+        ]);
+        assert_eq!(value.get("a").and_then(|v| v.as_f64()), Some(3.0));
+        assert_eq!(value, parse_value(r#"{"b": 2, "a": 3}"#).unwrap());
+        assert_eq!(value.remove("a").and_then(|v| v.as_f64()), Some(3.0));
+        assert_eq!(value.get("a"), None);
+    }
+    #[test]
+    fn move_values_out_without_cloning() {
+        let input = format!(
+            "{{\"items\": [{}], \"name\": \"jp\"}}",
+            vec!["0"; 10_000].join(",")
+        );
+        let mut value = parse_value(&input).unwrap();
+        let buffer = value
+            .get("items")
+            .and_then(|v| v.as_array())
+            .unwrap()
+            .as_ptr();
+        let items = value
+            .pointer_mut("/items")
+            .unwrap()
+            .take()
+            .into_array()
+            .unwrap();
+        // The same allocation, moved rather than copied
+        assert_eq!(items.as_ptr(), buffer);
+        assert_eq!(items.len(), 10_000);
+        assert!(value.get("items").unwrap().is_null());
+
+        let mut object = value.into_object().unwrap();
+        let name = object.pop().unwrap().1.into_string().unwrap();
+        assert_eq!(name, "jp");
+        assert!(JsonValue::Null.into_string().is_none());
+        assert!(JsonValue::from(1.5).into_array().is_none());
+        assert!(JsonValue::Array(items).into_object().is_none());
+    }
+    #[test]
+    fn type_predicates() {
+        let values = parse_value(r#"[null, false, -1.5e3, "text", [1], {"a": 1}]"#).unwrap();
+        let checks: [fn(&JsonValue) -> bool; 6] = [
+            |v| v.is_null(),
+            |v| v.is_bool(),
+            |v| v.is_number(),
+            |v| v.is_string(),
+            |v| v.is_array(),
+            |v| v.is_object(),
+        ];
+        // Each predicate holds for exactly the value at its own index
+        for (i, check) in checks.iter().enumerate() {
+            for (j, value) in values.elements().enumerate() {
+                assert_eq!(check(value), i == j, "predicate {} on {}", i, value);
+            }
+        }
+    }
+    #[test]
+    fn iterate_entries_and_elements() {
+        let value = parse_value(r#"{"a": [1, 2], "b": {"c": [3]}, "d": null}"#).unwrap();
+        let mut numbers = Vec::new();
+        let mut keys = Vec::new();
+        fn walk<'v>(value: &'v JsonValue, keys: &mut Vec<&'v str>, numbers: &mut Vec<f64>) {
+            numbers.extend(value.as_f64());
+            for (key, item) in value.entries() {
+                keys.push(key);
+                walk(item, keys, numbers);
+            }
+            for item in value.elements() {
+                walk(item, keys, numbers);
+            }
+        }
+        walk(&value, &mut keys, &mut numbers);
+        assert_eq!(keys, ["a", "b", "c", "d"]);
+        assert_eq!(numbers, [1.0, 2.0, 3.0]);
+        assert_eq!(value.elements().count(), 0);
+        assert_eq!(JsonValue::Null.entries().count(), 0);
+    }
+    #[test]
+    fn build_values_from_rust_types() {
+        let value = [
+            ("a", JsonValue::from(vec![1.5.into(), i64::MAX.into()])),
+            ("b", JsonValue::from(String::from("x"))),
+        ]
+        .into_iter()
+        .collect::<JsonValue>();
+        assert_eq!(
+            value,
+            parse_value(r#"{"b": "x", "a": [1.5, 9223372036854775807]}"#).unwrap()
+        );
+        let large = value.get("a").and_then(|a| a.get_index(1));
+        assert!(matches!(large, Some(JsonValue::Number(n)) if n.as_i64() == Some(i64::MAX)));
+    }
+    #[test]
+    fn edit_through_pointer_mut() {
+        let mut value = parse_value(r#"{"a": [1, {"b": "old"}], "c/d": null}"#).unwrap();
+        *value.pointer_mut("/a/1/b").unwrap() = JsonValue::String("new".into());
+        *value.pointer_mut("/c~1d").unwrap() = JsonValue::Bool(true);
+        if let Some(JsonValue::Array(array)) = value.pointer_mut("/a") {
+            array.push(JsonValue::Null);
+        }
+        let options = FormatOptions {
+            style: crate::Style::Compact,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            crate::serialize_pretty(&value, &options),
+            r#"{"a":[1,{"b":"new"},null],"c/d":true}"#
+        );
+        for pointer in ["/missing", "/a/3", "/a/01", "/a/0/x", "a"] {
+            assert!(value.pointer_mut(pointer).is_none(), "{}", pointer);
+        }
+    }
+    #[test]
+    fn typed_accessors_mismatch() {
+        let value = parse_value(r#"[1, "two"]"#).unwrap();
+        assert_eq!(value.get("key"), None);
+        assert_eq!(value.get_index(2), None);
+        assert_eq!(value.get_index(0).and_then(|v| v.as_str()), None);
+        assert_eq!(value.get_index(1).and_then(|v| v.as_f64()), None);
+        assert_eq!(value.as_bool(), None);
+        assert_eq!(value.as_object(), None);
+    }
+    #[test]
+    fn from_str_owns_its_data() {
+        let value: JsonValue<'static> = {
+            let input = String::from(r#"{"a": [1.50, "x"]}"#);
+            input.parse().unwrap()
+        };
+        let array = value.get("a").and_then(|v| v.as_array()).unwrap();
+        assert_eq!(array[0].to_string(), "1.50");
+        assert_eq!(array[1].as_str(), Some("x"));
+        assert!("[1,".parse::<JsonValue>().is_err());
+    }
+    #[test]
+    fn error_kind_and_position() {
+        let error = parse_value("[1, ~]").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Lexical);
+        assert_eq!(error.position(), Some((1, 5)));
+        assert_eq!(error.name(), "UnexpectedCharacter");
+
+        let error = parse_value("{\n  \"a\" 1}").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Syntactic);
+        assert_eq!(error.position(), Some((2, 7)));
+        assert_eq!(error.name(), "UnexpectedToken");
+
+        let error = parse_value("[1,").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Syntactic);
+        assert_eq!(error.position(), None);
+
+        let options = ParseOptions::default();
+        let error = parse_ndjson("{}\n[1 2]", &options, &FormatOptions::default()).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::Syntactic);
+        assert_eq!(error.position(), Some((2, 4)));
+        assert_eq!(error.name(), "UnexpectedToken");
+    }
+    #[test]
+    fn clone_every_error_variant() {
+        let position = (2, 3);
+        let errors = [
+            Error::UnexpectedEndOfString,
+            Error::UnexpectedEndOfArray,
+            Error::UnexpectedEndOfObject,
+            Error::UnexpectedEndOfInput,
+            Error::EmptyDocument,
+            Error::UnexpectedEndOfComment(position, 4),
+            Error::DanglingEscape(position, 4),
+            Error::UnexpectedCharacter('~', position, 4),
+            Error::InvalidEscape("\\q".to_string(), position, 4),
+            Error::NumberOutOfRange("1e999".to_string(), position, 4),
+            Error::ImpreciseNumber("9007199254740993".to_string(), position, 4),
+            Error::UnexpectedToken("Expected ':'".to_string(), position, 4),
+            Error::ParseNumber("1.e".to_string(), position, 4),
+            Error::MaxDepthExceeded(128),
+            Error::InvalidPointer("No such key".to_string()),
+            Error::InvalidPath("Bad path".to_string()),
+            Error::InvalidLine(7, Box::new(Error::UnexpectedCharacter('~', position, 4))),
+            Error::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "missing.json",
+            )),
+            Error::Unsupported("No canonical form".to_string()),
+        ];
+        for error in &errors {
+            let copy = error.clone();
+            assert_eq!(copy.to_string(), error.to_string());
+            assert_eq!(copy.kind(), error.kind());
+            assert_eq!(copy.position(), error.position());
+        }
+        let Error::Io(copy) = errors[17].clone() else {
+            panic!("expected an IO error");
+        };
+        assert_eq!(copy.kind(), std::io::ErrorKind::NotFound);
+    }
+    #[test]
+    fn number_equality_ignores_representation() {
+        assert_eq!(parse_value("1").unwrap(), parse_value("1.0").unwrap());
+        assert_eq!(
+            parse_value("[1e2, -0]").unwrap(),
+            parse_value("[100, 0]").unwrap()
+        );
+        assert_ne!(parse_value("1").unwrap(), parse_value("\"1\"").unwrap());
+        let nan = JsonValue::Number(Number::new(f64::NAN, "NaN".into()));
+        assert_eq!(nan, nan.clone());
+    }
+    #[test]
+    fn non_finite_floats_are_not_numbers() {
+        for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            assert!(Number::try_from(value).is_err(), "{}", value);
+            assert_eq!(JsonValue::from(value), JsonValue::Null);
+        }
+        let value = JsonValue::from(vec![f64::NAN.into(), 2.5.into(), f64::INFINITY.into()]);
+        let options = FormatOptions {
+            style: crate::Style::Compact,
+            ..FormatOptions::default()
+        };
+        assert_eq!(crate::serialize_pretty(&value, &options), "[null,2.5,null]");
+        assert_eq!(Number::try_from(-0.5).unwrap().raw(), "-0.5");
+    }
+    #[test]
+    fn object_equality_and_hash_ignore_key_order() {
+        let a = parse_value(r#"{"x": 1, "y": [true, {"z": null}]}"#).unwrap();
+        let b = parse_value(r#"{"y": [true, {"z": null}], "x": 1.0}"#).unwrap();
+        assert_eq!(a, b);
+        assert_ne!(
+            a,
+            parse_value(r#"{"x": 1, "y": [{"z": null}, true]}"#).unwrap()
+        );
+        assert_ne!(a, parse_value(r#"{"x": 1}"#).unwrap());
+
+        let set = [a, b, parse_value("-0").unwrap(), parse_value("0").unwrap()]
+            .into_iter()
+            .collect::<HashSet<_>>();
+        assert_eq!(set.len(), 2);
+    }
+    #[test]
+    fn repeated_keys_compare_by_their_last_value() {
+        let hash = |value: &JsonValue| {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        };
+        let repeated = [("a", JsonValue::from(1)), ("a", 1.into())]
+            .into_iter()
+            .collect::<JsonValue>();
+        let distinct = [("a", JsonValue::from(1)), ("b", 2.into())]
+            .into_iter()
+            .collect::<JsonValue>();
+        // Equality is symmetric even though both have two entries
+        assert_ne!(repeated, distinct);
+        assert_ne!(distinct, repeated);
+
+        let single = parse_value(r#"{"a": 1}"#).unwrap();
+        assert_eq!(repeated, single);
+        assert_eq!(single, repeated);
+        assert_eq!(hash(&repeated), hash(&single));
+
+        let overridden = [("a", JsonValue::from(0)), ("a", 1.into())]
+            .into_iter()
+            .collect::<JsonValue>();
+        assert_eq!(overridden, single);
+        assert_ne!(overridden, parse_value(r#"{"a": 0}"#).unwrap());
     }
 }
@@ -0,0 +1,150 @@
+use crate::escape;
+use crate::types::JsonValue;
+
+// Render a parsed value as block-style YAML, indenting two spaces per level
+pub fn to_yaml(value: &JsonValue) -> String {
+    lines(value).join("\n")
+}
+
+// Lines of a node relative to its own indentation
+fn lines(value: &JsonValue) -> Vec<String> {
+    match value {
+        JsonValue::Object(object) if !object.is_empty() => {
+            let mut out = Vec::new();
+            for (key, item) in object {
+                let key = scalar_string(key);
+                match inline(item) {
+                    Some(text) => out.push(format!("{}: {}", key, text)),
+                    None => {
+                        out.push(format!("{}:", key));
+                        out.extend(lines(item).into_iter().map(|line| format!("  {}", line)));
+                    }
+                }
+            }
+            out
+        }
+        JsonValue::Array(array) if !array.is_empty() => {
+            let mut out = Vec::new();
+            for item in array {
+                // Nested blocks start on the dash line, continuation lines align after it
+                for (i, line) in lines(item).into_iter().enumerate() {
+                    let prefix = if i == 0 { "- " } else { "  " };
+                    out.push(format!("{}{}", prefix, line));
+                }
+            }
+            out
+        }
+        scalar => vec![inline(scalar).unwrap_or_default()],
+    }
+}
+
+// Text for values that fit on the line they start on
+fn inline(value: &JsonValue) -> Option<String> {
+    match value {
+        JsonValue::Null => Some("null".to_string()),
+        JsonValue::Bool(bool) => Some(bool.to_string()),
+        JsonValue::Number(number) => Some(number.to_string()),
+        JsonValue::String(string) => Some(scalar_string(string)),
+        JsonValue::Array(array) if array.is_empty() => Some("[]".to_string()),
+        JsonValue::Object(object) if object.is_empty() => Some("{}".to_string()),
+        _ => None,
+    }
+}
+
+// Leave strings plain unless YAML would read them as something else
+fn scalar_string(string: &str) -> String {
+    if needs_quotes(string) {
+        escape::quote(string, false)
+    } else {
+        string.to_string()
+    }
+}
+
+fn needs_quotes(string: &str) -> bool {
+    const RESERVED: &[&str] = &[
+        "null", "~", "true", "false", "yes", "no", "on", "off", "y", "n",
+    ];
+    const INDICATORS: &[char] = &[
+        '-', '?', ':', ',', '[', ']', '{', '}', '#', '&', '*', '!', '|', '>', '\'', '"', '%', '@',
+        '`',
+    ];
+
+    let Some(first) = string.chars().next() else {
+        return true;
+    };
+    RESERVED.contains(&string.to_ascii_lowercase().as_str())
+        || string.parse::<f64>().is_ok()
+        || first.is_ascii_digit()
+        || first == '.'
+        || INDICATORS.contains(&first)
+        || string.starts_with(char::is_whitespace)
+        || string.ends_with(char::is_whitespace)
+        || string.ends_with(':')
+        || string.contains(": ")
+        || string.contains(" #")
+        || string.chars().any(char::is_control)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_value;
+
+    fn yaml(input: &str) -> String {
+        to_yaml(&parse_value(input).unwrap())
+    }
+
+    #[test]
+    fn yaml_nested_structures() {
+        let input = r#"{
+            "name": "jp",
+            "tags": ["json", "cli"],
+            "owner": {"id": 7, "admin": true},
+            "matrix": [[1, 2], [3]],
+            "people": [{"name": "a", "age": null}, {}],
+            "empty": [],
+            "none": {}
+        }"#;
+        let expected = "name: jp
+tags:
+  - json
+  - cli
+owner:
+  id: 7
+  admin: true
+matrix:
+  - - 1
+    - 2
+  - - 3
+people:
+  - name: a
+    age: null
+  - {}
+empty: []
+none: {}";
+        assert_eq!(yaml(input), expected);
+    }
+    #[test]
+    fn yaml_quotes_ambiguous_strings() {
+        let cases = [
+            (r#""plain text""#, "plain text"),
+            (r#""""#, r#""""#),
+            (r#""true""#, r#""true""#),
+            (r#""No""#, r#""No""#),
+            (r#""null""#, r#""null""#),
+            (r#""12""#, r#""12""#),
+            (r#""1e3""#, r#""1e3""#),
+            (r#""- item""#, r#""- item""#),
+            (r#""key: value""#, r#""key: value""#),
+            (r#""issue#1""#, "issue#1"),
+            (r#""a #comment""#, r#""a #comment""#),
+            (r#"" padded ""#, r#"" padded ""#),
+            (r#""two\nlines""#, r#""two\nlines""#),
+            (r#""say \"hi\"""#, r#"say "hi""#),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(yaml(input), expected, "{}", input);
+        }
+        assert_eq!(yaml(r#"{"true": 1}"#), r#""true": 1"#);
+    }
+}
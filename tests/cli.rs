@@ -0,0 +1,627 @@
+// End-to-end runs of the jp binary over stdin and files
+mod common;
+
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+use common::{jp, jp_with_env};
+
+// A path in a fresh directory under the system temp dir. The directory and
+// everything in it is removed when this is dropped, even if the test fails.
+struct TempPath {
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+impl Deref for TempPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+// An empty directory
+fn temp_dir(name: &str) -> TempPath {
+    let dir = std::env::temp_dir().join(format!("jp-cli-{}-{}", std::process::id(), name));
+    fs::create_dir_all(&dir).unwrap();
+    TempPath {
+        path: dir.clone(),
+        dir,
+    }
+}
+
+// A file alone in its directory
+fn temp_file(name: &str, contents: &str) -> TempPath {
+    let mut temp = temp_dir(name);
+    temp.path = temp.dir.join(name);
+    fs::write(&temp.path, contents).unwrap();
+    temp
+}
+
+#[test]
+fn format_stdin() {
+    let output = jp(&[], r#"{"a":[1,2],"b":{}}"#);
+    assert_eq!(output.code, 0);
+    assert_eq!(
+        output.stdout,
+        "{\n    \"a\": [\n        1,\n        2\n    ],\n    \"b\": {}\n}\n"
+    );
+    assert_eq!(output.stderr, "");
+
+    let output = jp(
+        &["--compact", "--sort-keys"],
+        r#"{"b": 1, "a": [true, null]}"#,
+    );
+    assert_eq!(output.stdout, "{\"a\":[true,null],\"b\":1}\n");
+}
+
+#[test]
+fn format_files() {
+    let a = temp_file("a.json", r#"{"x": 1}"#);
+    let b = temp_file("b.json", "[]");
+    let (a, b) = (a.to_str().unwrap(), b.to_str().unwrap());
+
+    let output = jp(&["-c", a], "");
+    assert_eq!(output.code, 0);
+    assert_eq!(output.stdout, "{\"x\":1}\n");
+
+    let output = jp(&["-c", a, b], "");
+    assert_eq!(
+        output.stdout,
+        format!("==> {} <==\n{{\"x\":1}}\n\n==> {} <==\n[]\n", a, b)
+    );
+}
+
+#[test]
+fn report_invalid_input() {
+    let output = jp(&[], r#"{"a": }"#);
+    assert_eq!(output.code, 1);
+    assert_eq!(output.stdout, "");
+    assert_eq!(
+        output.stderr,
+        "Invalid JSON: Expected a value (object, array, string, number, boolean, or null), found '}' at line 1 column 7 (byte 6)\n"
+    );
+
+    // A bad file is reported and skipped while the others are still formatted
+    let bad = temp_file("bad.json", "[1,");
+    let good = temp_file("good.json", "[1]");
+    let (bad, good) = (bad.to_str().unwrap(), good.to_str().unwrap());
+    let output = jp(&["-c", bad, good], "");
+    assert_eq!(output.code, 1);
+    assert_eq!(output.stdout, format!("==> {} <==\n[1]\n", good));
+    assert_eq!(
+        output.stderr,
+        format!(
+            "jp: {}: Invalid JSON: Unexpected end-of-array bracket\n",
+            bad
+        )
+    );
+}
+
+#[test]
+fn report_missing_files_and_bad_options() {
+    let output = jp(&["missing.json"], "");
+    assert_eq!(output.code, 3);
+    assert_eq!(
+        output.stderr,
+        "jp: missing.json: No such file or directory\n"
+    );
+
+    let output = jp(&["--bogus"], "{}");
+    assert_eq!(output.code, 2);
+    assert!(output
+        .stderr
+        .starts_with("jp: unknown option: --bogus\nUsage: jp"));
+}
+
+#[test]
+fn short_q_validates_and_quiet_is_long_only() {
+    // -q is --validate, so nothing is printed for valid input
+    let output = jp(&["-q"], r#"{"a": 1}"#);
+    assert_eq!((output.code, output.stdout.as_str()), (0, ""));
+    let output = jp(&["-q"], "[1,");
+    assert_eq!(output.code, 1);
+    // --quiet still prints the formatted document
+    let output = jp(&["--quiet", "-c"], r#"{"a": 1}"#);
+    assert_eq!((output.code, output.stdout.as_str()), (0, "{\"a\":1}\n"));
+}
+
+#[test]
+fn raw_output_unquotes_strings() {
+    let input = r#"{"name": "caf\u00e9\n\"bar\"", "count": 3}"#;
+    let output = jp(&["--raw-output", "--pointer", "/name"], input);
+    assert_eq!(output.code, 0);
+    assert_eq!(output.stdout, "caf\u{e9}\n\"bar\"\n");
+
+    let output = jp(&["--raw-output", "--pointer", "/count"], input);
+    assert_eq!(output.stdout, "3\n");
+    let output = jp(&["--raw-output", "--path", "$.*"], input);
+    assert_eq!(output.stdout, "caf\u{e9}\n\"bar\"\n3\n");
+
+    // -r is short for --raw-output, as in jq
+    let output = jp(&["-r"], r#""x""#);
+    assert_eq!(output.stdout, "x\n");
+}
+
+#[test]
+fn set_replaces_values() {
+    let input = r#"{"a": {"b": 1}, "c": [1, 2]}"#;
+    let output = jp(&["-c", "--set", "/a/b", r#""new""#], input);
+    assert_eq!(output.code, 0);
+    assert_eq!(output.stdout, "{\"a\":{\"b\":\"new\"},\"c\":[1,2]}\n");
+
+    let output = jp(&["-c", "--set", "/c", r#"{"d": [true]}"#], input);
+    assert_eq!(output.stdout, "{\"a\":{\"b\":1},\"c\":{\"d\":[true]}}\n");
+
+    let output = jp(&["-c", "--set", "/x/y", "null", "--create"], input);
+    assert_eq!(
+        output.stdout,
+        "{\"a\":{\"b\":1},\"c\":[1,2],\"x\":{\"y\":null}}\n"
+    );
+
+    let output = jp(&["--set", "/x/y", "null"], input);
+    assert_eq!(output.code, 1);
+    assert_eq!(output.stderr, "jp: No such key 'x' in pointer /x/y\n");
+    let output = jp(&["--set", "/a", "{"], input);
+    assert_eq!(output.code, 2);
+    assert_eq!(
+        output.stderr,
+        "jp: invalid --set value for /a: Invalid JSON: Unexpected end-of-object brace\n"
+    );
+}
+
+#[test]
+fn delete_removes_values() {
+    let input = r#"{"a": {"b": 1, "c": 2}, "d": [10, 20, 30]}"#;
+    let output = jp(&["-c", "--delete", "/a/b"], input);
+    assert_eq!(output.code, 0);
+    assert_eq!(output.stdout, "{\"a\":{\"c\":2},\"d\":[10,20,30]}\n");
+
+    let output = jp(&["-c", "--delete", "/d/0", "--pointer", "/d/0"], input);
+    assert_eq!(output.stdout, "20\n");
+
+    let output = jp(&["--delete", "/d/3"], input);
+    assert_eq!(output.code, 1);
+    assert_eq!(output.stdout, "");
+    assert_eq!(
+        output.stderr,
+        "jp: Invalid array index '3' in pointer /d/3\n"
+    );
+}
+
+#[test]
+fn precision_loss_is_reported() {
+    let input = "[9007199254740992, 9007199254740993]";
+    let output = jp(&["-c", "--warn-precision"], input);
+    assert_eq!(output.code, 0);
+    assert_eq!(output.stdout, "[9007199254740992,9007199254740993]\n");
+    assert_eq!(
+        output.stderr,
+        "jp: warning: integer 9007199254740993 is not exactly representable as a double, line 1 column 20\n"
+    );
+
+    let output = jp(&["--strict-precision"], input);
+    assert_eq!(output.code, 1);
+    assert_eq!(
+        output.stderr,
+        "Invalid JSON: Integer not exactly representable as a double: 9007199254740993, line 1 column 20 (byte 19)\n"
+    );
+}
+
+#[test]
+fn dash_reads_stdin() {
+    let output = jp(&["--sort-keys", "-c", "-"], r#"{"b": 1, "a": 2}"#);
+    assert_eq!(output.code, 0);
+    assert_eq!(output.stdout, "{\"a\":2,\"b\":1}\n");
+
+    let file = temp_file("dash.json", "[]");
+    let file = file.to_str().unwrap();
+    let output = jp(&["-c", file, "-"], "{}");
+    assert_eq!(
+        output.stdout,
+        format!("==> {} <==\n[]\n\n==> - <==\n{{}}\n", file)
+    );
+    let output = jp(&["--validate", "-"], "[1,");
+    assert_eq!(output.code, 1);
+}
+
+#[test]
+fn drop_nulls_keeps_array_nulls() {
+    let input = r#"{"a": null, "b": {"c": null, "d": 1}, "e": [null, {"f": null}]}"#;
+    let output = jp(&["-c", "--drop-nulls"], input);
+    assert_eq!(output.code, 0);
+    assert_eq!(output.stdout, "{\"b\":{\"d\":1},\"e\":[null,{}]}\n");
+}
+
+#[test]
+fn max_size_refuses_larger_input() {
+    let output = jp(&["-c", "--max-size", "7"], "[1,  2]");
+    assert_eq!(output.code, 0);
+    assert_eq!(output.stdout, "[1,2]\n");
+
+    let output = jp(&["-c", "--max-size", "6"], "[1,  2]");
+    assert_eq!(output.code, 3);
+    assert_eq!(output.stdout, "");
+    assert_eq!(
+        output.stderr,
+        "jp: input is larger than the --max-size limit of 6 bytes\n"
+    );
+
+    let file = temp_file("large.json", "[1,  2]");
+    let file = file.to_str().unwrap();
+    let output = jp(&["--validate", "--max-size", "6", file], "");
+    assert_eq!(output.code, 3);
+    assert_eq!(
+        output.stderr,
+        format!(
+            "jp: {}: input is larger than the --max-size limit of 6 bytes\n",
+            file
+        )
+    );
+}
+
+#[test]
+fn preserve_comments_in_jsonc() {
+    let input = "// settings\n{\"a\": 1, // first\n\"b\": 2}";
+    let output = jp(&["--jsonc", "--preserve-comments"], input);
+    assert_eq!(output.code, 0);
+    assert_eq!(
+        output.stdout,
+        "// settings\n{\n    \"a\": 1, // first\n    \"b\": 2\n}\n"
+    );
+    let output = jp(&["--jsonc"], input);
+    assert_eq!(output.stdout, "{\n    \"a\": 1,\n    \"b\": 2\n}\n");
+    let output = jp(&["--preserve-comments"], input);
+    assert_eq!(output.code, 2);
+}
+
+#[test]
+fn numbers_as_strings_keep_their_digits() {
+    let input = r#"{"id": 1234567890123456789, "price": 1.50, "big": -2.5E400, "n": [0]}"#;
+    let output = jp(&["-c", "--numbers-as-strings"], input);
+    assert_eq!(output.code, 0);
+    assert_eq!(
+        output.stdout,
+        "{\"id\":\"1234567890123456789\",\"price\":\"1.50\",\"big\":\"-2.5E400\",\"n\":[\"0\"]}\n"
+    );
+    let output = jp(&["--numbers-as-strings", "--to", "yaml"], input);
+    assert_eq!(output.code, 2);
+}
+
+#[test]
+fn head_limits_array_elements() {
+    let input = r#"{"records": [{"id": 1}, {"id": 2}, {"id": 3}]}"#;
+    let output = jp(&["-c", "--head", "2", "--pointer", "/records"], input);
+    assert_eq!(output.code, 0);
+    assert_eq!(output.stdout, "[{\"id\":1},{\"id\":2},…1 more item]\n");
+    let output = jp(&["-c", "--head", "3", "--pointer", "/records"], input);
+    assert_eq!(output.stdout, "[{\"id\":1},{\"id\":2},{\"id\":3}]\n");
+    let output = jp(&["-c", "--head", "5", "--pointer", "/records"], input);
+    assert_eq!(output.stdout, "[{\"id\":1},{\"id\":2},{\"id\":3}]\n");
+    // An object is printed whole
+    let output = jp(&["-c", "--head", "1"], input);
+    assert_eq!(
+        output.stdout,
+        "{\"records\":[{\"id\":1},{\"id\":2},{\"id\":3}]}\n"
+    );
+    let output = jp(&["--head", "1", "--write"], input);
+    assert_eq!(output.code, 2);
+}
+
+#[test]
+fn pretty_errors_point_at_the_column() {
+    let input = "{\n  \"a\": [1, 2,\n  \"b\" 3]\n}";
+    let output = jp(&["--pretty-errors"], input);
+    assert_eq!(output.code, 1);
+    assert_eq!(
+        output.stderr,
+        "Invalid JSON: Expected ',' or ']', found number at line 3 column 7 (byte 22)\n  |\n3 |   \"b\" 3]\n  |       ^\n"
+    );
+
+    let file = temp_file("pretty.json", "[1, 2,]");
+    let file = file.to_str().unwrap();
+    let output = jp(&["--validate", "--pretty-errors", file], "");
+    assert_eq!(
+        output.stderr,
+        format!(
+            "jp: {}: Invalid JSON: Expected a value (object, array, string, number, boolean, or null), found ']' at line 1 column 7 (byte 6)\n  |\n1 | [1, 2,]\n  |       ^\n",
+            file
+        )
+    );
+    // Errors without a position are printed alone
+    let output = jp(&["--pretty-errors"], "[1,");
+    assert_eq!(
+        output.stderr,
+        "Invalid JSON: Unexpected end-of-array bracket\n"
+    );
+}
+
+#[test]
+fn pick_builds_an_object_from_pointers() {
+    let input = r#"{"a": {"b": [1, 2]}, "c": ["x", "y"]}"#;
+    let output = jp(&["-c", "--pick", "/a/b=foo", "--pick", "/c/0=bar"], input);
+    assert_eq!(output.code, 0);
+    assert_eq!(output.stdout, "{\"foo\":[1,2],\"bar\":\"x\"}\n");
+
+    let args = ["-c", "--pick", "/a/b=foo", "--pick", "/missing=gone"];
+    let output = jp(&args, input);
+    assert_eq!(output.code, 1);
+    assert_eq!(output.stdout, "");
+    assert_eq!(
+        output.stderr,
+        "jp: No such key 'missing' in pointer /missing\n"
+    );
+    let output = jp(&[&args[..], &["--lenient"]].concat(), input);
+    assert_eq!(output.code, 0);
+    assert_eq!(output.stdout, "{\"foo\":[1,2]}\n");
+
+    let output = jp(&["--pick", "a/b"], input);
+    assert_eq!(output.code, 2);
+}
+
+#[test]
+fn multi_reads_documents_back_to_back() {
+    let output = jp(&["-c", "--multi"], r#"{"a": 1}{"b": 2}"#);
+    assert_eq!(output.code, 0);
+    assert_eq!(output.stdout, "{\"a\":1}\n{\"b\":2}\n");
+    let output = jp(&["--multi"], "{\"a\": 1}\n[1,\n2]");
+    assert_eq!(output.stdout, "{\n    \"a\": 1\n}\n[\n    1,\n    2\n]\n");
+    let output = jp(&["-c"], r#"{"a": 1}{"b": 2}"#);
+    assert_eq!(output.code, 1);
+    let output = jp(&["--multi", "--ndjson"], "{}");
+    assert_eq!(output.code, 2);
+}
+
+#[test]
+fn compact_arrays_inline_only_scalar_arrays() {
+    let input = r#"{"tags": ["a", "b"], "matrix": [[1, 2], [3]], "empty": [], "point": {"x": 1}}"#;
+    let output = jp(&["--compact-arrays", "--indent", "2"], input);
+    assert_eq!(output.code, 0);
+    assert_eq!(
+        output.stdout,
+        "{\n  \"tags\": [\"a\", \"b\"],\n  \"matrix\": [\n    [1, 2],\n    [3]\n  ],\n  \"empty\": [],\n  \"point\": {\n    \"x\": 1\n  }\n}\n"
+    );
+    assert_eq!(
+        output.stdout,
+        jp(&["--inline-scalar-arrays", "--indent", "2"], input).stdout
+    );
+}
+
+#[test]
+fn indent_string_sets_each_level() {
+    let output = jp(&["--indent-string", "\t "], r#"{"a": [1]}"#);
+    assert_eq!(output.code, 0);
+    assert_eq!(output.stdout, "{\n\t \"a\": [\n\t \t 1\n\t ]\n}\n");
+    let output = jp(&["--indent-string", "--"], "{}");
+    assert_eq!(output.code, 2);
+    assert!(output.stderr.contains("invalid --indent-string value"));
+}
+
+#[test]
+fn type_names_the_top_level_value() {
+    let cases = [
+        (r#"{"a": [1]}"#, "object"),
+        ("[]", "array"),
+        (r#""text""#, "string"),
+        ("-1.5e3", "number"),
+        ("false", "boolean"),
+        ("null", "null"),
+    ];
+    for (input, expected) in cases {
+        let output = jp(&["--type"], input);
+        assert_eq!(output.code, 0, "{}", input);
+        assert_eq!(output.stdout, format!("{}\n", expected));
+    }
+    let output = jp(&["--type", "--pointer", "/a/0"], r#"{"a": [1]}"#);
+    assert_eq!(output.stdout, "number\n");
+    // The whole document is still validated
+    let output = jp(&["--type"], "[1, }");
+    assert_eq!(output.code, 1);
+    assert_eq!(output.stdout, "");
+}
+
+#[test]
+fn files_from_lists_the_inputs() {
+    let a = temp_file("listed-a.json", r#"{"a":1}"#);
+    let b = temp_file("listed-b.json", "[ ]");
+    let (a, b) = (a.to_str().unwrap(), b.to_str().unwrap());
+    let list = format!("{}\n\nmissing.json\n{}\n", a, b);
+    let output = jp(&["-c", "--files-from", "-"], &list);
+    assert_eq!(output.code, 3);
+    assert_eq!(
+        output.stdout,
+        format!("==> {} <==\n{{\"a\":1}}\n\n==> {} <==\n[]\n", a, b)
+    );
+    assert_eq!(
+        output.stderr,
+        "jp: missing.json: No such file or directory\n"
+    );
+
+    let list = temp_file("list.txt", &format!("{}\n{}\n", a, b));
+    let output = jp(&["--check", "--files-from", list.to_str().unwrap()], "");
+    assert_eq!(output.code, 1);
+    assert_eq!(output.stdout, format!("{}\n{}\n", a, b));
+    let output = jp(&["--files-from", "-"], "");
+    assert_eq!(output.code, 0);
+    assert_eq!(output.stdout, "");
+}
+
+#[test]
+fn theme_none_turns_color_off() {
+    let output = jp(&["-c", "--color", "always", "--theme", "none"], "[true]");
+    assert_eq!(output.code, 0);
+    assert_eq!(output.stdout, "[true]\n");
+    let output = jp(&["-c", "--color", "always", "--theme", "light"], "[true]");
+    assert_eq!(output.stdout, "[\x1b[35mtrue\x1b[0m]\n");
+    let output = jp(&["--theme", "solarized"], "[]");
+    assert_eq!(output.code, 2);
+    assert_eq!(output.stderr, "jp: invalid --theme value: solarized\n");
+}
+
+#[test]
+fn dry_run_lists_files_write_would_change() {
+    let dir = temp_dir("dry-run");
+    fs::create_dir_all(dir.join("nested")).unwrap();
+    let files = [
+        ("formatted.json", "{\n    \"a\": 1\n}\n"),
+        ("messy.json", "{\"a\":1}"),
+        ("nested/also-messy.json", "[1,2]"),
+        ("nested/empty.json", "[]\n"),
+    ];
+    for (name, contents) in files {
+        fs::write(dir.join(name), contents).unwrap();
+    }
+    let output = jp(
+        &["--write", "--dry-run", "--recursive", dir.to_str().unwrap()],
+        "",
+    );
+    assert_eq!(output.code, 0);
+    let mut listed = output.stdout.lines().collect::<Vec<_>>();
+    listed.sort();
+    let messy = dir.join("messy.json");
+    let nested = dir.join("nested/also-messy.json");
+    assert_eq!(listed, [messy.to_str().unwrap(), nested.to_str().unwrap()]);
+    assert_eq!(output.stderr, "jp: 2 of 4 files would be reformatted\n");
+    // Nothing was written
+    for (name, contents) in files {
+        assert_eq!(fs::read_to_string(dir.join(name)).unwrap(), contents);
+    }
+    assert_eq!(jp(&["--dry-run", messy.to_str().unwrap()], "").code, 2);
+}
+
+#[test]
+fn jp_indent_sets_the_default_indent() {
+    let input = r#"{"a": [1]}"#;
+    let output = jp_with_env(&[], input, &[("JP_INDENT", "2")]);
+    assert_eq!(output.code, 0);
+    assert_eq!(output.stdout, "{\n  \"a\": [\n    1\n  ]\n}\n");
+    let output = jp_with_env(&[], input, &[("JP_INDENT", "tab")]);
+    assert_eq!(output.stdout, "{\n\t\"a\": [\n\t\t1\n\t]\n}\n");
+}
+
+#[test]
+fn indent_flag_overrides_jp_indent() {
+    let input = r#"{"a": 1}"#;
+    let output = jp_with_env(&["--indent", "1"], input, &[("JP_INDENT", "8")]);
+    assert_eq!(output.stdout, "{\n \"a\": 1\n}\n");
+    let output = jp_with_env(&["--indent-string", "\t"], input, &[("JP_INDENT", "8")]);
+    assert_eq!(output.stdout, "{\n\t\"a\": 1\n}\n");
+    let output = jp_with_env(&["--indent", "1"], input, &[("JP_INDENT", "wide")]);
+    assert_eq!(output.code, 2);
+    assert_eq!(output.stderr, "jp: invalid JP_INDENT value: wide\n");
+}
+
+#[test]
+fn fail_on_conflict_names_both_keys() {
+    let base = temp_file(
+        "base.json",
+        "{\n    \"db\": {\"port\": 5432},\n    \"name\": \"app\"\n}",
+    );
+    let same = temp_file("same.json", r#"{"name": "app"}"#);
+    let other = temp_file(
+        "other.json",
+        "{\"db\": {\n  \"host\": \"x\", \"port\": 6543}}",
+    );
+    let (base, same, other) = (
+        base.to_str().unwrap(),
+        same.to_str().unwrap(),
+        other.to_str().unwrap(),
+    );
+    let output = jp(
+        &["--merge", "--fail-on-conflict", "-c", base, same, other],
+        "",
+    );
+    assert_eq!(output.code, 1);
+    assert_eq!(output.stdout, "");
+    assert_eq!(
+        output.stderr,
+        format!(
+            "jp: {}: key /db/port at line 2 column 16 conflicts with {} at line 2 column 12\n",
+            other, base
+        )
+    );
+    // Equal values and nested objects merge as usual
+    let output = jp(&["--merge", "--fail-on-conflict", "-c", base, same], "");
+    assert_eq!(output.code, 0);
+    assert_eq!(output.stdout, "{\"db\":{\"port\":5432},\"name\":\"app\"}\n");
+    let output = jp(&["--merge", "-c", base, other], "");
+    assert_eq!(
+        output.stdout,
+        "{\"db\":{\"port\":6543,\"host\":\"x\"},\"name\":\"app\"}\n"
+    );
+    assert_eq!(jp(&["--fail-on-conflict", base], "").code, 2);
+}
+
+#[test]
+fn pointer_exists_prints_only_with_verbose() {
+    let input = r#"{"a": {"b": [1, 2]}}"#;
+    let output = jp(&["--pointer-exists", "/a/b"], input);
+    assert_eq!(
+        (output.code, output.stdout, output.stderr),
+        (0, "".into(), "".into())
+    );
+    let output = jp(&["--pointer-exists", "/a/b", "-v", "-c"], input);
+    assert_eq!((output.code, output.stdout.as_str()), (0, "[1,2]\n"));
+    let output = jp(&["--pointer-exists", "/a/c", "-v"], input);
+    assert_eq!(
+        (output.code, output.stdout, output.stderr),
+        (1, "".into(), "".into())
+    );
+}
+
+#[test]
+fn error_format_json_describes_each_error() {
+    let output = jp(&["--error-format", "json"], "[1,\n ~]");
+    assert_eq!(output.code, 1);
+    assert_eq!(
+        output.stderr,
+        "{\"error\":\"UnexpectedCharacter\",\"message\":\"Unexpected character: ~, line 2 column 2 (byte 5)\",\"line\":2,\"column\":2}\n"
+    );
+    let path = temp_file("colon.json", r#"{"a" 1}"#);
+    let path = path.to_str().unwrap();
+    let output = jp(&["--validate", "--error-format", "json", path], "");
+    assert_eq!(output.code, 1);
+    assert_eq!(
+        output.stderr,
+        format!(
+            "{{\"error\":\"UnexpectedToken\",\"message\":\"Expected ':' after the object key, found number at line 1 column 6 (byte 5)\",\"line\":1,\"column\":6,\"file\":\"{}\"}}\n",
+            path
+        )
+    );
+    let output = jp(&["--error-format", "json", "--pretty-errors"], "");
+    assert_eq!(
+        output.stderr,
+        "{\"error\":\"EmptyDocument\",\"message\":\"Empty document: expected a JSON value\",\"line\":null,\"column\":null}\n"
+    );
+}
+
+#[test]
+fn preserve_order_keeps_keys_and_refuses_sorting() {
+    let input = r#"{"zeta": 1, "alpha": {"m": 2, "b": 3}}"#;
+    let output = jp(&["--preserve-order", "-c"], input);
+    assert_eq!(output.code, 0);
+    assert_eq!(output.stdout, "{\"zeta\":1,\"alpha\":{\"m\":2,\"b\":3}}\n");
+    // Rebuilding from the parsed value keeps the order too
+    let output = jp(&["--preserve-order", "-c", "--pointer", "/alpha"], input);
+    assert_eq!(output.stdout, "{\"m\":2,\"b\":3}\n");
+    for flags in [
+        &["--sort-keys"][..],
+        &["--sort-keys-by", "length"],
+        &["--canonical"],
+    ] {
+        let output = jp(&[&["--preserve-order"], flags].concat(), input);
+        assert_eq!(output.code, 2, "{:?}", flags);
+        assert_eq!(output.stdout, "");
+        assert!(output
+            .stderr
+            .contains("--preserve-order cannot be combined"));
+    }
+}
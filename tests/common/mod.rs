@@ -0,0 +1,38 @@
+// Helpers shared by the integration tests; not every test file uses all of them
+#![allow(dead_code)]
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+pub struct Output {
+    pub code: i32,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+// Run the built binary with `stdin` piped in
+pub fn jp(args: &[&str], stdin: &str) -> Output {
+    jp_with_env(args, stdin, &[])
+}
+
+// Run with extra environment variables; a JP_INDENT from the caller's shell is
+// ignored either way
+pub fn jp_with_env(args: &[&str], stdin: &str, vars: &[(&str, &str)]) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_jp"))
+        .args(args)
+        .env_remove("JP_INDENT")
+        .envs(vars.iter().copied())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    // jp may exit before reading its input, closing the pipe
+    let _ = child.stdin.take().unwrap().write_all(stdin.as_bytes());
+    let output = child.wait_with_output().unwrap();
+    Output {
+        code: output.status.code().unwrap(),
+        stdout: String::from_utf8(output.stdout).unwrap(),
+        stderr: String::from_utf8(output.stderr).unwrap(),
+    }
+}
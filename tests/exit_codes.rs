@@ -0,0 +1,53 @@
+// Run the built binary and check the exit code for each kind of failure
+mod common;
+
+use common::jp;
+
+#[test]
+fn success_exits_zero() {
+    assert_eq!(jp(&[], r#"{"a": [1, 2]}"#).code, 0);
+    assert_eq!(jp(&["--validate"], "[]").code, 0);
+    assert_eq!(jp(&["--help"], "").code, 0);
+}
+
+#[test]
+fn invalid_json_exits_one() {
+    assert_eq!(jp(&[], r#"{"a": }"#).code, 1);
+    assert_eq!(jp(&["--validate"], "[1,").code, 1);
+    assert_eq!(jp(&["--all-errors"], "[~, ~]").code, 1);
+    assert_eq!(jp(&["--pointer", "/missing"], "{}").code, 1);
+}
+
+#[test]
+fn usage_errors_exit_two() {
+    assert_eq!(jp(&["--no-such-flag"], "{}").code, 2);
+    assert_eq!(jp(&["--style"], "{}").code, 2);
+    assert_eq!(jp(&["--style", "wide"], "{}").code, 2);
+    assert_eq!(jp(&["--write"], "{}").code, 2);
+    assert_eq!(jp(&["--flatten", "--ndjson"], "{}").code, 2);
+    assert_eq!(jp(&["--diff", "only-one.json"], "").code, 2);
+}
+
+#[test]
+fn io_errors_exit_three() {
+    assert_eq!(jp(&["no/such/file.json"], "").code, 3);
+    assert_eq!(jp(&["--validate", "no/such/file.json"], "").code, 3);
+    assert_eq!(jp(&["--output", "no/such/dir/out.json"], "{}").code, 3);
+    assert_eq!(jp(&["--max-size", "2"], "[1]").code, 3);
+}
+
+#[test]
+fn pointer_exists_answers_with_the_exit_code() {
+    let input = r#"{"a": {"b": null}, "c": [1]}"#;
+    assert_eq!(jp(&["--pointer-exists", "/a/b"], input).code, 0);
+    assert_eq!(jp(&["--pointer-exists", "/c/0"], input).code, 0);
+    assert_eq!(jp(&["--pointer-exists", ""], input).code, 0);
+    assert_eq!(jp(&["--pointer-exists", "/a/x"], input).code, 1);
+    assert_eq!(jp(&["--pointer-exists", "/c/1"], input).code, 1);
+    assert_eq!(jp(&["--pointer-exists", "/a"], "{").code, 1);
+    assert_eq!(jp(&["--pointer-exists", "a"], input).code, 2);
+    assert_eq!(
+        jp(&["--pointer-exists", "/a", "no/such/file.json"], "").code,
+        3
+    );
+}